@@ -44,10 +44,35 @@ pub fn main() {
 
     let mut a = BitFieldVec::<usize>::new(args.width, 1 << args.log2_size);
     let mask = (1 << args.log2_size) - 1;
+    let value_mask = if args.width == 0 {
+        0
+    } else {
+        usize::MAX >> (usize::BITS as usize - args.width)
+    };
 
     let mut pl = ProgressLogger::default();
     let mut u = 0;
 
+    pl.item_name("value");
+    pl.start("Building (serial)...");
+    for i in 0..a.len() {
+        unsafe { a.set_unchecked(i, i & value_mask) };
+    }
+    pl.done_with_count(a.len());
+
+    pl.start("Building (parallel)...");
+    let b = BitFieldVec::<usize>::from_indexed_parallel(args.width, 1 << args.log2_size, |i| {
+        i & value_mask
+    });
+    pl.done_with_count(b.len());
+    black_box(b);
+
+    let values = (0..a.len()).map(|i| i & value_mask).collect::<Vec<_>>();
+    pl.start("Building (bulk copy)...");
+    a.copy_from_slice(0, &values);
+    pl.done_with_count(a.len());
+    drop(values);
+
     for _ in 0..args.repeats {
         let mut rand = SmallRng::seed_from_u64(0);
         pl.item_name("write");
@@ -0,0 +1,92 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use clap::Parser;
+use dsi_progress_logger::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use std::hint::black_box;
+use std::io::prelude::*;
+use sux::prelude::*;
+
+#[derive(Parser, Debug)]
+#[command(about = "Benchmarks construction and access of Rice-coded lists", long_about = None)]
+struct Args {
+    /// The file to read; every line must contain an integer, and integers
+    /// must be nondecreasing.
+    file_path: String,
+
+    #[arg(short, long, default_value = "32")]
+    /// Store explicitly every value with index multiple of k.
+    k: usize,
+
+    #[arg(short, long)]
+    /// The base-2 logarithm of the Rice parameter (default: chosen from the average gap).
+    log2_b: Option<usize>,
+
+    #[arg(short, long, default_value = "10000")]
+    /// How many iterations of random access speed test
+    accesses: usize,
+}
+
+pub fn main() {
+    stderrlog::new()
+        .verbosity(2)
+        .timestamp(stderrlog::Timestamp::Second)
+        .init()
+        .unwrap();
+
+    let args = Args::parse();
+
+    let mut rlb = RiceListBuilder::new(args.k);
+    if let Some(log2_b) = args.log2_b {
+        rlb = rlb.log2_b(log2_b);
+    }
+    let mut pl = ProgressLogger::default();
+    pl.display_memory(true).item_name("value");
+
+    let lines = std::io::BufReader::new(std::fs::File::open(&args.file_path).unwrap())
+        .lines()
+        .map(|line| line.unwrap());
+
+    pl.start("Inserting...");
+    for line in lines {
+        rlb.push(line.trim().parse().unwrap()).unwrap();
+        pl.light_update();
+    }
+
+    pl.done();
+
+    let rl = rlb.build();
+    rl.print_stats();
+
+    let mut rand = SmallRng::seed_from_u64(0);
+
+    let start = std::time::Instant::now();
+    for _ in 0..args.accesses {
+        let i = rand.gen::<usize>() % rl.len();
+        let _ = black_box(rl.get(i));
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "avg_rnd_access_speed: {} ns/access",
+        elapsed.as_nanos() as f64 / args.accesses as f64
+    );
+
+    let max = rl.get(rl.len() - 1);
+    let start = std::time::Instant::now();
+    for _ in 0..args.accesses {
+        let x = rand.gen_range(0..=max);
+        let _ = black_box(rl.succ(&x));
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "avg_rnd_succ_speed: {} ns/succ",
+        elapsed.as_nanos() as f64 / args.accesses as f64
+    );
+}
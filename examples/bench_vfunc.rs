@@ -10,6 +10,8 @@ use clap::Parser;
 use dsi_progress_logger::*;
 use epserde::prelude::*;
 use sux::func::VFunc;
+use sux::traits::BitFieldSlice;
+use sux::utils::ToSig;
 
 #[derive(Parser, Debug)]
 #[command(about = "Benchmark VFunc with strings or 64-bit integers", long_about = None)]
@@ -24,6 +26,25 @@ struct Args {
     func: String,
 }
 
+fn bench_batch<T: ToSig + Sync, S: BitFieldSlice<usize> + Sync>(
+    func: &VFunc<T, usize, S>,
+    keys: &[T],
+    pl: &mut ProgressLogger,
+) {
+    let mut out = vec![0; keys.len()];
+
+    pl.start("Querying (batch)...");
+    func.get_batch(keys, &mut out);
+    pl.done_with_count(keys.len());
+    assert!(out.iter().copied().eq(0..keys.len()));
+
+    out.fill(0);
+    pl.start("Querying (parallel batch)...");
+    func.par_get_batch(keys, &mut out);
+    pl.done_with_count(keys.len());
+    assert!(out.iter().copied().eq(0..keys.len()));
+}
+
 fn main() -> Result<()> {
     stderrlog::new()
         .verbosity(2)
@@ -46,6 +67,8 @@ fn main() -> Result<()> {
             assert_eq!(i, func.get(key));
         }
         pl.done_with_count(keys.len());
+
+        bench_batch(&*func, &keys, &mut pl);
     } else {
         let func = VFunc::<_>::load_mem(&args.func)?;
         pl.start("Querying...");
@@ -53,6 +76,8 @@ fn main() -> Result<()> {
             assert_eq!(i, func.get(&i));
         }
         pl.done_with_count(args.n);
+
+        bench_batch(&*func, &(0..args.n).collect::<Vec<_>>(), &mut pl);
     }
 
     Ok(())
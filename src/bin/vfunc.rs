@@ -5,16 +5,32 @@
  * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
  */
 
-use anyhow::Result;
-use clap::{ArgGroup, Parser};
+use anyhow::{anyhow, bail, Result};
+use clap::{ArgGroup, Parser, ValueEnum};
 use dsi_progress_logger::*;
 use epserde::ser::Serialize;
+use std::cell::RefCell;
+use std::time::Instant;
+use sux::func::{BuildStats, Phase};
 use sux::prelude::VFuncBuilder;
-use sux::utils::file::FilenameIntoIterator;
-use sux::utils::FilenameZstdIntoIterator;
+use sux::utils::file::FileLines;
+use sux::utils::{FilenameAutoIntoIterator, FilenameGzipIntoIterator};
+
+/// The compression format of the file containing the keys.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Compression {
+    /// Detect the format from the magic bytes at the start of the file.
+    Auto,
+    /// The file is not compressed.
+    None,
+    /// The file is compressed with zstd.
+    Zstd,
+    /// The file is compressed with gzip.
+    Gzip,
+}
 
 #[derive(Parser, Debug)]
-#[command(about = "Generate a VFunc mapping each input to its rank and serialize it with ε-serde", long_about = None)]
+#[command(about = "Generate a VFunc mapping each input to its rank, or to a value read from a file, and serialize it with ε-serde", long_about = None)]
 #[clap(group(
             ArgGroup::new("input")
                 .required(true)
@@ -32,15 +48,90 @@ struct Args {
     threads: Option<usize>,
     /// A name for the ε-serde serialized function.
     func: String,
-    /// The filename containing the keys is compressed with zstd.
-    #[arg(short, long)]
-    zstd: bool,
-    /// Use disk-based buckets to reduce memory usage at construction time.
+    /// The compression format of the file containing the keys.
+    #[arg(long, value_enum, default_value_t = Compression::Auto)]
+    compression: Compression,
+    /// Use disk-based buckets to reduce memory usage at construction time; keys are read in a single pass and never kept in memory.
     #[arg(short, long)]
     offline: bool,
     /// The number of high bits defining the number of buckets. Very large key sets may benefit from a larger number of buckets.
     #[arg(short, long, default_value_t = 8)]
     high_bits: u32,
+    /// A file containing unsigned integer values, one per line, to be associated with the keys in the same order; the function will use the minimum bit width sufficient to represent all values. If not specified, each key is mapped to its rank.
+    #[arg(long)]
+    values: Option<String>,
+    /// Print the parameters used to build the function (e.g., the seed), its size, and the time spent in each phase of the construction.
+    #[arg(long)]
+    stats: bool,
+}
+
+/// Read the values associated with the keys, one per line.
+fn read_values(filename: &str) -> Result<Vec<usize>> {
+    FileLines::plain(filename)
+        .enumerate()
+        .map(|(i, line)| {
+            line.map_err(anyhow::Error::from)
+                .and_then(|line| Ok(line.trim().parse::<usize>()?))
+                .map_err(|e| anyhow!("Line {} of values file {}: {}", i + 1, filename, e))
+        })
+        .collect()
+}
+
+/// The lines of a file containing keys.
+///
+/// Iteration stops at the first I/O error, which is recorded in `error` and
+/// must be checked after using the keys.
+#[derive(Clone, Copy)]
+struct Keys<'a> {
+    filename: &'a str,
+    compression: Compression,
+    error: &'a RefCell<Option<std::io::Error>>,
+}
+
+impl<'a> IntoIterator for Keys<'a> {
+    type Item = String;
+    type IntoIter = Box<dyn Iterator<Item = String> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let lines: Box<dyn Iterator<Item = std::io::Result<String>>> = match self.compression {
+            Compression::Auto => Box::new(FilenameAutoIntoIterator(self.filename).into_iter()),
+            Compression::None => Box::new(FileLines::plain(self.filename)),
+            Compression::Zstd => Box::new(FileLines::zstd(self.filename)),
+            Compression::Gzip => Box::new(FilenameGzipIntoIterator(self.filename).into_iter()),
+        };
+        let error = self.error;
+        Box::new(lines.map_while(move |line| line.map_err(|e| *error.borrow_mut() = Some(e)).ok()))
+    }
+}
+
+impl Keys<'_> {
+    /// Return an error if reading the keys failed.
+    fn check(&self) -> Result<()> {
+        match self.error.borrow_mut().take() {
+            Some(e) => Err(anyhow!("Error reading keys from {}: {}", self.filename, e)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Check that the number of values is equal to the number of keys.
+fn check_len(values: &[usize], num_keys: usize) -> Result<()> {
+    if values.len() != num_keys {
+        bail!(
+            "The values file contains {} lines, but there are {} keys",
+            values.len(),
+            num_keys
+        );
+    }
+    Ok(())
+}
+
+/// Serialize a function, recording the time spent in `stats`.
+fn store(func: &impl Serialize, filename: &str, stats: &mut BuildStats) -> Result<()> {
+    let start = Instant::now();
+    func.store(filename)?;
+    stats.record(Phase::Serialization, start.elapsed());
+    Ok(())
 }
 
 fn main() -> Result<()> {
@@ -55,6 +146,8 @@ fn main() -> Result<()> {
     let mut pl = ProgressLogger::default();
     pl.display_memory(true);
 
+    let values = args.values.as_deref().map(read_values).transpose()?;
+
     if let Some(filename) = args.filename {
         let mut builder = VFuncBuilder::default()
             .offline(args.offline)
@@ -62,12 +155,48 @@ fn main() -> Result<()> {
         if let Some(threads) = args.threads {
             builder = builder.num_threads(threads);
         }
-        let func = if args.zstd {
-            builder.build(FilenameZstdIntoIterator(&filename), &(0_usize..), &mut pl)?
+        let error = RefCell::new(None);
+        let keys = Keys {
+            filename: &filename,
+            compression: args.compression,
+            error: &error,
+        };
+        let (func, mut stats) = if args.offline {
+            // Single pass on the keys
+            if let Some(values) = &values {
+                let mut num_keys = 0;
+                let func = builder.build_from_stream_with_stats(
+                    keys.into_iter()
+                        .inspect(|_| num_keys += 1)
+                        .zip(values.iter().copied().chain(std::iter::repeat(0))),
+                    &mut pl,
+                );
+                keys.check()?;
+                let func = func?;
+                check_len(values, num_keys)?;
+                func
+            } else {
+                let func = builder.build_from_stream_with_stats(keys.into_iter().zip(0..), &mut pl);
+                keys.check()?;
+                func?
+            }
+        } else if let Some(values) = &values {
+            let num_keys = keys.into_iter().count();
+            keys.check()?;
+            check_len(values, num_keys)?;
+            let func = builder.build_with_stats(keys, values, &mut pl);
+            keys.check()?;
+            func?
         } else {
-            builder.build(FilenameIntoIterator(&filename), &(0..), &mut pl)?
+            let func = builder.build_with_stats(keys, &(0_usize..), &mut pl);
+            keys.check()?;
+            func?
         };
-        func.store(&args.func)?;
+        store(&func, &args.func, &mut stats)?;
+        if args.stats {
+            func.print_stats();
+            stats.print();
+        }
     }
 
     if let Some(n) = args.n {
@@ -77,9 +206,17 @@ fn main() -> Result<()> {
         if let Some(threads) = args.threads {
             builder = builder.num_threads(threads);
         }
-        let func = builder.build(0..n, &(0_usize..), &mut pl)?;
-
-        func.store(&args.func)?;
+        let (func, mut stats) = if let Some(values) = &values {
+            check_len(values, n)?;
+            builder.build_with_stats(0..n, values, &mut pl)?
+        } else {
+            builder.build_with_stats(0..n, &(0_usize..), &mut pl)?
+        };
+        store(&func, &args.func, &mut stats)?;
+        if args.stats {
+            func.print_stats();
+            stats.print();
+        }
     }
     Ok(())
 }
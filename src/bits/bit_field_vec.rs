@@ -44,19 +44,44 @@ for [Elias-Fano](crate::dict::elias_fano::EliasFano).
 
 ## Low-level support
 
+The method [`as_words`](BitFieldVec::as_words) provides a read-only view of
+the storage words, for example to pass the packed data to a foreign library;
+its documentation describes the exact packing, and
+[`word_len`](BitFieldVec::word_len) returns the number of words containing
+values.
+
 The methods [`address_of`](BitFieldVec::address_of)
 and [`get_unaligned`](BitFieldVec::get_unaligned) can be used to manually
 prefetch parts of the data structure, or read values using unaligned
 read, when the bit width makes it possible.
+
+The methods [`to_bytes`](BitFieldVec::to_bytes) and
+[`from_bytes`](BitFieldVec::from_bytes) provide a portable byte format
+that stores explicitly the length in bits of the underlying bit stream. Using
+[`truncate_bits`](BitFieldVec::truncate_bits) it is possible to represent
+bit streams whose length is not a multiple of the bit width, for
+interoperability with bit-granular formats.
+
+## Construction
+
+Besides [`BitFieldVec::new`], which creates a vector of zeroes, vectors
+can be built incrementally using a [`BitFieldVecBuilder`], which can
+either use a fixed bit width or infer it from the values.
 */
 
 use crate::prelude::*;
 use crate::traits::bit_field_slice::*;
+use alloc::vec;
+use alloc::vec::Vec;
 use anyhow::{bail, Result};
 use common_traits::*;
+use core::sync::atomic::*;
+#[cfg(feature = "std")]
 use epserde::*;
-use std::sync::atomic::*;
-#[derive(Epserde, Debug, Clone, Hash)]
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg_attr(feature = "std", derive(Epserde))]
+#[derive(Debug, Clone, Hash)]
 
 /// A vector of bit fields of fixed width.
 pub struct BitFieldVec<W: Word = usize, B = Vec<W>> {
@@ -70,7 +95,8 @@ pub struct BitFieldVec<W: Word = usize, B = Vec<W>> {
     len: usize,
 }
 
-#[derive(Epserde, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "std", derive(Epserde))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 /// A tentatively thread-safe vector of bit fields of fixed width.
 pub struct AtomicBitFieldVec<W: Word + IntoAtomic = usize, B = Vec<<W as IntoAtomic>::AtomicType>> {
     /// The underlying storage.
@@ -103,6 +129,21 @@ impl<W: Word> BitFieldVec<W, Vec<W>> {
         }
     }
 
+    /// Create a new vector of bit width `W::BITS` using `data` as backend.
+    ///
+    /// The resulting vector is semantically equivalent to `data`: its length
+    /// is `data.len()` and its `i`-th value is `data[i]`. No copy is performed,
+    /// and values never cross word boundaries, so [`get`](BitFieldSlice::get) and
+    /// [`set`](BitFieldSliceMut::set) always access a single word.
+    pub fn from_full_width_vec(data: Vec<W>) -> Self {
+        Self {
+            len: data.len(),
+            data,
+            bit_width: W::BITS,
+            mask: W::MAX,
+        }
+    }
+
     /// Create a new vector by copying a slice; the bit width will be the minimum
     /// width sufficient to hold all values in the slice.
     ///
@@ -121,7 +162,7 @@ impl<W: Word> BitFieldVec<W, Vec<W>> {
             bail!(
                 "Cannot convert a slice of bit width {} into a slice with W = {}",
                 max_len,
-                std::any::type_name::<W>()
+                core::any::type_name::<W>()
             );
         }
         let mut result = Self::new(max_len, slice.len());
@@ -132,10 +173,15 @@ impl<W: Word> BitFieldVec<W, Vec<W>> {
         Ok(result)
     }
 
+    /// Append a value to the vector, growing the backend if necessary.
+    ///
+    /// # Panic
+    /// Panics if the value does not fit in the bit width.
     pub fn push(&mut self, value: W) {
         panic_if_value!(value, self.mask, self.bit_width);
-        if (self.len + 1) * self.bit_width > self.data.len() * W::BITS {
-            self.data.push(W::ZERO);
+        let n_of_words = ((self.len + 1) * self.bit_width + W::BITS - 1) / W::BITS;
+        if n_of_words > self.data.len() {
+            self.data.resize(n_of_words, W::ZERO);
         }
         unsafe {
             self.set_unchecked(self.len, value);
@@ -143,12 +189,137 @@ impl<W: Word> BitFieldVec<W, Vec<W>> {
         self.len += 1;
     }
 
+    /// Remove the last value from the vector and return it, or `None` if
+    /// the vector is empty.
+    ///
+    /// The backend is not shrunk.
+    pub fn pop(&mut self) -> Option<W> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // SAFETY: the index was within bounds before decrementing len
+        Some(unsafe { self.get_unchecked(self.len) })
+    }
+
+    /// Append a value to the vector, returning an error if the value does
+    /// not fit in the bit width.
+    pub fn checked_push(&mut self, value: W) -> Result<()> {
+        if value & self.mask != value {
+            bail!("Value {} does not fit in {} bits", value, self.bit_width);
+        }
+        self.push(value);
+        Ok(())
+    }
+
+    /// Create a new vector of the given bit width containing the values
+    /// returned by an iterator.
+    ///
+    /// # Panic
+    /// Panics if a value does not fit in the bit width.
+    pub fn from_iter_with_width(bit_width: usize, iter: impl IntoIterator<Item = W>) -> Self {
+        let iter = iter.into_iter();
+        let mut result = Self::new(bit_width, 0);
+        result
+            .data
+            .reserve((iter.size_hint().0 * bit_width + W::BITS - 1) / W::BITS);
+        result.extend(iter);
+        result
+    }
+
+    /// Create a new vector by copying a slice of values; the bit width will
+    /// be the minimum width sufficient to hold all values (zero if the slice
+    /// is empty or contains only zeroes).
+    pub fn from_values(values: &[W]) -> Self {
+        let max = values.iter().copied().fold(W::ZERO, Ord::max);
+        let bit_width = if max == W::ZERO {
+            0
+        } else {
+            max.ilog2() as usize + 1
+        };
+        Self::from_iter_with_width(bit_width, values.iter().copied())
+    }
+
+    /// Create a new vector of the given bit width and length whose `i`-th
+    /// value is `f(i)`.
+    ///
+    /// If the feature "rayon" is enabled, this function is parallelized: the
+    /// backend is split into chunks starting at a value whose bit offset is a
+    /// multiple of `W::BITS`, so that no value crosses the boundary between
+    /// two chunks, and the chunks are filled independently.
+    ///
+    /// # Panic
+    /// Panics if a value does not fit in the bit width.
+    pub fn from_indexed_parallel(
+        bit_width: usize,
+        len: usize,
+        f: impl Fn(usize) -> W + Sync,
+    ) -> Self {
+        let mut result = Self::new(bit_width, len);
+        if bit_width == 0 {
+            // Just check the values
+            result.apply_in_place(|index, _| f(index));
+            return result;
+        }
+
+        // The smallest number of values whose bits fill exactly a number of
+        // words
+        let gcd = {
+            let (mut a, mut b) = (bit_width, W::BITS);
+            while b != 0 {
+                (a, b) = (b, a % b);
+            }
+            a
+        };
+        let values_per_period = W::BITS / gcd;
+        let words_per_period = bit_width / gcd;
+        // Chunks of approximately 2^16 words
+        let periods_per_chunk = Ord::max(1, (1 << 16) / words_per_period);
+        let values_per_chunk = values_per_period * periods_per_chunk;
+        let words_per_chunk = words_per_period * periods_per_chunk;
+
+        let fill = |(chunk_index, chunk): (usize, &mut [W])| {
+            let start = chunk_index * values_per_chunk;
+            let chunk_len = Ord::min(values_per_chunk, len.saturating_sub(start));
+            // SAFETY: the chunk contains at least chunk_len * bit_width bits
+            let mut chunk = unsafe { BitFieldVec::from_raw_parts(chunk, bit_width, chunk_len) };
+            chunk.apply_in_place(|index, _| f(start + index));
+        };
+
+        #[cfg(feature = "rayon")]
+        {
+            result
+                .data
+                .par_chunks_mut(words_per_chunk)
+                .enumerate()
+                .for_each(fill);
+        }
+
+        #[cfg(not(feature = "rayon"))]
+        {
+            result
+                .data
+                .chunks_mut(words_per_chunk)
+                .enumerate()
+                .for_each(fill);
+        }
+
+        result
+    }
+
     pub fn extend(&mut self, i: impl IntoIterator<Item = W>) {
         for value in i {
             self.push(value);
         }
     }
 
+    /// Resize the vector in place so that its length is `new_len`, with the
+    /// same semantics of [`Vec::resize`]: if `new_len` is greater than the
+    /// current length, the vector is extended by copies of `value`;
+    /// otherwise, it is truncated.
+    ///
+    /// # Panic
+    /// Panics if the value does not fit in the bit width.
     pub fn resize(&mut self, new_len: usize, value: W) {
         panic_if_value!(value, self.mask, self.bit_width);
         if new_len > self.len {
@@ -164,9 +335,174 @@ impl<W: Word> BitFieldVec<W, Vec<W>> {
         }
         self.len = new_len;
     }
+
+    /// Keep only the values at the positions that are set in `keep`,
+    /// moving them forward and truncating the vector to the
+    /// number of ones in `keep`.
+    ///
+    /// This is the standard operation of applying a deletion mask
+    /// to a column: the positions to retain are enumerated using
+    /// [`BitVec::ones`], so runs of deleted positions are skipped
+    /// efficiently.
+    ///
+    /// # Panic
+    /// Panics if the length of `keep` is different from the length of the vector.
+    pub fn compact<C: AsRef<[usize]>>(&mut self, keep: &BitVec<C>) {
+        assert_eq!(
+            keep.len(),
+            self.len,
+            "The length of the mask ({}) is different from the length of the vector ({})",
+            keep.len(),
+            self.len
+        );
+        let mut write = 0;
+        for read in keep.ones() {
+            debug_assert!(read >= write);
+            if read != write {
+                unsafe {
+                    let value = self.get_unchecked(read);
+                    self.set_unchecked(write, value);
+                }
+            }
+            write += 1;
+        }
+        self.len = write;
+    }
+
+    /// Create a new vector from a raw little-endian bit stream of `bit_len` bits.
+    ///
+    /// The length of the vector will be `bit_len` divided by `bit_width`, rounded
+    /// up: if `bit_len` is not a multiple of `bit_width`, the last value is
+    /// partial, and its missing high bits are set to zero. Bits of `data`
+    /// beyond `bit_len` are cleared.
+    ///
+    /// Returns an error if the bit width is larger than `W::BITS`, if
+    /// `data` contains less than `bit_len` bits, or if the bit width is zero
+    /// but `bit_len` is not.
+    pub fn from_bit_len(mut data: Vec<W>, bit_width: usize, bit_len: usize) -> Result<Self> {
+        if bit_width > W::BITS {
+            bail!(
+                "Bit width {} is larger than the number of bits of {}",
+                bit_width,
+                core::any::type_name::<W>()
+            );
+        }
+        if bit_len > data.len() * W::BITS {
+            bail!(
+                "Bit length {} is larger than the number of available bits ({})",
+                bit_len,
+                data.len() * W::BITS
+            );
+        }
+        if bit_width == 0 && bit_len != 0 {
+            bail!("Bit length {} is not zero but bit width is zero", bit_len);
+        }
+
+        let len = if bit_width == 0 {
+            0
+        } else {
+            (bit_len + bit_width - 1) / bit_width
+        };
+        // Clear the bits beyond bit_len
+        let n_of_words = (bit_len + W::BITS - 1) / W::BITS;
+        data.truncate(n_of_words);
+        if bit_len % W::BITS != 0 {
+            data[n_of_words - 1] &= mask(bit_len % W::BITS);
+        }
+        // Make room for the missing bits of the last value
+        data.resize(
+            Ord::max(1, (len * bit_width + W::BITS - 1) / W::BITS),
+            W::ZERO,
+        );
+
+        Ok(Self {
+            data,
+            bit_width,
+            mask: mask(bit_width),
+            len,
+        })
+    }
+
+    /// Deserialize a vector from the format written by
+    /// [`to_bytes`](BitFieldVec::to_bytes) or
+    /// [`truncate_bits`](BitFieldVec::truncate_bits), returning the vector
+    /// and the bit length stored in the format.
+    ///
+    /// The vector is built by [`from_bit_len`](BitFieldVec::from_bit_len).
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize)>
+    where
+        W: FromBytes,
+    {
+        if bytes.len() < 16 {
+            bail!("Byte format is too short ({} bytes)", bytes.len());
+        }
+        let bit_width = u64::from_le_bytes(bytes[0..8].try_into().unwrap()) as usize;
+        let bit_len = u64::from_le_bytes(bytes[8..16].try_into().unwrap()) as usize;
+        let payload = &bytes[16..];
+        if payload.len() != bit_len.div_ceil(8) {
+            bail!(
+                "Expected {} bytes of payload for bit length {}, found {}",
+                bit_len.div_ceil(8),
+                bit_len,
+                payload.len()
+            );
+        }
+
+        let data = payload
+            .chunks(W::BYTES)
+            .map(|chunk| {
+                let mut word_bytes = W::Bytes::default();
+                word_bytes.as_mut()[..chunk.len()].copy_from_slice(chunk);
+                W::from_le_bytes(word_bytes)
+            })
+            .collect();
+
+        Ok((Self::from_bit_len(data, bit_width, bit_len)?, bit_len))
+    }
 }
 
 impl<W: Word, B: AsRef<[W]>> BitFieldVec<W, B> {
+    /// Return the value at the specified index, or `None` if the index
+    /// is out of bounds.
+    #[inline]
+    pub fn checked_get(&self, index: usize) -> Option<W> {
+        if index < self.len {
+            Some(unsafe { self.get_unchecked(index) })
+        } else {
+            None
+        }
+    }
+
+    /// Return a view of the underlying storage words.
+    ///
+    /// This method exposes the storage layout, not the logical values:
+    /// values are packed contiguously starting from the lowest bit of the
+    /// first word, so the value of index `i` occupies the `bit_width` bits
+    /// starting at bit position `i` &middot; `bit_width`, where bit position
+    /// `p` is bit `p % W::BITS` (counting from the least significant bit)
+    /// of word `p / W::BITS`. A value crossing a word boundary has its lower
+    /// bits in the upper part of a word and its upper bits in the lower part
+    /// of the following word.
+    ///
+    /// Only the first [`word_len`](BitFieldVec::word_len) words contain
+    /// values; the storage might contain further words (e.g., after
+    /// [`pop`](BitFieldVec::pop)), and the content of the bits following the last value
+    /// is unspecified.
+    #[inline(always)]
+    pub fn as_words(&self) -> &[W] {
+        self.data.as_ref()
+    }
+
+    /// Return the number of storage words containing values, that is,
+    /// the number of bits of the values, `len` &middot; `bit_width`, divided by
+    /// the number of bits of a word, rounded up.
+    ///
+    /// See [`as_words`](BitFieldVec::as_words) for the storage layout.
+    #[inline(always)]
+    pub fn word_len(&self) -> usize {
+        (self.len * self.bit_width).div_ceil(W::BITS)
+    }
+
     /// Get the address of the item storing (the first part of)
     /// the element of given index.
     ///
@@ -212,6 +548,312 @@ impl<W: Word, B: AsRef<[W]>> BitFieldVec<W, B> {
         let word = core::ptr::read_unaligned(ptr);
         (word >> (index % W::BITS)) & self.mask
     }
+
+    /// Serialize the vector into a portable byte format.
+    ///
+    /// The format contains the bit width and the bit length (which is
+    /// `len` times `bit_width`) as little-endian 64-bit integers, followed
+    /// by the bit stream of the values, as little-endian bytes,
+    /// truncated to the last byte containing a bit of the stream.
+    ///
+    /// See [`truncate_bits`](BitFieldVec::truncate_bits) for writing
+    /// a bit length that is not a multiple of the bit width, and
+    /// [`from_bytes`](BitFieldVec::from_bytes) for deserialization.
+    pub fn to_bytes(&self) -> Vec<u8>
+    where
+        W: ToBytes,
+    {
+        self.truncate_bits(self.len * self.bit_width).unwrap()
+    }
+
+    /// Like [`to_bytes`](BitFieldVec::to_bytes), but the stored bit
+    /// length is `bit_len`, so the last value of the vector is truncated
+    /// to its lowest `bit_len` - (`len` - 1) &middot; `bit_width` bits.
+    ///
+    /// This method makes it possible to faithfully represent
+    /// bit streams whose length is not a multiple of the bit width.
+    ///
+    /// Returns an error if `bit_len` does not end within the last value,
+    /// or if the bits of the last value that would be truncated are not zero.
+    pub fn truncate_bits(&self, bit_len: usize) -> Result<Vec<u8>>
+    where
+        W: ToBytes,
+    {
+        let full_len = self.len * self.bit_width;
+        if bit_len > full_len
+            || (self.len > 0 && self.bit_width > 0 && bit_len <= full_len - self.bit_width)
+        {
+            bail!(
+                "Bit length {} does not end within the last value (bit width {}, length {})",
+                bit_len,
+                self.bit_width,
+                self.len
+            );
+        }
+        if bit_len < full_len {
+            let last = unsafe { self.get_unchecked(self.len - 1) };
+            let shift = bit_len - (full_len - self.bit_width);
+            if last >> shift != W::ZERO {
+                bail!("The last value ({}) does not fit in {} bits", last, shift);
+            }
+        }
+
+        let mut bytes = Vec::with_capacity(16 + (bit_len + 7) / 8);
+        bytes.extend_from_slice(&(self.bit_width as u64).to_le_bytes());
+        bytes.extend_from_slice(&(bit_len as u64).to_le_bytes());
+        for word in self.data.as_ref() {
+            bytes.extend_from_slice((*word).to_le_bytes().as_ref());
+        }
+        bytes.truncate(16 + (bit_len + 7) / 8);
+        // Clear the bits beyond bit_len (e.g., left by truncation)
+        if bit_len % 8 != 0 {
+            *bytes.last_mut().unwrap() &= (1 << (bit_len % 8)) - 1;
+        }
+        Ok(bytes)
+    }
+
+    /// Return a 64-bit checksum of the content of the vector, suitable
+    /// to detect corruption (e.g., of a memory-mapped vector).
+    ///
+    /// The checksum is computed using [SpookyHash](spooky_short) on the
+    /// words of the backend containing the values, rather than on the
+    /// values themselves, so it is much faster than hashing the values one
+    /// by one. Bits beyond the last value are masked out, so vectors with the
+    /// same bit width and the same values have the same checksum,
+    /// independently of the content of the padding and of the length of the
+    /// backend.
+    pub fn checksum(&self) -> u64
+    where
+        W: ToBytes,
+    {
+        const BUFFER_SIZE: usize = 1024;
+        let bit_len = self.len * self.bit_width;
+        let num_words = (bit_len + W::BITS - 1) / W::BITS;
+        let mut seed = spooky_short(
+            [
+                (self.bit_width as u64).to_le_bytes(),
+                (self.len as u64).to_le_bytes(),
+            ]
+            .concat(),
+            0,
+        )[0];
+        let mut buffer = Vec::with_capacity(BUFFER_SIZE);
+        for (i, &word) in self.data.as_ref()[..num_words].iter().enumerate() {
+            let word = if i == num_words - 1 && bit_len % W::BITS != 0 {
+                word & ((W::ONE << (bit_len % W::BITS)) - W::ONE)
+            } else {
+                word
+            };
+            buffer.extend_from_slice(word.to_le_bytes().as_ref());
+            if buffer.len() >= BUFFER_SIZE {
+                seed = spooky_short(&buffer, seed)[0];
+                buffer.clear();
+            }
+        }
+        spooky_short(&buffer, seed)[0]
+    }
+
+    /// Return whether the [checksum](BitFieldVec::checksum) of the vector
+    /// is equal to `expected`.
+    pub fn verify_checksum(&self, expected: u64) -> bool
+    where
+        W: ToBytes,
+    {
+        self.checksum() == expected
+    }
+}
+
+impl<W: Word, B: AsRef<[W]> + AsMut<[W]>> BitFieldVec<W, B> {
+    /// Set the value at the specified index, returning an error if the index
+    /// is out of bounds or if the value does not fit in the bit width.
+    #[inline]
+    pub fn checked_set(&mut self, index: usize, value: W) -> Result<()> {
+        if index >= self.len {
+            bail!("Index out of bounds: {} >= {}", index, self.len);
+        }
+        if value & self.mask != value {
+            bail!("Value {} does not fit in {} bits", value, self.bit_width);
+        }
+        unsafe { self.set_unchecked(index, value) };
+        Ok(())
+    }
+
+    /// Move all values satisfying `pred` before all values that do not
+    /// satisfy it, returning the number of values satisfying `pred`.
+    ///
+    /// The relative order of the values is not preserved: see
+    /// [`stable_partition`](BitFieldVec::stable_partition) for a variant
+    /// preserving it. The predicate is called exactly once on each value.
+    pub fn partition(&mut self, pred: impl Fn(W) -> bool) -> usize {
+        let mut front = 0;
+        let mut back = self.len;
+        loop {
+            // Invariant: all values before front satisfy pred, and all
+            // values from back onwards do not
+            while front < back && pred(unsafe { self.get_unchecked(front) }) {
+                front += 1;
+            }
+            // Now either front == back or the value at front does not satisfy pred
+            while front + 1 < back && !pred(unsafe { self.get_unchecked(back - 1) }) {
+                back -= 1;
+            }
+            if front + 1 >= back {
+                return front;
+            }
+            // The value at back - 1 satisfies pred
+            back -= 1;
+            unsafe {
+                let value = self.get_unchecked(front);
+                self.set_unchecked(front, self.get_unchecked(back));
+                self.set_unchecked(back, value);
+            }
+            front += 1;
+        }
+    }
+
+    /// Move all values satisfying `pred` before all values that do not
+    /// satisfy it, preserving the relative order of the values in both
+    /// groups, and return the number of values satisfying `pred`.
+    ///
+    /// Values not satisfying `pred` are buffered in a temporary vector
+    /// before being written back at the end. The predicate is called
+    /// exactly once on each value.
+    pub fn stable_partition(&mut self, pred: impl Fn(W) -> bool) -> usize {
+        let mut rejected = Vec::new();
+        let mut write = 0;
+        for read in 0..self.len {
+            let value = unsafe { self.get_unchecked(read) };
+            if pred(value) {
+                if read != write {
+                    unsafe { self.set_unchecked(write, value) };
+                }
+                write += 1;
+            } else {
+                rejected.push(value);
+            }
+        }
+        for (i, value) in rejected.into_iter().enumerate() {
+            unsafe { self.set_unchecked(write + i, value) };
+        }
+        write
+    }
+
+    /// Replace each value with the result of applying `f` to its index and
+    /// to the value itself.
+    ///
+    /// The vector is scanned once, keeping track of the current word and bit
+    /// position, so this method is much faster than a loop calling
+    /// [`get`](BitFieldSlice::get) and [`set`](BitFieldSliceMut::set) on each
+    /// index.
+    ///
+    /// # Panics
+    /// If `f` returns a value that does not fit in
+    /// [`bit_width`](BitFieldSliceCore::bit_width) bits. In this case, the
+    /// values preceding the offending one have already been replaced.
+    pub fn apply_in_place(&mut self, mut f: impl FnMut(usize, W) -> W) {
+        let bit_width = self.bit_width;
+        let mask = self.mask;
+        let len = self.len;
+        if bit_width == 0 {
+            for index in 0..len {
+                let value = f(index, W::ZERO);
+                panic_if_value!(value, mask, bit_width);
+            }
+            return;
+        }
+        let data = self.data.as_mut();
+        let mut word_index = 0;
+        let mut bit_index = 0;
+        for index in 0..len {
+            // SAFETY: the backend contains at least len * bit_width bits
+            unsafe {
+                if bit_index + bit_width <= W::BITS {
+                    let word = *<[W]>::get_unchecked(data, word_index);
+                    let value = f(index, (word >> bit_index) & mask);
+                    panic_if_value!(value, mask, bit_width);
+                    *data.get_unchecked_mut(word_index) =
+                        word & !(mask << bit_index) | value << bit_index;
+                } else {
+                    let low = *<[W]>::get_unchecked(data, word_index);
+                    let high = *<[W]>::get_unchecked(data, word_index + 1);
+                    let value = f(
+                        index,
+                        (low >> bit_index | high << (W::BITS - bit_index)) & mask,
+                    );
+                    panic_if_value!(value, mask, bit_width);
+                    *data.get_unchecked_mut(word_index) =
+                        low & ((W::ONE << bit_index) - W::ONE) | value << bit_index;
+                    *data.get_unchecked_mut(word_index + 1) =
+                        high & !(mask >> (W::BITS - bit_index)) | value >> (W::BITS - bit_index);
+                }
+            }
+            bit_index += bit_width;
+            word_index += bit_index / W::BITS;
+            bit_index %= W::BITS;
+        }
+    }
+
+    /// Overwrite the `src.len()` consecutive values starting at `from` with
+    /// the values in `src`.
+    ///
+    /// The result is the same as calling [`set`](BitFieldSliceMut::set) on
+    /// each index, but values are accumulated in a word that is written
+    /// only when full, so each underlying word is written once, and only the
+    /// first and the last word are read. Bounds and values are checked once,
+    /// before modifying the vector.
+    ///
+    /// # Panic
+    /// Panics if `from + src.len()` is greater than the length of the vector,
+    /// or if a value in `src` does not fit in
+    /// [`bit_width`](BitFieldSliceCore::bit_width) bits.
+    pub fn copy_from_slice(&mut self, from: usize, src: &[W]) {
+        let len = src.len();
+        if from > self.len || len > self.len - from {
+            panic!(
+                "Range out of bounds: {}..{} (length {})",
+                from,
+                from.saturating_add(len),
+                self.len
+            );
+        }
+        let bit_width = self.bit_width;
+        let mask = self.mask;
+        for &value in src {
+            panic_if_value!(value, mask, bit_width);
+        }
+        if bit_width == 0 || len == 0 {
+            return;
+        }
+
+        let data = self.data.as_mut();
+        let pos = from * bit_width;
+        let mut word_index = pos / W::BITS;
+        // The number of valid (lower) bits in the accumulator
+        let mut fill = pos % W::BITS;
+        // SAFETY: the range has been checked above, so all accessed words
+        // contain at least one bit of the range
+        unsafe {
+            let mut acc = *<[W]>::get_unchecked(data, word_index) & ((W::ONE << fill) - W::ONE);
+            for &value in src {
+                acc |= value << fill;
+                fill += bit_width;
+                if fill >= W::BITS {
+                    *data.get_unchecked_mut(word_index) = acc;
+                    word_index += 1;
+                    fill -= W::BITS;
+                    acc = if fill == 0 {
+                        W::ZERO
+                    } else {
+                        value >> (bit_width - fill)
+                    };
+                }
+            }
+            if fill != 0 {
+                let word = *<[W]>::get_unchecked(data, word_index);
+                *data.get_unchecked_mut(word_index) = acc | word & !((W::ONE << fill) - W::ONE);
+            }
+        }
+    }
 }
 
 impl<W: Word + IntoAtomic> AtomicBitFieldVec<W> {
@@ -229,6 +871,146 @@ impl<W: Word + IntoAtomic> AtomicBitFieldVec<W> {
     }
 }
 
+/// A builder for [`BitFieldVec`].
+///
+/// The builder has two modes:
+/// - if created with [`BitFieldVecBuilder::new`], values are buffered in a
+///   `Vec<W>`, and [`BitFieldVecBuilder::build`] packs them using the
+///   minimum bit width sufficient to hold all of them;
+/// - if created with [`BitFieldVecBuilder::with_width`], the bit width is
+///   fixed, values are packed as they are pushed, and
+///   [`BitFieldVecBuilder::push`] returns an error if a value does not fit.
+///
+/// In both cases, after creating an instance, you can use
+/// [`BitFieldVecBuilder::push`] to add new values. For example,
+/// ```rust
+/// use sux::prelude::*;
+/// let mut builder = BitFieldVecBuilder::<usize>::new();
+/// builder.push(5).unwrap();
+/// builder.push(2).unwrap();
+/// let bfv = builder.build();
+/// assert_eq!(bfv.bit_width(), 3);
+/// assert_eq!(bfv.get(0), 5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BitFieldVecBuilder<W: Word = usize> {
+    mode: BuilderMode<W>,
+}
+
+#[derive(Debug, Clone)]
+enum BuilderMode<W: Word> {
+    /// Values are buffered, and the bit width is inferred.
+    Infer(Vec<W>),
+    /// Values are packed with a fixed bit width.
+    Fixed(BitFieldVec<W>),
+}
+
+impl<W: Word> BitFieldVecBuilder<W> {
+    /// Create a builder inferring the bit width from the pushed values.
+    pub fn new() -> Self {
+        Self {
+            mode: BuilderMode::Infer(Vec::new()),
+        }
+    }
+
+    /// Create a builder using the given bit width.
+    ///
+    /// # Panic
+    /// Panics if `bit_width` is larger than `W::BITS`.
+    pub fn with_width(bit_width: usize) -> Self {
+        assert!(
+            bit_width <= W::BITS,
+            "Bit width {} is larger than the number of bits of {}",
+            bit_width,
+            core::any::type_name::<W>()
+        );
+        Self {
+            mode: BuilderMode::Fixed(BitFieldVec::new(bit_width, 0)),
+        }
+    }
+
+    /// Reserve capacity for at least `additional` more values.
+    pub fn reserve(&mut self, additional: usize) {
+        match &mut self.mode {
+            BuilderMode::Infer(values) => values.reserve(additional),
+            BuilderMode::Fixed(bfv) => {
+                let n_of_words = ((bfv.len + additional) * bfv.bit_width + W::BITS - 1) / W::BITS;
+                bfv.data.reserve(n_of_words.saturating_sub(bfv.data.len()));
+            }
+        }
+    }
+
+    /// Add a new value to the builder.
+    ///
+    /// Returns an error if the bit width is fixed and the value does not fit
+    /// in it.
+    pub fn push(&mut self, value: W) -> Result<()> {
+        match &mut self.mode {
+            BuilderMode::Infer(values) => values.push(value),
+            BuilderMode::Fixed(bfv) => {
+                if value & !bfv.mask != W::ZERO {
+                    bail!("Value {} does not fit in {} bits", value, bfv.bit_width);
+                }
+                bfv.push(value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the number of values pushed so far.
+    pub fn len(&self) -> usize {
+        match &self.mode {
+            BuilderMode::Infer(values) => values.len(),
+            BuilderMode::Fixed(bfv) => bfv.len,
+        }
+    }
+
+    /// Return whether no value has been pushed so far.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Build the vector.
+    pub fn build(self) -> BitFieldVec<W> {
+        match self.mode {
+            BuilderMode::Infer(values) => BitFieldVec::from_values(&values),
+            BuilderMode::Fixed(bfv) => bfv,
+        }
+    }
+}
+
+/// Append values to the vector.
+///
+/// # Panic
+/// Panics if a value does not fit in the bit width.
+impl<W: Word> Extend<W> for BitFieldVec<W, Vec<W>> {
+    fn extend<T: IntoIterator<Item = W>>(&mut self, iter: T) {
+        BitFieldVec::extend(self, iter)
+    }
+}
+
+/// Pack a slice of values using the minimum bit width, as
+/// [`from_values`](BitFieldVec::from_values).
+impl<W: Word> From<&[W]> for BitFieldVec<W, Vec<W>> {
+    fn from(values: &[W]) -> Self {
+        Self::from_values(values)
+    }
+}
+
+/// Collect values using the minimum bit width sufficient to hold all of them
+/// (see [`BitFieldVec::from_values`]).
+impl<W: Word> FromIterator<W> for BitFieldVec<W, Vec<W>> {
+    fn from_iter<T: IntoIterator<Item = W>>(iter: T) -> Self {
+        Self::from_values(&iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<W: Word> Default for BitFieldVecBuilder<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<W: Word, B> BitFieldVec<W, B> {
     /// # Safety
     /// `len` * `bit_width` must be between 0 (included) the number of
@@ -320,8 +1102,12 @@ where
     W: Word,
 {
     vec: &'a BitFieldVec<W, B>,
+    /// The index of the next word to load.
     word_index: usize,
+    /// The `fill` bits of the current word that have not been returned yet.
     window: W,
+    /// Always smaller than `W::BITS`, so the window can be shifted by the
+    /// bit width even when the bit width is `W::BITS`.
     fill: usize,
 }
 
@@ -330,28 +1116,60 @@ impl<'a, W: Word, B: AsRef<[W]>> BitFieldVectorUncheckedIterator<'a, W, B> {
         if index > vec.len() {
             panic!("Start index out of bounds: {} > {}", index, vec.len());
         }
-        let (fill, word_index);
-        let window = if index == vec.len() {
-            word_index = 0;
-            fill = 0;
-            W::ZERO
+        let bit_offset = index * vec.bit_width;
+        let bit_index = bit_offset % W::BITS;
+        let word_index = bit_offset / W::BITS;
+        if bit_index == 0 {
+            // Start with an empty window, so that fill is never W::BITS
+            Self {
+                vec,
+                word_index,
+                window: W::ZERO,
+                fill: 0,
+            }
         } else {
-            let bit_offset = index * vec.bit_width;
-            let bit_index = bit_offset % W::BITS;
-
-            word_index = bit_offset / W::BITS;
-            fill = W::BITS - bit_index;
-            unsafe {
-                // SAFETY: index has been check at the start and it is within bounds
-                *vec.data.as_ref().get_unchecked(word_index) >> bit_index
+            Self {
+                vec,
+                word_index: word_index + 1,
+                // SAFETY: as bit_index is not zero, the word containing
+                // bit_offset contains bits of the value preceding index
+                window: unsafe { *vec.data.as_ref().get_unchecked(word_index) } >> bit_index,
+                fill: W::BITS - bit_index,
             }
+        }
+    }
+
+    /// Move the iterator one position backwards and return the value at the
+    /// new position, that is, the value preceding the one that would be
+    /// returned by [`next_unchecked`](crate::traits::UncheckedIterator::next_unchecked).
+    ///
+    /// After a call to this method, [`next_unchecked`](crate::traits::UncheckedIterator::next_unchecked)
+    /// will return the same value.
+    ///
+    /// # Safety
+    /// The iterator must not be at the start of the vector.
+    pub unsafe fn prev_unchecked(&mut self) -> W {
+        let bit_offset = self.word_index * W::BITS - self.fill - self.vec.bit_width;
+        let bit_index = bit_offset % W::BITS;
+        let word_index = bit_offset / W::BITS;
+        let data = self.vec.data.as_ref();
+        let window = *data.get_unchecked(word_index) >> bit_index;
+        let res = if bit_index + self.vec.bit_width <= W::BITS {
+            window & self.vec.mask
+        } else {
+            (window | *data.get_unchecked(word_index + 1) << (W::BITS - bit_index)) & self.vec.mask
         };
-        Self {
-            vec,
-            word_index,
-            window,
-            fill,
+        // Same state as new()
+        if bit_index == 0 {
+            self.word_index = word_index;
+            self.window = W::ZERO;
+            self.fill = 0;
+        } else {
+            self.word_index = word_index + 1;
+            self.window = window;
+            self.fill = W::BITS - bit_index;
         }
+        res
     }
 }
 
@@ -370,11 +1188,12 @@ impl<'a, W: Word, B: AsRef<[W]>> crate::traits::UncheckedIterator
         }
 
         let res = self.window;
-        self.word_index += 1;
         self.window = *self.vec.data.as_ref().get_unchecked(self.word_index);
+        self.word_index += 1;
         let res = (res | (self.window << self.fill)) & self.vec.mask;
         let used = bit_width - self.fill;
-        self.window >>= used;
+        // used might be W::BITS, so we shift in two steps
+        self.window = self.window >> (used - 1) >> 1;
         self.fill = W::BITS - used;
         res
     }
@@ -445,8 +1264,9 @@ impl<'a, W: Word, B: AsRef<[W]>> crate::traits::UncheckedIterator
         self.word_index -= 1;
         self.window = *self.vec.data.as_ref().get_unchecked(self.word_index);
         let used = bit_width - self.fill;
-        res = ((res << used) | self.window >> (W::BITS - used)) & self.vec.mask;
-        self.window <<= used;
+        // used might be W::BITS, so we shift in two steps
+        res = (res << (used - 1) << 1 | self.window >> (W::BITS - used)) & self.vec.mask;
+        self.window = self.window << (used - 1) << 1;
         self.fill = W::BITS - used;
         res
     }
@@ -466,12 +1286,20 @@ impl<'a, W: Word, B: AsRef<[W]>> IntoReverseUncheckedIterator for &'a BitFieldVe
 }
 
 /// An [`Iterator`] over the values of a [`BitFieldVec`].
+///
+/// Values are returned in index order: that is, the iterator returns the
+/// same values as calling [`get`](BitFieldSlice::get) on increasing indices.
+///
+/// The iterator is double ended: values can be consumed from both ends,
+/// and iteration stops when the two ends meet.
 pub struct BitFieldVecIterator<'a, W, B>
 where
     W: Word,
 {
     unchecked: BitFieldVectorUncheckedIterator<'a, W, B>,
     index: usize,
+    /// The index after the last value not yet returned by `next_back`.
+    back: usize,
 }
 
 impl<'a, W: Word, B: AsRef<[W]>> BitFieldVecIterator<'a, W, B> {
@@ -482,6 +1310,7 @@ impl<'a, W: Word, B: AsRef<[W]>> BitFieldVecIterator<'a, W, B> {
         Self {
             unchecked: BitFieldVectorUncheckedIterator::new(vec, from),
             index: from,
+            back: vec.len(),
         }
     }
 }
@@ -489,7 +1318,7 @@ impl<'a, W: Word, B: AsRef<[W]>> BitFieldVecIterator<'a, W, B> {
 impl<'a, W: Word, B: AsRef<[W]>> Iterator for BitFieldVecIterator<'a, W, B> {
     type Item = W;
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index < self.unchecked.vec.len() {
+        if self.index < self.back {
             // SAFETY: index has just been checked.
             let res = unsafe { self.unchecked.next_unchecked() };
             self.index += 1;
@@ -498,11 +1327,43 @@ impl<'a, W: Word, B: AsRef<[W]>> Iterator for BitFieldVecIterator<'a, W, B> {
             None
         }
     }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.index;
+        (len, Some(len))
+    }
+
+    /// Skip `n` values in constant time, repositioning the window
+    /// directly on the underlying words.
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if n >= self.back - self.index {
+            self.index = self.back;
+            return None;
+        }
+        if n != 0 {
+            self.index += n;
+            self.unchecked = BitFieldVectorUncheckedIterator::new(self.unchecked.vec, self.index);
+        }
+        self.next()
+    }
+}
+
+impl<'a, W: Word, B: AsRef<[W]>> DoubleEndedIterator for BitFieldVecIterator<'a, W, B> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index < self.back {
+            self.back -= 1;
+            // SAFETY: back is within bounds
+            Some(unsafe { self.unchecked.vec.get_unchecked(self.back) })
+        } else {
+            None
+        }
+    }
 }
 
 impl<'a, W: Word, B: AsRef<[W]>> ExactSizeIterator for BitFieldVecIterator<'a, W, B> {
     fn len(&self) -> usize {
-        self.unchecked.vec.len() - self.index
+        self.back - self.index
     }
 }
 
@@ -516,9 +1377,193 @@ impl<'a, W: Word, B: AsRef<[W]>> IntoIterator for &'a BitFieldVec<W, B> {
 }
 
 impl<W: Word, B: AsRef<[W]>> BitFieldVec<W, B> {
+    /// Fill `dst` with the `dst.len()` consecutive values starting at `from`.
+    ///
+    /// The result is the same as calling [`get`](BitFieldSlice::get) on each
+    /// index, but values are extracted by sliding a window over the
+    /// underlying words, as in [`BitFieldVectorUncheckedIterator`], so bounds
+    /// are checked once and no position is recomputed.
+    ///
+    /// # Panic
+    /// Panics if `from + dst.len()` is greater than the length of the vector.
+    pub fn copy_to_slice(&self, from: usize, dst: &mut [W]) {
+        let len = <[W]>::len(dst);
+        if from > self.len || len > self.len - from {
+            panic!(
+                "Range out of bounds: {}..{} (length {})",
+                from,
+                from.saturating_add(len),
+                self.len
+            );
+        }
+        let mut iter = BitFieldVectorUncheckedIterator::new(self, from);
+        for value in dst {
+            // SAFETY: the range has been checked above
+            *value = unsafe { iter.next_unchecked() };
+        }
+    }
+
+    /// Return a vector containing the values in the given range.
+    ///
+    /// See [`copy_to_slice`](BitFieldVec::copy_to_slice).
+    ///
+    /// # Panic
+    /// Panics if the range is decreasing or out of bounds.
+    pub fn get_range(&self, range: core::ops::Range<usize>) -> Vec<W> {
+        if range.start > range.end {
+            panic!("Decreasing range: {}..{}", range.start, range.end);
+        }
+        let mut result = vec![W::ZERO; range.end - range.start];
+        self.copy_to_slice(range.start, &mut result);
+        result
+    }
+
+    /// Return a vector containing all values, unpacked.
+    ///
+    /// This method is the logical-value counterpart of
+    /// [`into_raw_parts`](BitFieldVec::into_raw_parts), which returns the
+    /// packed backend; the inverse conversion is provided by
+    /// [`from_values`](BitFieldVec::from_values), or equivalently by the
+    /// implementation of [`From`] on slices.
+    pub fn to_vec(&self) -> Vec<W> {
+        self.get_range(0..self.len)
+    }
+
     pub fn into_iter_from(&self, from: usize) -> BitFieldVecIterator<W, B> {
         BitFieldVecIterator::new(self, from)
     }
+
+    /// Return the index of the first value satisfying the given predicate,
+    /// or `None` if no value satisfies it.
+    ///
+    /// Values are extracted by sliding a window over the underlying words, as
+    /// in [`BitFieldVectorUncheckedIterator`], and the scan stops at the first
+    /// match.
+    pub fn position(&self, pred: impl Fn(W) -> bool) -> Option<usize> {
+        let mut iter = BitFieldVectorUncheckedIterator::new(self, 0);
+        // SAFETY: the iterator is called at most len times
+        (0..self.len).find(|_| pred(unsafe { iter.next_unchecked() }))
+    }
+
+    /// Return the index of the first occurrence of the given value,
+    /// or `None` if the value does not occur in the vector.
+    ///
+    /// Values that do not fit in the bit width are rejected without scanning.
+    /// See [`position`](BitFieldVec::position).
+    pub fn find_value(&self, value: W) -> Option<usize> {
+        if value & !self.mask != W::ZERO {
+            return None;
+        }
+        self.position(|x| x == value)
+    }
+
+    /// Return the first value and an iterator over the remaining values,
+    /// or `None` if the vector is empty.
+    ///
+    /// This is the analogous of [`slice::split_first`], but since a view on
+    /// the remaining values would need a bit offset, the remaining values are
+    /// returned as an iterator.
+    pub fn split_first(&self) -> Option<(W, BitFieldVecIterator<W, B>)> {
+        if self.len == 0 {
+            return None;
+        }
+        Some((unsafe { self.get_unchecked(0) }, self.into_iter_from(1)))
+    }
+
+    /// Return the last value and an iterator over the remaining values,
+    /// or `None` if the vector is empty.
+    ///
+    /// This is the analogous of [`slice::split_last`], but the remaining
+    /// values are returned as an iterator: see
+    /// [`split_first`](BitFieldVec::split_first).
+    pub fn split_last(&self) -> Option<(W, core::iter::Take<BitFieldVecIterator<W, B>>)> {
+        if self.len == 0 {
+            return None;
+        }
+        Some((
+            unsafe { self.get_unchecked(self.len - 1) },
+            self.into_iter_from(0).take(self.len - 1),
+        ))
+    }
+
+    /// Return an iterator over the rows of this vector, seen as a row-major
+    /// matrix with `ncols` columns.
+    ///
+    /// Each row is returned as an iterator over its values; the sequential
+    /// decoder is repositioned at the start of each row, so no value
+    /// is materialized.
+    ///
+    /// Returns an error if `ncols` is zero or if the length of the vector
+    /// is not a multiple of `ncols`.
+    pub fn rows(
+        &self,
+        ncols: usize,
+    ) -> Result<impl Iterator<Item = core::iter::Take<BitFieldVecIterator<W, B>>> + '_> {
+        if ncols == 0 {
+            bail!("The number of columns must be positive");
+        }
+        if self.len % ncols != 0 {
+            bail!(
+                "The length of the vector ({}) is not a multiple of the number of columns ({})",
+                self.len,
+                ncols
+            );
+        }
+        Ok((0..self.len / ncols).map(move |row| self.into_iter_from(row * ncols).take(ncols)))
+    }
+
+    /// Return an estimate of the number of distinct values in the vector.
+    ///
+    /// The estimate is computed in a single sequential pass by feeding
+    /// the [SpookyHash](crate::utils::spooky) of each value into a
+    /// [HyperLogLog](https://en.wikipedia.org/wiki/HyperLogLog) counter
+    /// with 2<sup>`precision`</sup> registers of one byte. The relative standard
+    /// error of the estimate is about 1.04 / &radic;2<sup>`precision`</sup>
+    /// (e.g., 1.6% for `precision` equal to 12).
+    ///
+    /// # Panic
+    /// Panics if `precision` is not in the range [4..18].
+    #[cfg(feature = "std")]
+    pub fn approx_count_distinct(&self, precision: u8) -> u64
+    where
+        W: ToBytes,
+    {
+        assert!(
+            (4..=18).contains(&precision),
+            "Precision must be in the range [4..18] ({})",
+            precision
+        );
+        let m = 1_usize << precision;
+        let mut registers = vec![0_u8; m];
+        for value in self {
+            let hash = spooky_short(value.to_le_bytes(), 0)[0];
+            let register = (hash >> (64 - precision)) as usize;
+            // Position of the leftmost one in the remaining bits, capped
+            let rho = ((hash << precision) | (1 << (precision - 1))).leading_zeros() as u8 + 1;
+            registers[register] = Ord::max(registers[register], rho);
+        }
+
+        let m = m as f64;
+        let alpha = match precision {
+            4 => 0.673,
+            5 => 0.697,
+            6 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let mut sum = 0.0;
+        let mut zeros = 0;
+        for &r in &registers {
+            sum += 2_f64.powi(-(r as i32));
+            zeros += (r == 0) as usize;
+        }
+        let estimate = alpha * m * m / sum;
+        if estimate <= 2.5 * m && zeros != 0 {
+            // Small-range correction (linear counting)
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
 }
 
 impl<W: Word, B: AsRef<[W]> + AsMut<[W]>> BitFieldSliceMut<W> for BitFieldVec<W, B> {
@@ -602,13 +1647,15 @@ where
 
     #[inline]
     unsafe fn set_atomic_unchecked(&self, index: usize, value: W, order: Ordering) {
-        debug_assert!(self.bit_width != W::BITS);
         let pos = index * self.bit_width;
         let word_index = pos / W::BITS;
         let bit_index = pos % W::BITS;
         let data: &[W::AtomicType] = self.data.as_ref();
 
-        if bit_index + self.bit_width <= W::BITS {
+        if self.bit_width == W::BITS {
+            // values are words
+            data.get_unchecked(word_index).store(value, order);
+        } else if bit_index + self.bit_width <= W::BITS {
             // this is consistent
             let mut current = data.get_unchecked(word_index).load(order);
             loop {
@@ -670,6 +1717,268 @@ where
     }
 }
 
+impl<W: Word + IntoAtomic, T: AsRef<[W::AtomicType]>> AtomicBitFieldVec<W, T>
+where
+    W::AtomicType: AtomicUnsignedInt + AsBytes,
+{
+    /// Add `delta` to the value at the specified index, saturating at the
+    /// maximum value representable in [`BitFieldSliceCore::bit_width`] bits,
+    /// and return the previous value.
+    ///
+    /// The update never overflows into the bits of adjacent values. If the
+    /// value is contained in a single word, the update is atomic; if it
+    /// crosses a word boundary, it is performed as an atomic read followed by
+    /// an atomic write, with the same caveats as
+    /// [`set_atomic`](AtomicBitFieldSlice::set_atomic): concurrent updates of
+    /// the same value may be lost, but the result is always at most the maximum
+    /// value.
+    ///
+    /// # Panic
+    /// Panics if the index is out of bounds.
+    #[inline]
+    pub fn fetch_saturating_add(&self, index: usize, delta: W, order: Ordering) -> W {
+        panic_if_out_of_bounds!(index, self.len);
+        unsafe { self.fetch_saturating_add_unchecked(index, delta, order) }
+    }
+
+    /// Like [`fetch_saturating_add`](AtomicBitFieldVec::fetch_saturating_add),
+    /// but without bounds checking.
+    ///
+    /// # Safety
+    /// `index` must be between 0 (included) and the length of the vector (excluded).
+    #[inline]
+    pub unsafe fn fetch_saturating_add_unchecked(
+        &self,
+        index: usize,
+        delta: W,
+        order: Ordering,
+    ) -> W {
+        let pos = index * self.bit_width;
+        let word_index = pos / W::BITS;
+        let bit_index = pos % W::BITS;
+        let data: &[W::AtomicType] = self.data.as_ref();
+
+        if bit_index + self.bit_width <= W::BITS {
+            let mut word = data.get_unchecked(word_index).load(order);
+            loop {
+                let current = (word >> bit_index) & self.mask;
+                let value = Ord::min(current.saturating_add(delta), self.mask);
+                if value == current {
+                    return current;
+                }
+                let new = word & !(self.mask << bit_index) | value << bit_index;
+                match data
+                    .get_unchecked(word_index)
+                    .compare_exchange(word, new, order, order)
+                {
+                    Ok(_) => return current,
+                    Err(e) => word = e,
+                }
+            }
+        } else {
+            let current = self.get_atomic_unchecked(index, order);
+            let value = Ord::min(current.saturating_add(delta), self.mask);
+            if value != current {
+                self.set_atomic_unchecked(index, value, order);
+            }
+            current
+        }
+    }
+
+    /// Fetch the value at the specified index, and apply a function to it
+    /// that returns an optional new value, with the same semantics of
+    /// [`AtomicUsize::fetch_update`].
+    ///
+    /// Returns `Ok(previous_value)` if the function returned `Some(_)`, else
+    /// `Err(previous_value)`. The function may be called multiple times if
+    /// the value has been changed by other threads in the meantime.
+    ///
+    /// # Warning
+    ///
+    /// **The update is atomic only if the [bit
+    /// width](BitFieldSliceCore::bit_width) is zero or a power of two**, as in
+    /// this case no value crosses a word boundary. This contract is checked by
+    /// a debug assertion; in release mode, values crossing a word
+    /// boundary are updated by an atomic read followed by an atomic write, and
+    /// concurrent updates might be lost.
+    ///
+    /// # Panic
+    /// Panics if the index is out of bounds, or if the value returned by
+    /// `f` does not fit in the bit width.
+    pub fn fetch_update(
+        &self,
+        index: usize,
+        order: Ordering,
+        mut f: impl FnMut(W) -> Option<W>,
+    ) -> Result<W, W> {
+        panic_if_out_of_bounds!(index, self.len);
+        debug_assert!(
+            self.bit_width == 0 || self.bit_width.is_power_of_two(),
+            "fetch_update requires a zero or power-of-two bit width, but the bit width is {}",
+            self.bit_width
+        );
+        let pos = index * self.bit_width;
+        let word_index = pos / W::BITS;
+        let bit_index = pos % W::BITS;
+        let data: &[W::AtomicType] = self.data.as_ref();
+
+        if bit_index + self.bit_width <= W::BITS {
+            // SAFETY: the index has been checked, so word_index is within bounds
+            let atomic = unsafe { data.get_unchecked(word_index) };
+            let mut word = atomic.load(order);
+            loop {
+                let current = (word >> bit_index) & self.mask;
+                let Some(value) = f(current) else {
+                    return Err(current);
+                };
+                panic_if_value!(value, self.mask, self.bit_width);
+                let new = word & !(self.mask << bit_index) | value << bit_index;
+                match atomic.compare_exchange(word, new, order, order) {
+                    Ok(_) => return Ok(current),
+                    Err(e) => word = e,
+                }
+            }
+        } else {
+            // SAFETY: the index has been checked
+            let current = unsafe { self.get_atomic_unchecked(index, order) };
+            let Some(value) = f(current) else {
+                return Err(current);
+            };
+            panic_if_value!(value, self.mask, self.bit_width);
+            unsafe { self.set_atomic_unchecked(index, value, order) };
+            Ok(current)
+        }
+    }
+
+    /// Return an iterator over the values, loading each value with the
+    /// given ordering.
+    ///
+    /// The values are decoded by sliding over the underlying words, so each
+    /// word is loaded at most once, and there is no need to convert the vector
+    /// into a non-atomic one.
+    ///
+    /// The iterator can be used concurrently with writers, but the values it
+    /// returns are not a consistent snapshot of the vector. Moreover, **if the [bit
+    /// width](BitFieldSliceCore::bit_width) is not a power of two** a value
+    /// crossing a word boundary is read using two loads, and a concurrent
+    /// write might be observed only partially, as in the case of
+    /// [`get_atomic`](AtomicBitFieldSlice::get_atomic).
+    pub fn iter_atomic(&self, order: Ordering) -> AtomicBitFieldVecIterator<'_, W, T> {
+        AtomicBitFieldVecIterator {
+            vec: self,
+            order,
+            index: 0,
+            word_index: 0,
+            bit_index: 0,
+            word: W::ZERO,
+        }
+    }
+
+    /// Return the number of nonzero values, loading each value with the
+    /// given ordering.
+    ///
+    /// The same caveats of [`iter_atomic`](AtomicBitFieldVec::iter_atomic)
+    /// apply.
+    pub fn count_nonzero(&self, order: Ordering) -> usize {
+        self.iter_atomic(order)
+            .filter(|&value| value != W::ZERO)
+            .count()
+    }
+
+    /// Return the sum of the values, loading each value with the given
+    /// ordering.
+    ///
+    /// The same caveats of [`iter_atomic`](AtomicBitFieldVec::iter_atomic)
+    /// apply.
+    pub fn sum(&self, order: Ordering) -> u128
+    where
+        W: CastableInto<u128>,
+    {
+        self.iter_atomic(order).map(|value| value.cast()).sum()
+    }
+}
+
+/// An [`Iterator`] over the values of an [`AtomicBitFieldVec`], returned
+/// by [`AtomicBitFieldVec::iter_atomic`].
+pub struct AtomicBitFieldVecIterator<'a, W: Word + IntoAtomic, T> {
+    vec: &'a AtomicBitFieldVec<W, T>,
+    order: Ordering,
+    /// The index of the next value.
+    index: usize,
+    /// The index of the word containing the first bit of the next value.
+    word_index: usize,
+    /// The position of the first bit of the next value in its word.
+    bit_index: usize,
+    /// The word at `word_index`; it is loaded lazily, so it is valid only
+    /// if `bit_index` is not zero.
+    word: W,
+}
+
+impl<'a, W: Word + IntoAtomic, T: AsRef<[W::AtomicType]>> Iterator
+    for AtomicBitFieldVecIterator<'a, W, T>
+where
+    W::AtomicType: AtomicUnsignedInt + AsBytes,
+{
+    type Item = W;
+
+    fn next(&mut self) -> Option<W> {
+        if self.index >= self.vec.len {
+            return None;
+        }
+        self.index += 1;
+        let bit_width = self.vec.bit_width;
+        if bit_width == 0 {
+            return Some(W::ZERO);
+        }
+        let data: &[W::AtomicType] = self.vec.data.as_ref();
+        // SAFETY: the value is within bounds, so the words containing
+        // its bits are within bounds, too
+        unsafe {
+            if self.bit_index == 0 {
+                self.word = data.get_unchecked(self.word_index).load(self.order);
+            }
+            let value = if self.bit_index + bit_width <= W::BITS {
+                (self.word >> self.bit_index) & self.vec.mask
+            } else {
+                let next = data.get_unchecked(self.word_index + 1).load(self.order);
+                let value = (self.word >> self.bit_index | next << (W::BITS - self.bit_index))
+                    & self.vec.mask;
+                self.word = next;
+                value
+            };
+            self.bit_index += bit_width;
+            if self.bit_index >= W::BITS {
+                self.bit_index -= W::BITS;
+                self.word_index += 1;
+            }
+            Some(value)
+        }
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.vec.len - self.index;
+        (len, Some(len))
+    }
+}
+
+impl<'a, W: Word + IntoAtomic, T: AsRef<[W::AtomicType]>> ExactSizeIterator
+    for AtomicBitFieldVecIterator<'a, W, T>
+where
+    W::AtomicType: AtomicUnsignedInt + AsBytes,
+{
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.vec.len - self.index
+    }
+}
+
+impl<W: Word, B: SpaceUsage> SpaceUsage for BitFieldVec<W, B> {
+    fn mem_size(&self) -> usize {
+        core::mem::size_of::<Self>() - core::mem::size_of::<B>() + self.data.mem_size()
+    }
+}
+
 /// Provide conversion from non-atomic to atomic bitfield vectors, provided their
 /// backends are [convertible](ConvertTo) into one another.
 ///
@@ -769,3 +2078,155 @@ impl_from!(u16, AtomicU16);
 impl_from!(u32, AtomicU32);
 impl_from!(u64, AtomicU64);
 impl_from!(usize, AtomicUsize);
+
+/// Serde support for [`BitFieldVec`] with owned storage.
+///
+/// A vector is serialized as a structure with fields `bit_width`, `len` and
+/// `data`, where `data` is the sequence of underlying words. Deserialization
+/// checks that `bit_width` is at most the number of bits of a word and that
+/// `data` contains at least `len` &middot; `bit_width` bits.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+    use core::fmt;
+    use core::marker::PhantomData;
+    use serde::de::{self, MapAccess, SeqAccess, Visitor};
+    use serde::ser::SerializeStruct;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    const FIELDS: &[&str] = &["bit_width", "len", "data"];
+
+    impl<W: Word + Serialize> Serialize for BitFieldVec<W, Vec<W>> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut state = serializer.serialize_struct("BitFieldVec", 3)?;
+            state.serialize_field("bit_width", &self.bit_width)?;
+            state.serialize_field("len", &self.len)?;
+            state.serialize_field("data", &self.data)?;
+            state.end()
+        }
+    }
+
+    enum Field {
+        BitWidth,
+        Len,
+        Data,
+    }
+
+    impl<'de> Deserialize<'de> for Field {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct FieldVisitor;
+
+            impl<'de> Visitor<'de> for FieldVisitor {
+                type Value = Field;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("`bit_width`, `len` or `data`")
+                }
+
+                fn visit_str<E: de::Error>(self, value: &str) -> Result<Field, E> {
+                    match value {
+                        "bit_width" => Ok(Field::BitWidth),
+                        "len" => Ok(Field::Len),
+                        "data" => Ok(Field::Data),
+                        _ => Err(de::Error::unknown_field(value, FIELDS)),
+                    }
+                }
+            }
+
+            deserializer.deserialize_identifier(FieldVisitor)
+        }
+    }
+
+    struct BitFieldVecVisitor<W>(PhantomData<W>);
+
+    impl<W: Word> BitFieldVecVisitor<W> {
+        /// Check the consistency of the parts and build the vector.
+        fn build<E: de::Error>(
+            data: Vec<W>,
+            bit_width: usize,
+            len: usize,
+        ) -> Result<BitFieldVec<W, Vec<W>>, E> {
+            if bit_width > W::BITS {
+                return Err(E::custom(format_args!(
+                    "bit width {} is larger than the word size {}",
+                    bit_width,
+                    W::BITS
+                )));
+            }
+            // Accessors read a word even when the bit width is zero
+            if data.is_empty() {
+                return Err(E::custom("data must contain at least one word"));
+            }
+            match len.checked_mul(bit_width) {
+                Some(bits) if bits <= data.len() * W::BITS => {}
+                _ => {
+                    return Err(E::custom(format_args!(
+                        "{} words are not enough to store {} values of width {}",
+                        data.len(),
+                        len,
+                        bit_width
+                    )))
+                }
+            }
+            // SAFETY: we checked that data contains len * bit_width bits
+            Ok(unsafe { BitFieldVec::from_raw_parts(data, bit_width, len) })
+        }
+    }
+
+    impl<'de, W: Word + Deserialize<'de>> Visitor<'de> for BitFieldVecVisitor<W> {
+        type Value = BitFieldVec<W, Vec<W>>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("struct BitFieldVec")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let bit_width = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+            let len = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+            let data = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+            Self::build(data, bit_width, len)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let (mut bit_width, mut len, mut data) = (None, None, None);
+            while let Some(key) = map.next_key()? {
+                match key {
+                    Field::BitWidth => {
+                        if bit_width.is_some() {
+                            return Err(de::Error::duplicate_field("bit_width"));
+                        }
+                        bit_width = Some(map.next_value()?);
+                    }
+                    Field::Len => {
+                        if len.is_some() {
+                            return Err(de::Error::duplicate_field("len"));
+                        }
+                        len = Some(map.next_value()?);
+                    }
+                    Field::Data => {
+                        if data.is_some() {
+                            return Err(de::Error::duplicate_field("data"));
+                        }
+                        data = Some(map.next_value()?);
+                    }
+                }
+            }
+            let bit_width = bit_width.ok_or_else(|| de::Error::missing_field("bit_width"))?;
+            let len = len.ok_or_else(|| de::Error::missing_field("len"))?;
+            let data = data.ok_or_else(|| de::Error::missing_field("data"))?;
+            Self::build(data, bit_width, len)
+        }
+    }
+
+    impl<'de, W: Word + Deserialize<'de>> Deserialize<'de> for BitFieldVec<W, Vec<W>> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_struct("BitFieldVec", FIELDS, BitFieldVecVisitor(PhantomData))
+        }
+    }
+}
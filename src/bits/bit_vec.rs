@@ -24,21 +24,32 @@ These flavors depends on a backend, and presently we provide:
 - `AtomicBitVec<AsRef<[AtomicUsize]>>`: a thread-safe, mutable (but not resizable) bit vector.
 
 It is possible to juggle between the three flavors using [`From`].
+
+[`BitVec`] and [`CountBitVec`] implement [`BitLength`], [`BitCount`], [`Rank`],
+[`Select`] and [`SelectZero`] by linear scans, so the ranking and selection
+traits can be used without building an index: these implementations are
+useful for small bit vectors and as a reference when testing indexed
+structures such as [`QuantumIndex`](crate::rank_sel::QuantumIndex).
  */
 use crate::traits::*;
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
 use anyhow::Result;
 use common_traits::SelectInWord;
+use core::{
+    ops::{Bound, Index, RangeBounds},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+#[cfg(feature = "std")]
 use epserde::*;
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
-use std::{
-    ops::Index,
-    sync::atomic::{AtomicUsize, Ordering},
-};
 
 const BITS: usize = usize::BITS as usize;
 
-#[derive(Epserde, Debug)]
+#[cfg_attr(feature = "std", derive(Epserde))]
+#[derive(Debug)]
 /// A bit vector.
 pub struct BitVec<B = Vec<usize>> {
     data: B,
@@ -60,6 +71,52 @@ macro_rules! panic_if_out_of_bounds {
     };
 }
 
+/// Turn a range into a pair of bounds, checking that it is within `len` bits.
+fn range_bounds(range: impl RangeBounds<usize>, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    if start > end {
+        panic!("Bit range starts at {} but ends at {}", start, end);
+    }
+    if end > len {
+        panic!("Bit range end out of bounds: {} > {}", end, len);
+    }
+    (start, end)
+}
+
+/// Call `f` on the index of each word intersecting the bit range
+/// `start..end`, together with a mask of the bits of the word in the range.
+///
+/// Interior words have a full mask, so operations on large ranges
+/// proceed a word at a time.
+#[inline(always)]
+fn for_each_word_in_range(start: usize, end: usize, mut f: impl FnMut(usize, usize)) {
+    if start == end {
+        return;
+    }
+    let first_word = start / BITS;
+    let last_word = (end - 1) / BITS;
+    let start_mask = usize::MAX << (start % BITS);
+    let end_mask = usize::MAX >> (BITS - 1 - (end - 1) % BITS);
+    if first_word == last_word {
+        f(first_word, start_mask & end_mask);
+    } else {
+        f(first_word, start_mask);
+        for word_index in first_word + 1..last_word {
+            f(word_index, usize::MAX);
+        }
+        f(last_word, end_mask);
+    }
+}
+
 impl<B> BitLength for BitVec<B> {
     #[inline(always)]
     fn len(&self) -> usize {
@@ -196,7 +253,11 @@ impl<B: AsRef<[usize]>> BitVec<B> {
 
         #[cfg(not(feature = "rayon"))]
         {
-            self.data.iter().map(|x| x.count_ones() as usize).sum()
+            self.data
+                .as_ref()
+                .iter()
+                .map(|x| x.count_ones() as usize)
+                .sum()
         }
     }
 
@@ -216,6 +277,12 @@ impl<B: AsRef<[usize]>> BitVec<B> {
             number_of_ones,
         }
     }
+
+    /// Return an iterator over the positions of the ones in this bit vector,
+    /// in increasing order.
+    pub fn ones(&self) -> OnesIterator<&[usize]> {
+        OnesIterator::new(self.data.as_ref(), self.count_ones())
+    }
 }
 
 impl<B: AsRef<[AtomicUsize]>> AtomicBitVec<B> {
@@ -239,6 +306,7 @@ impl<B: AsRef<[AtomicUsize]>> AtomicBitVec<B> {
         #[cfg(not(feature = "rayon"))]
         {
             self.data
+                .as_ref()
                 .iter()
                 .map(|x| x.load(Ordering::Relaxed).count_ones() as usize)
                 .sum()
@@ -260,6 +328,20 @@ impl<B: AsRef<[usize]>> BitVec<B> {
         let word = self.data.as_ref().get_unchecked(word_index);
         (word >> (index % BITS)) & 1 != 0
     }
+
+    /// Return the number of bits set to 1 in the given range.
+    ///
+    /// # Panic
+    /// Panics if the range is not within [`BitVec::len`].
+    pub fn count_ones_in(&self, range: impl RangeBounds<usize>) -> usize {
+        let (start, end) = range_bounds(range, self.len);
+        let data = self.data.as_ref();
+        let mut count = 0;
+        for_each_word_in_range(start, end, |word_index, mask| {
+            count += (data[word_index] & mask).count_ones() as usize;
+        });
+        count
+    }
 }
 
 impl<B: AsRef<[usize]> + AsMut<[usize]>> BitVec<B> {
@@ -283,6 +365,40 @@ impl<B: AsRef<[usize]> + AsMut<[usize]>> BitVec<B> {
             *data.get_unchecked_mut(word_index) &= !(1 << bit_index);
         }
     }
+
+    /// Flip the bit of given index.
+    pub fn flip(&mut self, index: usize) {
+        panic_if_out_of_bounds!(index, self.len);
+        self.data.as_mut()[index / BITS] ^= 1 << (index % BITS);
+    }
+
+    /// Set all bits in the given range to `value`.
+    ///
+    /// # Panic
+    /// Panics if the range is not within [`BitVec::len`].
+    pub fn set_range(&mut self, range: impl RangeBounds<usize>, value: bool) {
+        let (start, end) = range_bounds(range, self.len);
+        let data = self.data.as_mut();
+        for_each_word_in_range(start, end, |word_index, mask| {
+            if value {
+                data[word_index] |= mask;
+            } else {
+                data[word_index] &= !mask;
+            }
+        });
+    }
+
+    /// Flip all bits in the given range.
+    ///
+    /// # Panic
+    /// Panics if the range is not within [`BitVec::len`].
+    pub fn flip_range(&mut self, range: impl RangeBounds<usize>) {
+        let (start, end) = range_bounds(range, self.len);
+        let data = self.data.as_mut();
+        for_each_word_in_range(start, end, |word_index, mask| {
+            data[word_index] ^= mask;
+        });
+    }
 }
 
 impl<B: AsRef<[AtomicUsize]>> AtomicBitVec<B> {
@@ -319,6 +435,154 @@ impl<B: AsRef<[AtomicUsize]>> AtomicBitVec<B> {
     }
 }
 
+macro_rules! panic_if_different_len {
+    ($len: expr, $other_len: expr) => {
+        if $len != $other_len {
+            panic!(
+                "Bit vectors have different lengths: {} != {}",
+                $len, $other_len
+            )
+        }
+    };
+}
+
+/// Set algebra between bit vectors of the same length.
+///
+/// All methods operate a word at a time, and panic if the two bit vectors
+/// have different lengths. The bits of the final partial word
+/// beyond the length of the results are always zero.
+impl<B: AsRef<[usize]>> BitVec<B> {
+    /// The words containing the bits of this bit vector.
+    #[inline(always)]
+    fn words(&self) -> &[usize] {
+        &self.data.as_ref()[..(self.len + BITS - 1) / BITS]
+    }
+
+    /// A mask for the valid bits of the last word.
+    #[inline(always)]
+    fn last_word_mask(&self) -> usize {
+        match self.len % BITS {
+            0 => usize::MAX,
+            bits => (1 << bits) - 1,
+        }
+    }
+
+    /// Return an iterator on the pairs of corresponding words of this bit
+    /// vector and `other`, with the bits beyond the length cleared.
+    fn word_pairs<'a, C: AsRef<[usize]>>(
+        &'a self,
+        other: &'a BitVec<C>,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        panic_if_different_len!(self.len, other.len);
+        let last = self.words().len().wrapping_sub(1);
+        let mask = self.last_word_mask();
+        self.words()
+            .iter()
+            .zip(other.words())
+            .enumerate()
+            .map(move |(i, (&a, &b))| {
+                if i == last {
+                    (a & mask, b & mask)
+                } else {
+                    (a, b)
+                }
+            })
+    }
+
+    /// Combine word by word this bit vector with another one.
+    fn combine<C: AsRef<[usize]>>(
+        &self,
+        other: &BitVec<C>,
+        op: impl Fn(usize, usize) -> usize,
+    ) -> BitVec {
+        // All operations map zero padding to zero padding
+        BitVec {
+            data: self.word_pairs(other).map(|(a, b)| op(a, b)).collect(),
+            len: self.len,
+        }
+    }
+
+    /// Return the intersection of this bit vector and `other`.
+    pub fn and<C: AsRef<[usize]>>(&self, other: &BitVec<C>) -> BitVec {
+        self.combine(other, |a, b| a & b)
+    }
+
+    /// Return the union of this bit vector and `other`.
+    pub fn or<C: AsRef<[usize]>>(&self, other: &BitVec<C>) -> BitVec {
+        self.combine(other, |a, b| a | b)
+    }
+
+    /// Return the symmetric difference of this bit vector and `other`.
+    pub fn xor<C: AsRef<[usize]>>(&self, other: &BitVec<C>) -> BitVec {
+        self.combine(other, |a, b| a ^ b)
+    }
+
+    /// Return the difference of this bit vector and `other` (i.e., the bits
+    /// set in this bit vector but not in `other`).
+    pub fn andnot<C: AsRef<[usize]>>(&self, other: &BitVec<C>) -> BitVec {
+        self.combine(other, |a, b| a & !b)
+    }
+
+    /// Return whether all bits set in this bit vector are set in `other`.
+    pub fn is_subset<C: AsRef<[usize]>>(&self, other: &BitVec<C>) -> bool {
+        self.word_pairs(other).all(|(a, b)| a & !b == 0)
+    }
+
+    /// Return whether this bit vector and `other` have no bits set in common.
+    pub fn is_disjoint<C: AsRef<[usize]>>(&self, other: &BitVec<C>) -> bool {
+        self.word_pairs(other).all(|(a, b)| a & b == 0)
+    }
+
+    /// Return the number of bits set both in this bit vector and
+    /// in `other`, without materializing their intersection.
+    pub fn intersection_count<C: AsRef<[usize]>>(&self, other: &BitVec<C>) -> usize {
+        self.word_pairs(other)
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+}
+
+/// In-place set algebra between bit vectors of the same length.
+impl<B: AsRef<[usize]> + AsMut<[usize]>> BitVec<B> {
+    /// Combine in place word by word this bit vector with another one.
+    fn combine_assign<C: AsRef<[usize]>>(
+        &mut self,
+        other: &BitVec<C>,
+        op: impl Fn(&mut usize, usize),
+    ) {
+        panic_if_different_len!(self.len, other.len);
+        let mask = self.last_word_mask();
+        let n_of_words = (self.len + BITS - 1) / BITS;
+        let data = &mut self.data.as_mut()[..n_of_words];
+        for (a, &b) in data.iter_mut().zip(other.words()) {
+            op(a, b);
+        }
+        if let Some(last) = data.last_mut() {
+            *last &= mask;
+        }
+    }
+
+    /// Intersect in place this bit vector with `other`.
+    pub fn and_assign<C: AsRef<[usize]>>(&mut self, other: &BitVec<C>) {
+        self.combine_assign(other, |a, b| *a &= b)
+    }
+
+    /// Unite in place this bit vector with `other`.
+    pub fn or_assign<C: AsRef<[usize]>>(&mut self, other: &BitVec<C>) {
+        self.combine_assign(other, |a, b| *a |= b)
+    }
+
+    /// Replace this bit vector with its symmetric difference with `other`.
+    pub fn xor_assign<C: AsRef<[usize]>>(&mut self, other: &BitVec<C>) {
+        self.combine_assign(other, |a, b| *a ^= b)
+    }
+
+    /// Clear the bits of this bit vector that are set in `other`.
+    pub fn andnot_assign<C: AsRef<[usize]>>(&mut self, other: &BitVec<C>) {
+        self.combine_assign(other, |a, b| *a &= !b)
+    }
+}
+
 impl<B: AsRef<[usize]>> BitCount for BitVec<B> {
     fn count(&self) -> usize {
         self.data
@@ -336,6 +600,29 @@ impl<B: AsRef<[usize]>> Select for BitVec<B> {
     }
 }
 
+/// Rank by a linear scan: this implementation is useful mainly for testing
+/// and for small bit vectors.
+impl<B: AsRef<[usize]>> Rank for BitVec<B> {
+    #[inline(always)]
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        rank_unchecked(self.data.as_ref(), pos)
+    }
+}
+
+unsafe fn rank_unchecked(data: &[usize], pos: usize) -> usize {
+    let word_index = pos / BITS;
+    let bit_index = pos % BITS;
+    let mut rank = data
+        .get_unchecked(..word_index)
+        .iter()
+        .map(|w| w.count_ones() as usize)
+        .sum::<usize>();
+    if bit_index != 0 {
+        rank += (data.get_unchecked(word_index) & ((1 << bit_index) - 1)).count_ones() as usize;
+    }
+    rank
+}
+
 unsafe fn select_hinted_unchecked(
     data: impl AsRef<[usize]>,
     rank: usize,
@@ -467,7 +754,8 @@ impl<B: AsRef<[usize]>> SelectZeroHinted for BitVec<B> {
 }
 
 /// An immutable bit vector with a constant-time implementation of [`BitCount`].
-#[derive(Epserde, Debug)]
+#[cfg_attr(feature = "std", derive(Epserde))]
+#[derive(Debug)]
 pub struct CountBitVec<B = Vec<usize>> {
     data: B,
     len: usize,
@@ -546,6 +834,15 @@ impl<B: AsRef<[usize]>> Select for CountBitVec<B> {
     }
 }
 
+/// Rank by a linear scan: this implementation is useful mainly for testing
+/// and for small bit vectors.
+impl<B: AsRef<[usize]>> Rank for CountBitVec<B> {
+    #[inline(always)]
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        rank_unchecked(self.data.as_ref(), pos)
+    }
+}
+
 impl<B: AsRef<[usize]>> SelectHinted for CountBitVec<B> {
     unsafe fn select_hinted_unchecked(&self, rank: usize, pos: usize, rank_at_pos: usize) -> usize {
         select_hinted_unchecked(self.data.as_ref(), rank, pos, rank_at_pos)
@@ -702,6 +999,28 @@ where
     }
 }
 
+/// The space is reported as `bits`.
+impl<B: SpaceUsage> SpaceUsage for BitVec<B> {
+    fn mem_size(&self) -> usize {
+        core::mem::size_of::<Self>() - core::mem::size_of::<B>() + self.data.mem_size()
+    }
+
+    fn space_breakdown(&self) -> BTreeMap<&'static str, usize> {
+        BTreeMap::from([("bits", self.mem_size())])
+    }
+}
+
+/// The space is reported as `bits`.
+impl<B: SpaceUsage> SpaceUsage for CountBitVec<B> {
+    fn mem_size(&self) -> usize {
+        core::mem::size_of::<Self>() - core::mem::size_of::<B>() + self.data.mem_size()
+    }
+
+    fn space_breakdown(&self) -> BTreeMap<&'static str, usize> {
+        BTreeMap::from([("bits", self.mem_size())])
+    }
+}
+
 /// Needed so that the sparse index can build the ones.
 impl<B: AsRef<[usize]>> AsRef<[usize]> for CountBitVec<B> {
     #[inline(always)]
@@ -726,6 +1045,33 @@ impl FromIterator<bool> for BitVec<Vec<usize>> {
     }
 }
 
+/// Pack a slice of booleans, one bit per boolean.
+impl From<&[bool]> for BitVec<Vec<usize>> {
+    fn from(bools: &[bool]) -> Self {
+        let data = bools
+            .chunks(BITS)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0, |word, (i, &b)| word | (b as usize) << i)
+            })
+            .collect();
+        Self {
+            data,
+            len: bools.len(),
+        }
+    }
+}
+
+/// Pack a vector of booleans, one bit per boolean.
+impl From<Vec<bool>> for BitVec<Vec<usize>> {
+    #[inline(always)]
+    fn from(bools: Vec<bool>) -> Self {
+        Self::from(bools.as_slice())
+    }
+}
+
 /// An iterator over the ones in an underlying storage.
 pub struct OnesIterator<B> {
     mem_words: B,
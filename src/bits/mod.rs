@@ -17,3 +17,9 @@ pub use bit_field_vec::*;
 
 pub mod bit_vec;
 pub use bit_vec::*;
+
+pub mod safe_bit_field_vec;
+pub use safe_bit_field_vec::*;
+
+pub mod var_bit_field_vec;
+pub use var_bit_field_vec::*;
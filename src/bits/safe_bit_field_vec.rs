@@ -0,0 +1,127 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+A wrapper around [`BitFieldVec`] with a fully checked interface.
+
+[`SafeBitFieldVec`] exposes a small subset of the methods of [`BitFieldVec`]
+in which every index and every value is checked: accesses out of bounds return
+`None` and invalid writes return an error. No `unsafe` method is reachable
+from its public interface, and this module forbids unsafe code, so auditing
+it requires only auditing the checked methods of [`BitFieldVec`] it delegates
+to ([`BitFieldVec::checked_get`], [`BitFieldVec::checked_set`] and
+[`BitFieldVec::checked_push`]).
+
+The price is a comparison (two, for writes) and a branch on each access: in
+tight loops, [`BitFieldVec`] with unchecked access can be significantly faster,
+in particular because the checks prevent vectorization and make it impossible
+to skip the bounds check when iterating on a known range.
+
+*/
+
+#![forbid(unsafe_code)]
+
+use crate::prelude::*;
+use crate::traits::bit_field_slice::Word;
+use alloc::vec::Vec;
+use anyhow::{bail, Result};
+
+/// A [`BitFieldVec`] whose accessors are all bounds- and width-checked.
+///
+/// See the [module documentation](self) for more details.
+#[derive(Debug, Clone, Hash)]
+pub struct SafeBitFieldVec<W: Word = usize, B = Vec<W>> {
+    bits: BitFieldVec<W, B>,
+}
+
+impl<W: Word> SafeBitFieldVec<W, Vec<W>> {
+    /// Create a new vector of `len` zeroes of the given bit width, returning
+    /// an error if the bit width is larger than `W::BITS`.
+    pub fn new(bit_width: usize, len: usize) -> Result<Self> {
+        if bit_width > W::BITS {
+            bail!(
+                "Bit width {} is larger than the number of bits of {}",
+                bit_width,
+                core::any::type_name::<W>()
+            );
+        }
+        Ok(Self {
+            bits: BitFieldVec::new(bit_width, len),
+        })
+    }
+
+    /// Append a value to the vector, returning an error if the value does
+    /// not fit in the bit width.
+    pub fn push(&mut self, value: W) -> Result<()> {
+        self.bits.checked_push(value)
+    }
+}
+
+impl<W: Word, B> SafeBitFieldVec<W, B> {
+    /// Return the number of values.
+    pub fn len(&self) -> usize {
+        BitFieldSliceCore::len(&self.bits)
+    }
+
+    /// Return true if there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the bit width of the values.
+    pub fn bit_width(&self) -> usize {
+        self.bits.bit_width()
+    }
+
+    /// Return the underlying [`BitFieldVec`].
+    pub fn into_inner(self) -> BitFieldVec<W, B> {
+        self.bits
+    }
+}
+
+impl<W: Word, B: AsRef<[W]>> SafeBitFieldVec<W, B> {
+    /// Return the value at the specified index, or `None` if the index
+    /// is out of bounds.
+    pub fn get(&self, index: usize) -> Option<W> {
+        self.bits.checked_get(index)
+    }
+
+    /// Return an iterator over the values.
+    pub fn iter(&self) -> BitFieldVecIterator<'_, W, B> {
+        self.bits.into_iter()
+    }
+}
+
+impl<W: Word, B: AsRef<[W]> + AsMut<[W]>> SafeBitFieldVec<W, B> {
+    /// Set the value at the specified index, returning an error if the index
+    /// is out of bounds or if the value does not fit in the bit width.
+    pub fn set(&mut self, index: usize, value: W) -> Result<()> {
+        self.bits.checked_set(index, value)
+    }
+}
+
+impl<W: Word, B> From<BitFieldVec<W, B>> for SafeBitFieldVec<W, B> {
+    fn from(bits: BitFieldVec<W, B>) -> Self {
+        Self { bits }
+    }
+}
+
+impl<W: Word, B> From<SafeBitFieldVec<W, B>> for BitFieldVec<W, B> {
+    fn from(safe: SafeBitFieldVec<W, B>) -> Self {
+        safe.bits
+    }
+}
+
+impl<'a, W: Word, B: AsRef<[W]>> IntoIterator for &'a SafeBitFieldVec<W, B> {
+    type Item = W;
+    type IntoIter = BitFieldVecIterator<'a, W, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
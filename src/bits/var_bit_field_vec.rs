@@ -0,0 +1,235 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Vectors of values of variable bit width.
+
+A [`VarBitFieldVec`] stores each value using a number of bits depending on
+the value, rather than using the same bit width for all values as in a
+[`BitFieldVec`]: it is thus much smaller when most values are small but a few
+are large.
+
+More precisely, a value <var>v</var> is stored using
+&lfloor;log<sub>2</sub>(<var>v</var> + 1)&rfloor; bits, representing
+<var>v</var> + 1 without its most significant bit (so, in particular, zero
+uses no bits at all). The bits of the values are concatenated in a flat
+buffer, and the <var>n</var> + 1 cumulative bit offsets of the values are
+stored using the [Elias–Fano representation](crate::dict::elias_fano::EliasFano).
+
+Random access requires two accesses to the offsets, so it is slower than
+in a [`BitFieldVec`], but it is still constant-time.
+
+Instances are built using a [`VarBitFieldVecBuilder`].
+
+*/
+
+use crate::prelude::*;
+use crate::traits::bit_field_slice::panic_if_out_of_bounds;
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use anyhow::Result;
+#[cfg(feature = "std")]
+use epserde::*;
+
+const BITS: usize = usize::BITS as usize;
+
+/// Return a mask with the `width` lowest bits set.
+#[inline(always)]
+fn mask(width: usize) -> usize {
+    if width == BITS {
+        usize::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
+
+/// A builder for [`VarBitFieldVec`].
+///
+/// After creating an instance, you can use [`VarBitFieldVecBuilder::push`]
+/// to add new values.
+#[derive(Debug, Clone)]
+pub struct VarBitFieldVecBuilder {
+    /// The concatenation of the values.
+    data: Vec<usize>,
+    /// The `len` + 1 bit offsets of the values in `data`.
+    offsets: Vec<usize>,
+}
+
+impl VarBitFieldVecBuilder {
+    /// Create a new empty builder.
+    pub fn new() -> Self {
+        Self {
+            data: Vec::new(),
+            offsets: vec![0],
+        }
+    }
+
+    /// Add a new value to the builder.
+    pub fn push(&mut self, value: usize) {
+        // value + 1 without its most significant bit
+        let width = if value == usize::MAX {
+            BITS
+        } else {
+            (value + 1).ilog2() as usize
+        };
+        let bits = value - mask(width);
+
+        let pos = *self.offsets.last().unwrap();
+        let end = pos + width;
+        self.data.resize((end + BITS - 1) / BITS, 0);
+        if width != 0 {
+            let word_index = pos / BITS;
+            let bit_index = pos % BITS;
+            self.data[word_index] |= bits << bit_index;
+            if bit_index + width > BITS {
+                self.data[word_index + 1] |= bits >> (BITS - bit_index);
+            }
+        }
+        self.offsets.push(end);
+    }
+
+    /// Add all the values from an iterator.
+    pub fn extend(&mut self, values: impl IntoIterator<Item = usize>) {
+        for value in values {
+            self.push(value);
+        }
+    }
+
+    /// Return the number of values added so far.
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Return true if no values have been added so far.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Build the vector.
+    pub fn build(self) -> Result<VarBitFieldVec> {
+        let bit_len = *self.offsets.last().unwrap();
+        let mut efb = EliasFanoBuilder::new(self.offsets.len(), bit_len + 1);
+        for offset in self.offsets {
+            // SAFETY: the offsets are nondecreasing and at most bit_len
+            unsafe { efb.push_unchecked(offset) };
+        }
+        let mut data = self.data;
+        data.shrink_to_fit();
+        Ok(VarBitFieldVec {
+            data,
+            offsets: efb.build().convert_to()?,
+        })
+    }
+}
+
+impl Default for VarBitFieldVecBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An immutable vector of values of variable bit width.
+///
+/// See the [module documentation](self) for more details.
+#[cfg_attr(feature = "std", derive(Epserde))]
+#[derive(Debug)]
+pub struct VarBitFieldVec<
+    D: AsRef<[usize]> = Vec<usize>,
+    O: IndexedDict<Input = usize, Output = usize> = EliasFano<QuantumIndex<CountBitVec>>,
+> {
+    /// The concatenation of the values.
+    data: D,
+    /// The `len` + 1 bit offsets of the values in `data`.
+    offsets: O,
+}
+
+impl<D: AsRef<[usize]>, O: IndexedDict<Input = usize, Output = usize>> VarBitFieldVec<D, O> {
+    /// Return the number of values.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Return true if there are no values.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the number of bits used by the values (excluding the offsets).
+    #[inline(always)]
+    pub fn bit_len(&self) -> usize {
+        // SAFETY: there are len + 1 offsets
+        unsafe { self.offsets.get_unchecked(self.len()) }
+    }
+
+    /// Return the value at the specified index.
+    ///
+    /// # Panic
+    /// Panics if the index is out of bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> usize {
+        panic_if_out_of_bounds!(index, self.len());
+        unsafe { self.get_unchecked(index) }
+    }
+
+    /// Return the value at the specified index.
+    ///
+    /// # Safety
+    /// `index` must be between 0 (included) and [`len`](VarBitFieldVec::len) (excluded).
+    #[inline]
+    pub unsafe fn get_unchecked(&self, index: usize) -> usize {
+        let start = self.offsets.get_unchecked(index);
+        let width = self.offsets.get_unchecked(index + 1) - start;
+        if width == 0 {
+            return 0;
+        }
+        let data = self.data.as_ref();
+        let word_index = start / BITS;
+        let bit_index = start % BITS;
+        let mut bits = *data.get_unchecked(word_index) >> bit_index;
+        if bit_index + width > BITS {
+            bits |= *data.get_unchecked(word_index + 1) << (BITS - bit_index);
+        }
+        // Add back the most significant bit of value + 1, and subtract one
+        (bits & mask(width)) + mask(width)
+    }
+
+    /// Return an iterator over the values.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        // SAFETY: index is within bounds
+        (0..self.len()).map(|index| unsafe { self.get_unchecked(index) })
+    }
+}
+
+/// The space is reported as `data` (the values), `offsets` and `metadata`.
+impl<
+        D: AsRef<[usize]> + SpaceUsage,
+        O: IndexedDict<Input = usize, Output = usize> + SpaceUsage,
+    > SpaceUsage for VarBitFieldVec<D, O>
+{
+    fn mem_size(&self) -> usize {
+        core::mem::size_of::<Self>() - core::mem::size_of::<D>() - core::mem::size_of::<O>()
+            + self.data.mem_size()
+            + self.offsets.mem_size()
+    }
+
+    fn space_breakdown(&self) -> BTreeMap<&'static str, usize> {
+        BTreeMap::from([
+            ("data", self.data.mem_size()),
+            ("offsets", self.offsets.mem_size()),
+            (
+                "metadata",
+                core::mem::size_of::<Self>()
+                    - core::mem::size_of::<D>()
+                    - core::mem::size_of::<O>(),
+            ),
+        ])
+    }
+}
@@ -16,12 +16,21 @@ an [`EliasFanoBuilder`] or an [`EliasFanoConcurrentBuilder`].
 
 The main trait implemented by [`EliasFano`] is [`IndexedDict`], which
 makes it possible to access its values with [`IndexedDict::get`].
+The lower bits of the values are stored in a [`BitFieldVec`], and the
+upper bits are stored in unary code in a bit vector: [`IndexedDict::get`]
+uses [`Select`] on the bit vector, whereas [`Succ`], [`Pred`] and
+[`SortedIndexedDict`] use also [`SelectZero`]. The bit vector
+can be enriched with [selection indices](crate::rank_sel) to make these
+operations fast (see [`EliasFano`]).
 
  */
 use crate::prelude::*;
 use crate::traits::bit_field_slice::*;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
 use anyhow::{bail, Result};
 use core::sync::atomic::Ordering;
+#[cfg(feature = "std")]
 use epserde::*;
 
 /// A sequential builder for [`EliasFano`].
@@ -41,8 +50,8 @@ impl EliasFanoBuilder {
     /// Create a builder for an [`EliasFano`] containing
     /// `n` numbers smaller than `u`.
     pub fn new(n: usize, u: usize) -> Self {
-        let l = if u >= n {
-            (u as f64 / n as f64).log2().floor() as usize
+        let l = if n != 0 && u >= n {
+            (u / n).ilog2() as usize
         } else {
             0
         };
@@ -58,11 +67,54 @@ impl EliasFanoBuilder {
         }
     }
 
+    /// Build an [`EliasFano`] structure from values in arbitrary order.
+    ///
+    /// The values are collected, converted to `usize` and sorted; the upper
+    /// bound of the structure is one plus the largest value. If `dedup` is true,
+    /// duplicate values are removed; otherwise, they are stored (an
+    /// [`EliasFano`] structure can represent nondecreasing sequences).
+    /// The second element of the returned pair is true if duplicate values were
+    /// present.
+    ///
+    /// Returns an error if a value cannot be converted to `usize` (e.g.,
+    /// because it is negative) or if the largest value is `usize::MAX`.
+    pub fn from_unsorted<V: TryInto<usize> + Copy + core::fmt::Display>(
+        values: impl IntoIterator<Item = V>,
+        dedup: bool,
+    ) -> Result<(EliasFano, bool)> {
+        let mut values = values
+            .into_iter()
+            .map(|value| match value.try_into() {
+                Ok(value) => Ok(value),
+                Err(_) => bail!("Value {} cannot be represented as a usize", value),
+            })
+            .collect::<Result<Vec<usize>>>()?;
+        values.sort_unstable();
+        let has_dups = values.windows(2).any(|w| w[0] == w[1]);
+        if dedup {
+            values.dedup();
+        }
+        let u = match values.last() {
+            Some(&max) => match max.checked_add(1) {
+                Some(u) => u,
+                None => bail!("The universe is too large: the largest value is usize::MAX"),
+            },
+            None => 0,
+        };
+
+        let mut builder = Self::new(values.len(), u);
+        for value in values {
+            // SAFETY: the values are sorted, smaller than u and exactly n
+            unsafe { builder.push_unchecked(value) };
+        }
+        Ok((builder.build(), has_dups))
+    }
+
     /// Add a new value to the builder.
     ///
-    /// # Panic
-    /// May panic if the value is smaller than the last provided
-    /// value, or if too many values are provided.
+    /// Returns an error if the value is smaller than the last provided
+    /// value or not smaller than the upper bound `u`, or if more than `n`
+    /// values are provided.
     pub fn push(&mut self, value: usize) -> Result<()> {
         if self.count == self.n {
             bail!("Too many values");
@@ -123,8 +175,8 @@ impl EliasFanoConcurrentBuilder {
     /// Create a builder for an [`EliasFano`] containing
     /// `n` numbers smaller than `u`.
     pub fn new(n: usize, u: usize) -> Self {
-        let l = if u >= n {
-            (u as f64 / n as f64).log2().floor() as usize
+        let l = if n != 0 && u >= n {
+            (u / n).ilog2() as usize
         } else {
             0
         };
@@ -167,7 +219,8 @@ impl EliasFanoConcurrentBuilder {
     }
 }
 
-#[derive(Epserde, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "std", derive(Epserde))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct EliasFano<H = CountBitVec, L = BitFieldVec> {
     /// An upper bound to the values.
     u: usize,
@@ -193,6 +246,7 @@ impl<H, L> EliasFano<H, L> {
     }
 
     /// Estimate the size of an instance.
+    #[cfg(feature = "std")]
     pub fn estimate_size(u: usize, n: usize) -> usize {
         2 * n + (n * (u as f64 / n as f64).log2().ceil() as usize)
     }
@@ -233,6 +287,10 @@ let efo: EliasFano<QuantumIndex<CountBitVec>, BitFieldVec> =
 // Add also an index on the zeros  (accelerates precedessor and successor).
 let efoz: EliasFano<QuantumZeroIndex<QuantumIndex<CountBitVec>>, BitFieldVec> =
     efo.convert_to().unwrap();
+assert_eq!(efoz.get(1), 1);
+assert_eq!(efoz.succ(&1), Some((1, 1)));
+assert_eq!(efoz.pred(&4), Some((1, 1)));
+assert_eq!(efoz.succ_strict(&1), None);
 ```
 You have either of the indices, both, or none of them, but in the latter
 case all operations will be very slow, except iterating over the whole sequence.
@@ -274,6 +332,36 @@ impl<H: AsRef<[usize]> + Select, L: BitFieldSlice<usize>> IndexedDict for EliasF
         let low_bits = self.low_bits.get_unchecked(index);
         (high_bits << self.l) | low_bits
     }
+
+    /// Return whether the value is contained in the sequence using a binary
+    /// search, as values are monotonically increasing.
+    ///
+    /// Differently from [`binary_search`](SortedIndexedDict::binary_search),
+    /// this method does not need selection on zeroes.
+    fn contains(&self, value: &usize) -> bool {
+        let (mut lo, mut hi) = (0, self.n);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            // SAFETY: mid < hi <= n
+            if unsafe { self.get_unchecked(mid) } < *value {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        // SAFETY: lo < n
+        lo < self.n && unsafe { self.get_unchecked(lo) } == *value
+    }
+}
+
+/// Values are returned by value, as they are decoded on the fly.
+impl<H: AsRef<[usize]> + Select, L: BitFieldSlice<usize>> IndexedDictRef for EliasFano<H, L> {
+    type OutputRef<'a> = usize where Self: 'a;
+
+    #[inline(always)]
+    unsafe fn get_ref_unchecked(&self, index: usize) -> usize {
+        self.get_unchecked(index)
+    }
 }
 
 impl<'a, H: AsRef<[usize]> + Select, L: BitFieldSlice<usize>> IntoIterator for &'a EliasFano<H, L>
@@ -480,6 +568,53 @@ where
     }
 }
 
+/// The space is reported as `low_bits`, `high_bits` (the bit vector of
+/// the upper bits), `metadata`, and the components of the indices on the
+/// upper bits, if any.
+impl<H: SpaceUsage, L: SpaceUsage> SpaceUsage for EliasFano<H, L> {
+    fn mem_size(&self) -> usize {
+        core::mem::size_of::<Self>() - core::mem::size_of::<H>() - core::mem::size_of::<L>()
+            + self.high_bits.mem_size()
+            + self.low_bits.mem_size()
+    }
+
+    fn space_breakdown(&self) -> BTreeMap<&'static str, usize> {
+        let mut breakdown = BTreeMap::from([
+            ("low_bits", self.low_bits.mem_size()),
+            (
+                "metadata",
+                core::mem::size_of::<Self>()
+                    - core::mem::size_of::<H>()
+                    - core::mem::size_of::<L>(),
+            ),
+        ]);
+        merge_breakdown(
+            &mut breakdown,
+            self.high_bits.space_breakdown(),
+            "bits",
+            "high_bits",
+        );
+        breakdown
+    }
+}
+
+/// Binary search using [successors](Succ::succ), which
+/// skip directly to the first value with the same high bits.
+impl<H: SelectZero + Select + AsRef<[usize]>, L: BitFieldSlice<usize>> SortedIndexedDict
+    for EliasFano<H, L>
+where
+    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+{
+    fn binary_search(&self, value: &usize) -> Result<usize, usize> {
+        // The successor is the first value greater than or equal to value
+        match self.succ(value) {
+            Some((index, succ)) if succ == *value => Ok(index),
+            Some((index, _)) => Err(index),
+            None => Err(self.len()),
+        }
+    }
+}
+
 #[allow(clippy::collapsible_else_if)]
 impl<H: SelectZero + Select + AsRef<[usize]>, L: BitFieldSlice<usize>> Pred for EliasFano<H, L>
 where
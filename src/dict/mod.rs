@@ -10,5 +10,13 @@
 pub mod elias_fano;
 pub use elias_fano::{EliasFano, EliasFanoBuilder, EliasFanoConcurrentBuilder};
 
+#[cfg(feature = "std")]
 pub mod rear_coded_list;
+#[cfg(feature = "std")]
 pub use rear_coded_list::{RearCodedList, RearCodedListBuilder};
+
+pub mod rice_list;
+pub use rice_list::{RiceList, RiceListBuilder};
+
+pub mod sampled_sa;
+pub use sampled_sa::SampledSA;
@@ -11,13 +11,16 @@ Immutable lists of strings compressed by prefix omission via rear coding.
 
 */
 
-use crate::traits::IndexedDict;
+use crate::traits::{IndexedDict, SortedIndexedDict, SpaceUsage};
 use epserde::*;
 use lender::{ExactSizeLender, IntoLender, Lender, Lending};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Default)]
 /// Statistics of the encoded data.
-struct Stats {
+///
+/// See [`RearCodedListBuilder::stats`].
+pub struct Stats {
     /// Maximum block size in bytes.
     pub max_block_bytes: usize,
     /// The total sum of the block size in bytes.
@@ -40,6 +43,11 @@ struct Stats {
 
     /// The bytes wasted writing without compression the first string in block.
     pub redundancy: isize,
+
+    /// The total sum of the shared suffixes in bytes.
+    pub sum_shared_suffix: usize,
+    /// The number of bytes used to store the lengths of the shared suffixes.
+    pub suffix_code_bytes: usize,
 }
 
 /**
@@ -55,6 +63,12 @@ The encoding is done in blocks of `k` strings: in each block the first string is
 without compression, wheres the other strings are encoded with the common prefix
 removed.
 
+Optionally (see [`RearCodedListBuilder::strip_suffixes`]), the longest suffix
+shared with the previous string (and not overlapping the common prefix) can be
+removed, too, storing its length after the length of the rear. This is useful
+for lists in which consecutive strings often share a suffix, such as
+paths or URLs.
+
 */
 #[derive(Debug, Clone, Epserde)]
 pub struct RearCodedList<D: AsRef<[u8]> = Vec<u8>, P: AsRef<[usize]> = Vec<usize>> {
@@ -64,6 +78,8 @@ pub struct RearCodedList<D: AsRef<[u8]> = Vec<u8>, P: AsRef<[usize]> = Vec<usize
     len: usize,
     /// Whether the strings are sorted.
     is_sorted: bool,
+    /// Whether the suffixes shared with the previous string are removed.
+    strip_suffixes: bool,
     /// The encoded strings, `\0`-terminated.
     data: D,
     /// The pointer to the starting string of each block.
@@ -85,6 +101,11 @@ pub struct RearCodedListBuilder {
     stats: Stats,
     /// Cache of the last encoded string for incremental encoding.
     last_str: Vec<u8>,
+    /// Whether to check in debug mode that strings are pushed in strictly
+    /// increasing order.
+    check_sorted: bool,
+    /// Whether to remove the suffixes shared with the previous string.
+    strip_suffixes: bool,
 }
 
 /// Copy a string until the first `\0` from `data` to `result` and return the
@@ -102,6 +123,30 @@ fn strcpy<'a>(mut data: &'a [u8], result: &mut Vec<u8>) -> &'a [u8] {
     data
 }
 
+/// Decode a string encoded with respect to the previous one, which is
+/// contained in `result`, and return the remaining data.
+#[inline(always)]
+fn decode_next<'a>(data: &'a [u8], result: &mut Vec<u8>, strip_suffixes: bool) -> &'a [u8] {
+    // get how much data to throw away
+    let (len, data) = decode_int(data);
+    let keep = result.len() - len;
+    if !strip_suffixes {
+        // throw away the data
+        result.truncate(keep);
+        // copy the new suffix
+        return strcpy(data, result);
+    }
+    let (suffix_len, data) = decode_int(data);
+    // move the shared suffix right after the shared prefix
+    let suffix_start = result.len() - suffix_len;
+    result.copy_within(suffix_start.., keep);
+    result.truncate(keep + suffix_len);
+    // insert the middle bytes between the two
+    let middle_len = data.iter().position(|&c| c == 0).unwrap();
+    result.splice(keep..keep, data[..middle_len].iter().copied());
+    &data[middle_len + 1..]
+}
+
 #[inline(always)]
 /// strcmp but string is a Rust string and data is a `\0`-terminated string.
 fn strcmp(string: &[u8], data: &[u8]) -> core::cmp::Ordering {
@@ -139,9 +184,51 @@ impl RearCodedListBuilder {
             is_sorted: true,
             k,
             stats: Default::default(),
+            check_sorted: false,
+            strip_suffixes: false,
         }
     }
 
+    /// Set whether to remove from each string, besides the prefix shared with
+    /// the previous string, the longest suffix shared with the previous string
+    /// that does not overlap the shared prefix (default: `false`).
+    ///
+    /// This option costs the length of the shared suffix for each string that
+    /// is not the first of a block, and it pays off when consecutive strings
+    /// often share suffixes (e.g., file extensions or URL paths): the amount of
+    /// space saved with respect to plain prefix omission is reported by
+    /// [`print_stats`](RearCodedListBuilder::print_stats).
+    ///
+    /// # Panic
+    /// Panics if some strings have already been pushed.
+    #[inline]
+    pub fn strip_suffixes(mut self, strip_suffixes: bool) -> Self {
+        assert!(
+            self.len == 0,
+            "Suffix stripping must be set before pushing strings"
+        );
+        self.strip_suffixes = strip_suffixes;
+        self
+    }
+    /// Set whether to check in debug mode that strings are pushed in strictly
+    /// increasing lexicographical order (i.e., sorted and without duplicates),
+    /// which is necessary for [`RearCodedList::index_of`] to use a binary
+    /// search.
+    ///
+    /// If the check is enabled, debug builds panic on out-of-order or
+    /// repeated strings; in release builds, the check is not performed.
+    #[inline]
+    pub fn check_sorted(mut self, check_sorted: bool) -> Self {
+        self.check_sorted = check_sorted;
+        self
+    }
+
+    /// Return whether the strings pushed so far are sorted.
+    #[inline(always)]
+    pub fn is_sorted(&self) -> bool {
+        self.is_sorted
+    }
+
     #[inline]
     pub fn build(self) -> RearCodedList<Vec<u8>, Vec<usize>> {
         RearCodedList {
@@ -149,6 +236,7 @@ impl RearCodedListBuilder {
             pointers: self.pointers,
             len: self.len,
             is_sorted: self.is_sorted,
+            strip_suffixes: self.strip_suffixes,
             k: self.k,
         }
     }
@@ -173,6 +261,13 @@ impl RearCodedListBuilder {
         if order == core::cmp::Ordering::Greater {
             self.is_sorted = false;
         }
+        debug_assert!(
+            !self.check_sorted || self.len == 0 || order == core::cmp::Ordering::Less,
+            "String {:?} at index {} is not greater than the previous string {:?}",
+            string,
+            self.len,
+            String::from_utf8_lossy(&self.last_str)
+        );
 
         // at every multiple of k we just encode the string as is
         let to_encode = if self.len % self.k == 0 {
@@ -201,10 +296,22 @@ impl RearCodedListBuilder {
             let rear_length = self.last_str.len() - lcp;
             let prev_len = self.data.len();
             encode_int(rear_length, &mut self.data);
+            let suffix_len = if self.strip_suffixes {
+                let suffix_len =
+                    longest_common_suffix(&self.last_str[lcp..], &string.as_bytes()[lcp..]);
+                let prev_len = self.data.len();
+                encode_int(suffix_len, &mut self.data);
+                // update stats
+                self.stats.sum_shared_suffix += suffix_len;
+                self.stats.suffix_code_bytes += self.data.len() - prev_len;
+                suffix_len
+            } else {
+                0
+            };
             // update stats
             self.stats.code_bytes += self.data.len() - prev_len;
             // return the delta suffix
-            &string.as_bytes()[lcp..]
+            &string.as_bytes()[lcp..string.len() - suffix_len]
         };
         // Write the data to the buffer
         self.data.extend_from_slice(to_encode);
@@ -226,6 +333,27 @@ impl RearCodedListBuilder {
         }
     }
 
+    /// Return an estimate of the size in bytes of the structure that would be
+    /// returned by [`build`](RearCodedListBuilder::build), as serialized
+    /// by ε-serde.
+    ///
+    /// The estimate is updated as strings are pushed, and it includes the
+    /// encoded strings, the pointers to the blocks and the scalar fields, but
+    /// not the serialization header and alignment padding, which add a small
+    /// constant number of bytes.
+    pub fn estimated_size(&self) -> usize {
+        // k, len, is_sorted, strip_suffixes, and the lengths of data and pointers
+        4 * core::mem::size_of::<usize>()
+            + 2 * core::mem::size_of::<bool>()
+            + self.data.len()
+            + self.pointers.len() * core::mem::size_of::<usize>()
+    }
+
+    /// Return the statistics of the strings pushed so far.
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
     /// Print in an human readable format the statistics of the RCL
     pub fn print_stats(&self) {
         println!(
@@ -278,6 +406,16 @@ impl RearCodedListBuilder {
             (self.data.len() as isize - self.stats.redundancy) as usize,
         );
         human("redundancy", self.stats.redundancy as usize);
+        if self.strip_suffixes {
+            human("shared_suffix_bytes", self.stats.sum_shared_suffix);
+            human("suffix_codes_bytes", self.stats.suffix_code_bytes);
+            // the bytes saved with respect to plain prefix omission
+            println!(
+                "{:>20}: {:>10}",
+                "suffix_saved_bytes",
+                self.stats.sum_shared_suffix as isize - self.stats.suffix_code_bytes as isize
+            );
+        }
         let overhead = self.stats.redundancy + ptr_size as isize;
         println!(
             "overhead_ratio: {:>10}",
@@ -313,13 +451,40 @@ impl<D: AsRef<[u8]>, P: AsRef<[usize]>> RearCodedList<D, P> {
         let mut data = strcpy(data, result);
 
         for _ in 0..offset {
-            // get how much data to throw away
-            let (len, tmp) = decode_int(data);
-            // throw away the data
-            result.resize(result.len() - len, 0);
-            // copy the new suffix
-            let tmp = strcpy(tmp, result);
-            data = tmp;
+            data = decode_next(data, result, self.strip_suffixes);
+        }
+    }
+
+    /// Return whether the strings in the list are sorted.
+    #[inline(always)]
+    pub fn is_sorted(&self) -> bool {
+        self.is_sorted
+    }
+
+    /// Return the index of the given string in the list, or `None` if the
+    /// string is not in the list.
+    ///
+    /// If the list was built from sorted input, this method performs a binary
+    /// search on the first string of each block, followed by a linear scan of
+    /// at most `k` strings (see [`SortedIndexedDict::binary_search`]), and
+    /// returns the index of the first occurrence of the string; the result is
+    /// unique if the input contained no duplicates, which can be checked in
+    /// debug mode using [`RearCodedListBuilder::check_sorted`]. Otherwise, the
+    /// whole list is scanned.
+    pub fn index_of(&self, string: &str) -> Option<usize> {
+        if self.is_sorted {
+            self.binary_search(string).ok()
+        } else {
+            let key = string.as_bytes();
+            let mut iter = self.into_lender();
+            let mut index = 0;
+            while let Some(s) = iter.next() {
+                if key == s.as_bytes() {
+                    return Some(index);
+                }
+                index += 1;
+            }
+            None
         }
     }
 
@@ -327,7 +492,7 @@ impl<D: AsRef<[u8]>, P: AsRef<[usize]>> RearCodedList<D, P> {
         let key = key.as_bytes();
         let mut iter = self.into_lender();
         while let Some(string) = iter.next() {
-            if matches!(strcmp(key, string.as_bytes()), core::cmp::Ordering::Equal) {
+            if key == string.as_bytes() {
                 return true;
             }
         }
@@ -335,49 +500,7 @@ impl<D: AsRef<[u8]>, P: AsRef<[usize]>> RearCodedList<D, P> {
     }
 
     fn contains_sorted(&self, string: &<Self as IndexedDict>::Input) -> bool {
-        let string = string.as_bytes();
-        // first to a binary search on the blocks to find the block
-        let block_idx = self
-            .pointers
-            .as_ref()
-            .binary_search_by(|block_ptr| strcmp(string, &self.data.as_ref()[*block_ptr..]));
-
-        if block_idx.is_ok() {
-            return true;
-        }
-
-        let mut block_idx = block_idx.unwrap_err();
-        if block_idx == 0 || block_idx > self.pointers.as_ref().len() {
-            // the string is before the first block
-            return false;
-        }
-        block_idx -= 1;
-        // finish by a linear search on the block
-        let mut result = Vec::with_capacity(128);
-        let start = self.pointers.as_ref()[block_idx];
-        let data = &self.data.as_ref()[start..];
-
-        // decode the first string in the block
-        let mut data = strcpy(data, &mut result);
-        let in_block = (self.k - 1).min(self.len - block_idx * self.k - 1);
-        for _ in 0..in_block {
-            // get how much data to throw away
-            let (len, tmp) = decode_int(data);
-            let lcp = result.len() - len;
-            // throw away the data
-            result.resize(lcp, 0);
-            // copy the new suffix
-            let tmp = strcpy(tmp, &mut result);
-            data = tmp;
-
-            // TODO!: this can be optimized to avoid the copy
-            match strcmp_rust(string, &result) {
-                core::cmp::Ordering::Less => {}
-                core::cmp::Ordering::Equal => return true,
-                core::cmp::Ordering::Greater => return false,
-            }
-        }
-        false
+        self.binary_search(string).is_ok()
     }
 }
 
@@ -421,6 +544,73 @@ impl<D: AsRef<[u8]>, P: AsRef<[usize]>> IndexedDict for RearCodedList<D, P> {
     }
 }
 
+/// Binary search on the first string of each block, followed
+/// by a linear scan.
+///
+/// # Panics
+/// This implementation will panic if the strings are not sorted.
+impl<D: AsRef<[u8]>, P: AsRef<[usize]>> SortedIndexedDict for RearCodedList<D, P> {
+    fn binary_search(&self, string: &Self::Input) -> Result<usize, usize> {
+        assert!(self.is_sorted, "The strings in the list are not sorted");
+        if self.len == 0 {
+            return Err(0);
+        }
+        let string = string.as_bytes();
+        // find the number of blocks whose first string is smaller than string
+        let block_idx = self
+            .pointers
+            .as_ref()
+            .binary_search_by(
+                |block_ptr| match strcmp(string, &self.data.as_ref()[*block_ptr..]) {
+                    core::cmp::Ordering::Equal => core::cmp::Ordering::Greater,
+                    ord => ord,
+                },
+            )
+            .unwrap_err();
+
+        // the first occurrence of string, if any, is in the previous block
+        // or it is the first string of this block
+        let start = block_idx.saturating_sub(1) * self.k;
+        let mut iter = Iterator::new_from(self, start);
+        let mut index = start;
+        while let Some(result) = iter.next() {
+            // TODO!: this can be optimized to avoid the copy
+            match strcmp_rust(string, result.as_bytes()) {
+                core::cmp::Ordering::Less => {}
+                core::cmp::Ordering::Equal => return Ok(index),
+                core::cmp::Ordering::Greater => return Err(index),
+            }
+            index += 1;
+        }
+        Err(index)
+    }
+}
+
+/// The space is reported as `data` (the encoded strings), `pointers` and
+/// `metadata`.
+impl<D: AsRef<[u8]> + SpaceUsage, P: AsRef<[usize]> + SpaceUsage> SpaceUsage
+    for RearCodedList<D, P>
+{
+    fn mem_size(&self) -> usize {
+        core::mem::size_of::<Self>() - core::mem::size_of::<D>() - core::mem::size_of::<P>()
+            + self.data.mem_size()
+            + self.pointers.mem_size()
+    }
+
+    fn space_breakdown(&self) -> BTreeMap<&'static str, usize> {
+        BTreeMap::from([
+            ("data", self.data.mem_size()),
+            ("pointers", self.pointers.mem_size()),
+            (
+                "metadata",
+                core::mem::size_of::<Self>()
+                    - core::mem::size_of::<D>()
+                    - core::mem::size_of::<P>(),
+            ),
+        ])
+    }
+}
+
 /// Sequential iterator over the strings.
 pub struct Iterator<'a, D: AsRef<[u8]>, P: AsRef<[usize]>> {
     rca: &'a RearCodedList<D, P>,
@@ -429,6 +619,12 @@ pub struct Iterator<'a, D: AsRef<[u8]>, P: AsRef<[usize]>> {
     index: usize,
 }
 
+/// Sequential iterator over the strings returning owned values.
+///
+/// The iterator keeps the current decoded string across calls, so iterating
+/// over the whole list requires linear time, whereas calling
+/// [`get`](IndexedDict::get) repeatedly would decode from the last
+/// checkpoint at each call.
 pub struct ValueIterator<'a, D: AsRef<[u8]>, P: AsRef<[usize]>> {
     iter: Iterator<'a, D, P>,
 }
@@ -441,9 +637,21 @@ impl<'a, D: AsRef<[u8]>, P: AsRef<[usize]>> std::iter::Iterator for ValueIterato
             .next()
             .map(|v| unsafe { String::from_utf8_unchecked(Vec::from(v)) })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = ExactSizeLender::len(&self.iter);
+        (len, Some(len))
+    }
+}
+
+impl<'a, D: AsRef<[u8]>, P: AsRef<[usize]>> ExactSizeIterator for ValueIterator<'a, D, P> {
+    fn len(&self) -> usize {
+        ExactSizeLender::len(&self.iter)
+    }
 }
 
 impl<'a, D: AsRef<[u8]>, P: AsRef<[usize]>> Iterator<'a, D, P> {
+    /// Create a new iterator starting at the first string.
     pub fn new(rca: &'a RearCodedList<D, P>) -> Self {
         Self {
             rca,
@@ -453,7 +661,22 @@ impl<'a, D: AsRef<[u8]>, P: AsRef<[usize]>> Iterator<'a, D, P> {
         }
     }
 
+    /// Create a new iterator starting at the given index.
+    ///
+    /// # Panic
+    /// Panics if `start_index` is greater than the length of the list.
     pub fn new_from(rca: &'a RearCodedList<D, P>, start_index: usize) -> Self {
+        if start_index > rca.len() {
+            panic!("Start index out of bounds: {} > {}", start_index, rca.len());
+        }
+        if start_index == rca.len() {
+            return Iterator {
+                rca,
+                index: start_index,
+                data: &[],
+                buffer: Vec::new(),
+            };
+        }
         let block = start_index / rca.k;
         let offset = start_index % rca.k;
 
@@ -490,9 +713,7 @@ impl<'a, D: AsRef<[u8]>, P: AsRef<[usize]>> Lender for Iterator<'a, D, P> {
             self.buffer.clear();
             self.data = strcpy(self.data, &mut self.buffer);
         } else {
-            let (len, tmp) = decode_int(self.data);
-            self.buffer.resize(self.buffer.len() - len, 0);
-            self.data = strcpy(tmp, &mut self.buffer);
+            self.data = decode_next(self.data, &mut self.buffer, self.rca.strip_suffixes);
         }
         self.index += 1;
 
@@ -518,11 +739,42 @@ impl<'a, D: AsRef<[u8]>, P: AsRef<[usize]>> IntoIterator for &'a RearCodedList<D
 }
 
 impl<D: AsRef<[u8]>, P: AsRef<[usize]>> RearCodedList<D, P> {
-    pub fn into_iter_from(&self, from: usize) -> ValueIterator<'_, D, P> {
+    /// Return an iterator over the strings.
+    ///
+    /// See [`ValueIterator`]; if you do not need to keep the strings around,
+    /// the [`Lender`] returned by [`IntoLender::into_lender`] avoids allocating
+    /// a new string at each step.
+    #[inline(always)]
+    pub fn iter(&self) -> ValueIterator<'_, D, P> {
+        self.iter_from(0)
+    }
+
+    /// Return an iterator over the strings starting at the given index.
+    ///
+    /// # Panic
+    /// Panics if `from` is greater than the length of the list.
+    #[inline(always)]
+    pub fn iter_from(&self, from: usize) -> ValueIterator<'_, D, P> {
         ValueIterator {
             iter: Iterator::new_from(self, from),
         }
     }
+
+    /// Same as [`iter_from`](RearCodedList::iter_from).
+    #[inline(always)]
+    pub fn into_iter_from(&self, from: usize) -> ValueIterator<'_, D, P> {
+        self.iter_from(from)
+    }
+}
+
+#[inline(always)]
+/// Compute the length of the longest common suffix between two strings as bytes.
+fn longest_common_suffix(a: &[u8], b: &[u8]) -> usize {
+    a.iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count()
 }
 
 #[inline(always)]
@@ -0,0 +1,608 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Immutable lists of monotone integers compressed by Rice coding of the gaps.
+
+The main trait implemented by [`RiceList`] is [`IndexedDict`], which
+makes it possible to access its values with [`IndexedDict::get`];
+[`Succ`], [`Pred`] and [`SortedIndexedDict`] are implemented, too.
+
+*/
+
+use crate::prelude::*;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use anyhow::{bail, Result};
+#[cfg(feature = "std")]
+use epserde::*;
+
+/// The largest Rice parameter.
+const MAX_LOG2_B: usize = 63;
+
+/// A builder for [`RiceList`].
+///
+/// After creating an instance with [`RiceListBuilder::new`], you can use
+/// [`RiceListBuilder::push`] to add new values, and
+/// [`RiceListBuilder::build`] to obtain the list.
+///
+/// The encoding is done in blocks of `k` values: in each block the first value
+/// is stored explicitly, whereas the other values are encoded by the Rice
+/// code of their gap from the previous value.
+///
+/// If the Rice parameter is set explicitly with
+/// [`RiceListBuilder::log2_b`], values are encoded as they are pushed;
+/// otherwise, they are buffered, and the parameter is chosen from their
+/// average gap when [`RiceListBuilder::build`] is called.
+#[derive(Debug, Clone)]
+pub struct RiceListBuilder {
+    /// The number of values in a block; this value trades off compression for speed.
+    k: usize,
+    /// The base-2 logarithm of the Rice parameter, if set explicitly.
+    log2_b: Option<usize>,
+    /// The values pushed so far, if the Rice parameter has not been set.
+    values: Vec<u64>,
+    /// Number of pushed values.
+    len: usize,
+    /// The last pushed value.
+    last_value: u64,
+    /// The Rice-coded gaps.
+    data: Vec<u64>,
+    /// The number of bits used in `data`.
+    bit_len: usize,
+    /// For each block, the first value and its bit offset in `data`.
+    samples: Vec<u64>,
+}
+
+impl RiceListBuilder {
+    /// Create a builder for a [`RiceList`] with blocks of `k` values.
+    ///
+    /// # Panics
+    /// This method will panic if `k` is zero.
+    pub fn new(k: usize) -> Self {
+        assert!(k != 0, "The block size must be positive");
+        Self {
+            k,
+            log2_b: None,
+            values: Vec::new(),
+            len: 0,
+            last_value: 0,
+            data: Vec::new(),
+            bit_len: 0,
+            samples: Vec::new(),
+        }
+    }
+
+    /// Set the base-2 logarithm of the Rice parameter, rather than choosing
+    /// it from the average gap.
+    ///
+    /// Gaps whose quotient by 2<sup>`log2_b`</sup> is large are encoded by long
+    /// unary codes, so an explicit parameter should be chosen only if the
+    /// distribution of the gaps is known in advance.
+    ///
+    /// # Panics
+    /// This method will panic if `log2_b` is larger than 63 or if values
+    /// have already been pushed.
+    pub fn log2_b(mut self, log2_b: usize) -> Self {
+        assert!(
+            log2_b <= MAX_LOG2_B,
+            "The Rice parameter is too large: {} > {}",
+            log2_b,
+            MAX_LOG2_B
+        );
+        assert!(
+            self.len == 0,
+            "The Rice parameter must be set before pushing values"
+        );
+        self.log2_b = Some(log2_b);
+        self
+    }
+
+    /// Return the number of values pushed so far.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return true if no value has been pushed.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Add a new value to the builder.
+    ///
+    /// Returns an error if the value is smaller than the last provided value.
+    pub fn push(&mut self, value: u64) -> Result<()> {
+        if self.len != 0 && value < self.last_value {
+            bail!(
+                "The values given to the Rice list are not monotone: {} < {}",
+                value,
+                self.last_value
+            );
+        }
+        match self.log2_b {
+            Some(log2_b) => self.encode(value, log2_b),
+            None => {
+                self.values.push(value);
+                self.last_value = value;
+                self.len += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Append all the values from an iterator to the end of the list.
+    ///
+    /// Returns an error if the values are not monotone.
+    pub fn extend<I: IntoIterator<Item = u64>>(&mut self, iter: I) -> Result<()> {
+        for value in iter {
+            self.push(value)?;
+        }
+        Ok(())
+    }
+
+    /// Encode a value after the last pushed one.
+    fn encode(&mut self, value: u64, log2_b: usize) {
+        if self.len % self.k == 0 {
+            // at every multiple of k we just store the value as is
+            self.samples.push(value);
+            self.samples.push(self.bit_len as u64);
+        } else {
+            let gap = value - self.last_value;
+            self.write_unary(gap >> log2_b);
+            self.write_bits(gap & ((1 << log2_b) - 1), log2_b);
+        }
+        self.last_value = value;
+        self.len += 1;
+    }
+
+    /// Write `q` zeroes followed by a one.
+    fn write_unary(&mut self, q: u64) {
+        let pos = self.bit_len + q as usize;
+        self.data.resize(pos / 64 + 1, 0);
+        self.data[pos / 64] |= 1 << (pos % 64);
+        self.bit_len = pos + 1;
+    }
+
+    /// Write the `n` lower bits of `value`, where `n` is at most 63.
+    fn write_bits(&mut self, value: u64, n: usize) {
+        if n == 0 {
+            return;
+        }
+        let bit = self.bit_len % 64;
+        if bit == 0 {
+            self.data.push(0);
+        }
+        *self.data.last_mut().unwrap() |= value << bit;
+        if bit + n > 64 {
+            self.data.push(value >> (64 - bit));
+        }
+        self.bit_len += n;
+    }
+
+    /// Choose the base-2 logarithm of the Rice parameter from the average
+    /// gap between the buffered values.
+    ///
+    /// For geometrically distributed gaps, the optimal Golomb modulus is
+    /// approximately ln 2 times the average gap; we use the largest power of
+    /// two not exceeding it.
+    fn auto_log2_b(&self) -> usize {
+        let (mut sum, mut count) = (0_u128, 0_u128);
+        for (i, pair) in self.values.windows(2).enumerate() {
+            // gaps to the first value of a block are not encoded
+            if (i + 1) % self.k != 0 {
+                sum += (pair[1] - pair[0]) as u128;
+                count += 1;
+            }
+        }
+        if count == 0 {
+            return 0;
+        }
+        // 45426 / 65536 approximates ln 2
+        let b = sum * 45426 / 65536 / count;
+        if b == 0 {
+            0
+        } else {
+            Ord::min(b.ilog2() as usize, MAX_LOG2_B)
+        }
+    }
+
+    /// Build the list, choosing the Rice parameter if it was not set
+    /// explicitly.
+    pub fn build(self) -> RiceList {
+        let mut builder = match self.log2_b {
+            Some(_) => self,
+            None => {
+                let log2_b = self.auto_log2_b();
+                let mut builder = Self::new(self.k).log2_b(log2_b);
+                for value in self.values {
+                    builder.encode(value, log2_b);
+                }
+                builder
+            }
+        };
+        builder.data.shrink_to_fit();
+        builder.samples.shrink_to_fit();
+        RiceList {
+            k: builder.k,
+            len: builder.len,
+            log2_b: builder.log2_b.unwrap(),
+            data: builder.data,
+            samples: builder.samples,
+        }
+    }
+}
+
+/// Immutable lists of monotone integers compressed by Rice coding of the gaps.
+///
+/// The list is divided in blocks of `k` values: the first value of each
+/// block is stored explicitly, together with a pointer to the bit stream
+/// containing the gaps between the following values of the block, which are
+/// encoded using a [Rice code](https://en.wikipedia.org/wiki/Golomb_coding)
+/// of parameter 2<sup>`log2_b`</sup>: the quotient of a gap by the parameter
+/// is written in unary, and the remainder in binary using `log2_b` bits.
+///
+/// Thus, [`IndexedDict::get`] decodes at most `k` &minus; 1 gaps, and
+/// [`Succ`], [`Pred`] and [`SortedIndexedDict`] perform a binary search on
+/// the first values of the blocks followed by a scan of at most two blocks.
+///
+/// Rice codes are optimal for geometrically distributed gaps, such as those
+/// of a random subset of an interval, in which case they use about
+/// `log2_b` + 2 bits per value; the first values of the blocks use 128 bits
+/// every `k` values. Instances are built using a [`RiceListBuilder`].
+#[cfg_attr(feature = "std", derive(Epserde))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RiceList<D: AsRef<[u64]> = Vec<u64>, S: AsRef<[u64]> = Vec<u64>> {
+    /// The number of values in a block.
+    k: usize,
+    /// The number of values.
+    len: usize,
+    /// The base-2 logarithm of the Rice parameter.
+    log2_b: usize,
+    /// The Rice-coded gaps.
+    data: D,
+    /// For each block, the first value and its bit offset in `data`.
+    samples: S,
+}
+
+/// A reader of Rice-coded gaps.
+struct GapReader<'a> {
+    data: &'a [u64],
+    pos: usize,
+}
+
+impl<'a> GapReader<'a> {
+    /// Read a gap encoded with the given Rice parameter.
+    ///
+    /// # Safety
+    /// There must be an encoded gap at the current position.
+    #[inline(always)]
+    unsafe fn read_gap(&mut self, log2_b: usize) -> u64 {
+        // read the unary code of the quotient
+        let mut word_idx = self.pos / 64;
+        let mut window = *self.data.get_unchecked(word_idx) >> (self.pos % 64);
+        let mut quotient = 0;
+        if window == 0 {
+            quotient = 64 - self.pos % 64;
+            loop {
+                word_idx += 1;
+                window = *self.data.get_unchecked(word_idx);
+                if window != 0 {
+                    break;
+                }
+                quotient += 64;
+            }
+        }
+        quotient += window.trailing_zeros() as usize;
+        self.pos += quotient + 1;
+
+        if log2_b == 0 {
+            return quotient as u64;
+        }
+        // read the remainder
+        let bit = self.pos % 64;
+        let mut remainder = *self.data.get_unchecked(self.pos / 64) >> bit;
+        if bit + log2_b > 64 {
+            remainder |= *self.data.get_unchecked(self.pos / 64 + 1) << (64 - bit);
+        }
+        self.pos += log2_b;
+        ((quotient as u64) << log2_b) | (remainder & ((1 << log2_b) - 1))
+    }
+}
+
+impl<D: AsRef<[u64]>, S: AsRef<[u64]>> RiceList<D, S> {
+    /// Return the number of values in a block.
+    #[inline(always)]
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Return the base-2 logarithm of the Rice parameter.
+    #[inline(always)]
+    pub fn log2_b(&self) -> usize {
+        self.log2_b
+    }
+
+    /// Return the number of blocks.
+    #[inline(always)]
+    pub fn num_blocks(&self) -> usize {
+        self.samples.as_ref().len() / 2
+    }
+
+    /// Return an iterator over the values starting at the given index.
+    ///
+    /// # Panics
+    /// This method will panic if `from` is greater than the length of the list.
+    pub fn into_iter_from(&self, from: usize) -> RiceListIterator<'_, D, S> {
+        RiceListIterator::new_from(self, from)
+    }
+
+    /// Return the first value of a block and a reader positioned at its gaps.
+    ///
+    /// # Safety
+    /// `block` must be smaller than the number of blocks.
+    #[inline(always)]
+    unsafe fn block(&self, block: usize) -> (u64, GapReader<'_>) {
+        let samples = self.samples.as_ref();
+        (
+            *samples.get_unchecked(block * 2),
+            GapReader {
+                data: self.data.as_ref(),
+                pos: *samples.get_unchecked(block * 2 + 1) as usize,
+            },
+        )
+    }
+
+    /// Return the number of blocks whose first value is smaller than
+    /// the given value, or smaller than or equal to it if `or_equal` is true.
+    fn num_blocks_below(&self, value: u64, or_equal: bool) -> usize {
+        let samples = self.samples.as_ref();
+        let (mut lo, mut hi) = (0, self.num_blocks());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let first = samples[mid * 2];
+            if first < value || (or_equal && first == value) {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: AsRef<[u64]> + SpaceUsage, S: AsRef<[u64]> + SpaceUsage> RiceList<D, S> {
+    /// Print in an human readable format the statistics of the list.
+    pub fn print_stats(&self) {
+        println!("{:>20}: {:>10}", "len", self.len);
+        println!("{:>20}: {:>10}", "k", self.k);
+        println!("{:>20}: {:>10}", "log2_b", self.log2_b);
+        println!("{:>20}: {:>10}", "blocks", self.num_blocks());
+        println!("{:>20}: {:>10}", "data_bytes", self.data.mem_size());
+        println!("{:>20}: {:>10}", "samples_bytes", self.samples.mem_size());
+        println!("{:>20}: {:>10}", "total_bytes", self.mem_size());
+        println!(
+            "{:>20}: {:>10.3}",
+            "bits/gap",
+            self.data.as_ref().len() as f64 * 64.0 / (self.len - self.num_blocks()) as f64
+        );
+        println!(
+            "{:>20}: {:>10.3}",
+            "bits/element",
+            self.mem_size() as f64 * 8.0 / self.len as f64
+        );
+    }
+}
+
+impl<D: AsRef<[u64]>, S: AsRef<[u64]>> IndexedDict for RiceList<D, S> {
+    type Input = u64;
+    type Output = u64;
+
+    unsafe fn get_unchecked(&self, index: usize) -> u64 {
+        let (mut value, mut reader) = self.block(index / self.k);
+        for _ in 0..index % self.k {
+            value += reader.read_gap(self.log2_b);
+        }
+        value
+    }
+
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return whether the value is contained in the list using a
+    /// [binary search](SortedIndexedDict::binary_search).
+    #[inline]
+    fn contains(&self, value: &u64) -> bool {
+        self.binary_search(value).is_ok()
+    }
+}
+
+impl<D: AsRef<[u64]>, S: AsRef<[u64]>> IndexedDictRef for RiceList<D, S> {
+    type OutputRef<'a> = u64 where Self: 'a;
+
+    #[inline(always)]
+    unsafe fn get_ref_unchecked(&self, index: usize) -> u64 {
+        self.get_unchecked(index)
+    }
+}
+
+impl<D: AsRef<[u64]>, S: AsRef<[u64]>> Succ for RiceList<D, S> {
+    unsafe fn succ_unchecked<const STRICT: bool>(&self, value: &u64) -> (usize, u64) {
+        if STRICT {
+            debug_assert!(*value < self.get(self.len() - 1));
+        } else {
+            debug_assert!(*value <= self.get(self.len() - 1));
+        }
+        // the successor is in the last block whose first value is
+        // (strictly) smaller than value, or it is the first value of the
+        // following block
+        let block = self.num_blocks_below(*value, STRICT).saturating_sub(1);
+        let mut index = block * self.k;
+        for res in self.into_iter_from(index) {
+            if (STRICT && res > *value) || (!STRICT && res >= *value) {
+                return (index, res);
+            }
+            index += 1;
+        }
+        unreachable!("No successor of {}", value);
+    }
+}
+
+impl<D: AsRef<[u64]>, S: AsRef<[u64]>> Pred for RiceList<D, S> {
+    unsafe fn pred_unchecked<const STRICT: bool>(&self, value: &u64) -> (usize, u64) {
+        if STRICT {
+            debug_assert!(*value > self.get(0));
+        } else {
+            debug_assert!(*value >= self.get(0));
+        }
+        // the predecessor is in the last block whose first value is
+        // smaller than (or equal to, if not strict) value
+        let block = self.num_blocks_below(*value, !STRICT) - 1;
+        let start = block * self.k;
+        let (mut res, mut reader) = self.block(block);
+        let mut index = start;
+        let end = Ord::min(start + self.k, self.len);
+        while index + 1 < end {
+            let next = res + reader.read_gap(self.log2_b);
+            if (STRICT && next >= *value) || (!STRICT && next > *value) {
+                break;
+            }
+            res = next;
+            index += 1;
+        }
+        (index, res)
+    }
+}
+
+/// Binary search using [successors](Succ::succ), which
+/// use a binary search on the first values of the blocks.
+impl<D: AsRef<[u64]>, S: AsRef<[u64]>> SortedIndexedDict for RiceList<D, S> {
+    fn binary_search(&self, value: &u64) -> Result<usize, usize> {
+        // The successor is the first value greater than or equal to value
+        match self.succ(value) {
+            Some((index, succ)) if succ == *value => Ok(index),
+            Some((index, _)) => Err(index),
+            None => Err(self.len()),
+        }
+    }
+}
+
+impl<'a, D: AsRef<[u64]>, S: AsRef<[u64]>> IntoIterator for &'a RiceList<D, S> {
+    type Item = u64;
+    type IntoIter = RiceListIterator<'a, D, S>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        RiceListIterator::new(self)
+    }
+}
+
+/// The space is reported as `data` (the Rice-coded gaps), `samples`
+/// and `metadata`.
+impl<D: AsRef<[u64]> + SpaceUsage, S: AsRef<[u64]> + SpaceUsage> SpaceUsage for RiceList<D, S> {
+    fn mem_size(&self) -> usize {
+        core::mem::size_of::<Self>() - core::mem::size_of::<D>() - core::mem::size_of::<S>()
+            + self.data.mem_size()
+            + self.samples.mem_size()
+    }
+
+    fn space_breakdown(&self) -> BTreeMap<&'static str, usize> {
+        BTreeMap::from([
+            ("data", self.data.mem_size()),
+            ("samples", self.samples.mem_size()),
+            (
+                "metadata",
+                core::mem::size_of::<Self>()
+                    - core::mem::size_of::<D>()
+                    - core::mem::size_of::<S>(),
+            ),
+        ])
+    }
+}
+
+/// Sequential iterator over the values of a [`RiceList`].
+pub struct RiceListIterator<'a, D: AsRef<[u64]>, S: AsRef<[u64]>> {
+    list: &'a RiceList<D, S>,
+    /// The index of the next value that will be returned.
+    index: usize,
+    /// The last returned value.
+    value: u64,
+    reader: GapReader<'a>,
+}
+
+impl<'a, D: AsRef<[u64]>, S: AsRef<[u64]>> RiceListIterator<'a, D, S> {
+    pub fn new(list: &'a RiceList<D, S>) -> Self {
+        Self::new_from(list, 0)
+    }
+
+    pub fn new_from(list: &'a RiceList<D, S>, start_index: usize) -> Self {
+        if start_index > list.len() {
+            panic!("Index out of bounds: {} > {}", start_index, list.len());
+        }
+        let offset = start_index % list.k;
+        if start_index == list.len() || offset == 0 {
+            // the next call to next will read the first value of a block
+            return Self {
+                list,
+                index: start_index,
+                value: 0,
+                reader: GapReader {
+                    data: list.data.as_ref(),
+                    pos: 0,
+                },
+            };
+        }
+        // SAFETY: start_index is within the list
+        let (mut value, mut reader) = unsafe { list.block(start_index / list.k) };
+        for _ in 1..offset {
+            value += unsafe { reader.read_gap(list.log2_b) };
+        }
+        Self {
+            list,
+            index: start_index,
+            value,
+            reader,
+        }
+    }
+}
+
+impl<'a, D: AsRef<[u64]>, S: AsRef<[u64]>> Iterator for RiceListIterator<'a, D, S> {
+    type Item = u64;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<u64> {
+        if self.index >= self.list.len() {
+            return None;
+        }
+        // SAFETY: the index is within the list
+        unsafe {
+            if self.index % self.list.k == 0 {
+                (self.value, self.reader) = self.list.block(self.index / self.list.k);
+            } else {
+                self.value += self.reader.read_gap(self.list.log2_b);
+            }
+        }
+        self.index += 1;
+        Some(self.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len(), Some(self.len()))
+    }
+}
+
+impl<'a, D: AsRef<[u64]>, S: AsRef<[u64]>> ExactSizeIterator for RiceListIterator<'a, D, S> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.list.len() - self.index
+    }
+}
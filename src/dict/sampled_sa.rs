@@ -0,0 +1,166 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Sampled suffix arrays.
+
+A [`SampledSA`] stores a suffix array of a text of length <var>n</var> by
+keeping only the values that are multiples of a sample rate <var>s</var>. Each
+stored value is divided by <var>s</var> and packed in a [`BitFieldVec`], and
+a [`BitVec`] indexed by [`Rank9`] marks the positions of the suffix array
+that have been sampled.
+
+The remaining values are reconstructed using the LF-mapping of the text
+(usually provided by an FM-index), which must be passed to
+[`SampledSA::get`]: since SA\[LF(<var>i</var>)\] = SA\[<var>i</var>\] &minus; 1,
+at most <var>s</var> &minus; 1 applications of the LF-mapping reach a sampled
+position.
+
+*/
+
+use crate::prelude::*;
+use crate::traits::bit_field_slice::*;
+use anyhow::{bail, Result};
+#[cfg(feature = "std")]
+use epserde::*;
+
+/// A suffix array sampled at a fixed rate.
+///
+/// See the [module documentation](self) for more details.
+#[cfg_attr(feature = "std", derive(Epserde))]
+#[derive(Debug)]
+pub struct SampledSA<M = Rank9, S: BitFieldSlice<usize> = BitFieldVec> {
+    /// The sample rate.
+    sample_rate: usize,
+    /// A bit vector marking the sampled positions, with a rank index.
+    marks: M,
+    /// The sampled values, divided by the sample rate.
+    samples: S,
+}
+
+impl SampledSA<Rank9, BitFieldVec> {
+    /// Create a new sampled suffix array from a full suffix array,
+    /// keeping the values that are multiples of `sample_rate`.
+    ///
+    /// `sa` must be a permutation of 0..`sa.len()`.
+    pub fn new(sa: &[usize], sample_rate: usize) -> Result<Self> {
+        if sample_rate == 0 {
+            bail!("The sample rate must be positive");
+        }
+        let n = sa.len();
+        let max_sample = n.saturating_sub(1) / sample_rate;
+        let bit_width = (usize::BITS - max_sample.leading_zeros()) as usize;
+
+        let mut marks = BitVec::new(n);
+        let mut samples = BitFieldVec::new(bit_width, 0);
+        let mut seen = BitVec::new(n);
+        for (i, &value) in sa.iter().enumerate() {
+            if value >= n {
+                bail!(
+                    "Suffix array value {} at position {} is not smaller than the length {}",
+                    value,
+                    i,
+                    n
+                );
+            }
+            if seen.get(value) {
+                bail!("Suffix array value {} appears twice", value);
+            }
+            seen.set(value, true);
+            if value % sample_rate == 0 {
+                marks.set(i, true);
+                samples.push(value / sample_rate);
+            }
+        }
+        Ok(Self {
+            sample_rate,
+            marks: Rank9::new(marks),
+            samples,
+        })
+    }
+}
+
+impl<M: Rank + AsRef<[usize]>, S: BitFieldSlice<usize>> SampledSA<M, S> {
+    /// Return the length of the suffix array.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        BitLength::len(&self.marks)
+    }
+
+    /// Return true if the suffix array is empty.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the sample rate.
+    #[inline(always)]
+    pub fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    /// Return the number of sampled values.
+    #[inline(always)]
+    pub fn num_samples(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Return true if the value at position `index` is sampled.
+    ///
+    /// # Panics
+    /// If `index` is out of bounds.
+    #[inline(always)]
+    pub fn is_sampled(&self, index: usize) -> bool {
+        assert!(
+            index < self.len(),
+            "Index out of bounds: {} >= {}",
+            index,
+            self.len()
+        );
+        let bits = usize::BITS as usize;
+        self.marks.as_ref()[index / bits] >> (index % bits) & 1 != 0
+    }
+
+    /// Return the value at position `index` of the suffix array, if it is
+    /// sampled.
+    ///
+    /// # Panics
+    /// If `index` is out of bounds.
+    #[inline]
+    pub fn get_sampled(&self, index: usize) -> Option<usize> {
+        if self.is_sampled(index) {
+            // SAFETY: index is within bounds and there are
+            // self.marks.rank(index) samples before it
+            Some(
+                unsafe { self.samples.get_unchecked(self.marks.rank_unchecked(index)) }
+                    * self.sample_rate,
+            )
+        } else {
+            None
+        }
+    }
+
+    /// Return the value at position `index` of the suffix array, using
+    /// the LF-mapping `lf` of the text to reach a sampled position.
+    ///
+    /// At most [`sample_rate`](SampledSA::sample_rate) &minus; 1 calls to `lf`
+    /// are performed.
+    ///
+    /// # Panics
+    /// If `index` is out of bounds.
+    pub fn get(&self, mut index: usize, lf: impl Fn(usize) -> usize) -> usize {
+        let mut steps = 0;
+        loop {
+            if let Some(value) = self.get_sampled(index) {
+                return value + steps;
+            }
+            index = lf(index);
+            steps += 1;
+        }
+    }
+}
@@ -26,3 +26,6 @@ serialized, and used later.
 mod vfunc;
 pub use vfunc::VFunc;
 pub use vfunc::VFuncBuilder;
+pub use vfunc::{BuildStats, Phase, VFuncError};
+mod vfunc_map;
+pub use vfunc_map::VFuncMap;
@@ -13,15 +13,18 @@ use anyhow::bail;
 use arbitrary_chunks::ArbitraryChunks;
 use bit_field_slice::BitFieldSliceCore;
 use bit_field_slice::Word;
-use common_traits::{AsBytes, AtomicUnsignedInt, IntoAtomic};
+use common_traits::{AsBytes, AtomicUnsignedInt, CastableFrom, IntoAtomic};
 use dsi_progress_logger::*;
 use epserde::prelude::*;
 use log::warn;
+#[cfg(feature = "rayon")]
 use rayon::prelude::*;
-use std::borrow::Cow;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::borrow::{Borrow, Cow};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Mutex;
 use std::thread;
+use std::time::{Duration, Instant};
 use Ordering::Relaxed;
 
 const PARAMS: [(usize, u32, f64); 15] = [
@@ -42,6 +45,14 @@ const PARAMS: [(usize, u32, f64); 15] = [
     (60180252, 9, 1.10),
 ];
 
+/// The number of keys whose variables are computed before accessing the
+/// values in [`VFunc::get_batch`].
+const BATCH_SLAB: usize = 32;
+
+/// The number of keys processed by each task in [`VFunc::par_get_batch`].
+#[cfg(feature = "rayon")]
+const PAR_BATCH_CHUNK: usize = 1 << 14;
+
 /**
 
 An edge list represented by a 64-bit integer. The lower DEG_SHIFT bits
@@ -126,6 +137,41 @@ fn edge(sig: &[u64; 2], log2_l: u32, segment_size: usize) -> [usize; 3] {
     ]
 }
 
+/// The number of high bits of the first half of a signature that are
+/// not modified by [`remix`]; it must be at least the maximum number of
+/// high bits used to define chunks.
+const REMIX_FIXED_BITS: u32 = 16;
+
+/// The 64-bit finalizer of MurmurHash3.
+#[inline(always)]
+fn fmix64(mut x: u64) -> u64 {
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    x ^ x >> 33
+}
+
+/// Remix a signature using a two-round Feistel network keyed by `remix`,
+/// leaving unchanged the [`REMIX_FIXED_BITS`] high bits of the first half.
+///
+/// The mapping is a bijection, so distinct signatures remain distinct, and
+/// since the bits defining chunks are unchanged, signatures can be remixed
+/// without re-bucketing. It is the identity if `remix` is zero.
+///
+/// Remixing is used by [`VFuncBuilder::build_from_stream`], which cannot
+/// rehash the keys with a different seed when a construction attempt fails.
+#[inline(always)]
+#[must_use]
+fn remix(sig: &[u64; 2], remix: u64) -> [u64; 2] {
+    if remix == 0 {
+        return *sig;
+    }
+    let sig1 = sig[1] ^ fmix64(sig[0] ^ remix);
+    let sig0 = sig[0] ^ (fmix64(sig1 ^ remix.rotate_left(32)) & (u64::MAX >> REMIX_FIXED_BITS));
+    [sig0, sig1]
+}
+
 use derive_setters::*;
 
 #[derive(Setters, Epserde, Debug, Default)]
@@ -143,6 +189,15 @@ pub struct VFuncBuilder<
     /// The base-2 logarithm of the number of buckets. Used only if `offline` is `true`.
     #[setters(generate = true, strip_option)]
     log2_buckets: Option<u32>,
+    #[setters(generate = true)]
+    /// The number of bits of the signature of each key stored alongside its
+    /// value to detect [absent keys](VFunc::try_get); zero disables the check.
+    signature_bits: u32,
+    #[setters(generate = true)]
+    /// Accept repeated keys associated with different values, keeping
+    /// one of the values (which one is unspecified) instead of returning a
+    /// [`VFuncError::DuplicateKey`] error.
+    allow_duplicates: bool,
     segment_size: usize,
     _marker_t: std::marker::PhantomData<T>,
     _marker_o: std::marker::PhantomData<O>,
@@ -159,6 +214,18 @@ compute a 128-bit signature of the key.
 The output type `O` can be selected to be any of the unsigned integer types
 with an atomic counterpart; The default is `usize`.
 
+A static function is a retrieval structure: [`get`](VFunc::get) returns a
+value for every key, including keys that were not in the key set. If the
+function is built with [`signature_bits`](VFuncBuilder::signature_bits) set
+to <var>s</var> > 0, <var>s</var> bits of a hash of the signature of each key
+are stored together with its value, and [`try_get`](VFunc::try_get) returns
+`None` if they do not match the signature of the queried key: the result is an
+approximate dictionary in which a key not in the key set is reported as present
+with probability 2<sup>&minus;<var>s</var></sup>. The check costs <var>s</var>
+bits per value, that is, about 1.1&ndash;1.23 &middot; <var>s</var> bits per key,
+depending on the number of keys, as the bit width of the values increases by
+<var>s</var>.
+
 */
 
 #[derive(Epserde, Debug, Default)]
@@ -173,11 +240,198 @@ pub struct VFunc<
     chunk_mask: u32,
     num_keys: usize,
     segment_size: usize,
+    signature_bits: u32,
+    remix: u64,
+    log2_buckets: u32,
     values: S,
     _marker_t: std::marker::PhantomData<T>,
     _marker_o: std::marker::PhantomData<O>,
 }
 
+/// Errors specific to the construction of a [`VFunc`].
+///
+/// Construction methods return an [`anyhow::Error`], from which these errors
+/// can be recovered by [downcasting](anyhow::Error::downcast_ref).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VFuncError {
+    /// The same key appears more than once, associated with different values.
+    ///
+    /// Keys are not compared directly: the error is reported when the same
+    /// 128-bit signature is associated with different values. In
+    /// [`build`](VFuncBuilder::build), this must happen with four different
+    /// seeds, so a spurious error due to a signature collision among distinct
+    /// keys is practically impossible; [`build_from_stream`](VFuncBuilder::build_from_stream)
+    /// does not recompute signatures, so a spurious error happens with
+    /// probability about <var>n</var><sup>2</sup> / 2<sup>129</sup> on
+    /// <var>n</var> keys.
+    DuplicateKey,
+}
+
+impl core::fmt::Display for VFuncError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            VFuncError::DuplicateKey => write!(
+                f,
+                "Duplicate keys (duplicate 128-bit signatures associated with different values)"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VFuncError {}
+
+/// A phase of the construction of a [`VFunc`].
+///
+/// Chunks are peeled and assigned in parallel by several threads, which in
+/// offline mode also read and sort the buckets lazily: the wall-clock time of
+/// this part of the construction is split among [`Sorting`](Phase::Sorting),
+/// [`Peeling`](Phase::Peeling) and [`Assignment`](Phase::Assignment)
+/// proportionally to the time spent by the threads in each phase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phase {
+    /// Reading the keys and the values and computing the signatures of the keys
+    /// (in offline mode, this phase includes writing the signatures to the buckets).
+    Hashing,
+    /// Sorting the signatures, checking for duplicates and dividing the signatures
+    /// into chunks.
+    Sorting,
+    /// Generating and peeling the hypergraphs.
+    Peeling,
+    /// Assigning values to the vertices of the peeled hypergraphs.
+    Assignment,
+    /// Serializing the function. This phase is not measured by the
+    /// builder: use [`BuildStats::record`] to add it.
+    Serialization,
+}
+
+/// Statistics about the construction of a [`VFunc`], returned by
+/// [`VFuncBuilder::build_with_stats`].
+#[derive(Debug, Clone, Default)]
+pub struct BuildStats {
+    /// The wall-clock time spent in each phase of the construction, summed over
+    /// all attempts.
+    pub phase_durations: BTreeMap<Phase, Duration>,
+    /// The wall-clock time of the whole construction.
+    pub total_duration: Duration,
+    /// The number of attempts (i.e., of seeds tried, or of remixes in
+    /// [`build_from_stream_with_stats`](VFuncBuilder::build_from_stream_with_stats))
+    /// necessary to build the function.
+    pub attempts: usize,
+}
+
+impl BuildStats {
+    fn add(&mut self, phase: Phase, start: Instant) {
+        *self.phase_durations.entry(phase).or_default() += start.elapsed();
+    }
+
+    /// Split the wall-clock time elapsed since `start` among phases run in
+    /// parallel, proportionally to the time (in nanoseconds) spent in each
+    /// phase by the threads.
+    fn add_parallel(&mut self, start: Instant, thread_times: &[(Phase, u64)]) {
+        let elapsed = start.elapsed();
+        let total = thread_times.iter().map(|&(_, time)| time).sum::<u64>();
+        let mut assigned = Duration::ZERO;
+        for (i, &(phase, time)) in thread_times.iter().enumerate() {
+            // The last phase gets the rounding errors
+            let share = if i == thread_times.len() - 1 {
+                elapsed - assigned
+            } else if total == 0 {
+                Duration::ZERO
+            } else {
+                (elapsed.mul_f64(time as f64 / total as f64)).min(elapsed - assigned)
+            };
+            assigned += share;
+            *self.phase_durations.entry(phase).or_default() += share;
+        }
+    }
+
+    /// Add a duration to a phase and to the total duration.
+    ///
+    /// This method makes it possible to account for phases that are not
+    /// measured by the builder, such as [serialization](Phase::Serialization).
+    pub fn record(&mut self, phase: Phase, duration: Duration) {
+        *self.phase_durations.entry(phase).or_default() += duration;
+        self.total_duration += duration;
+    }
+
+    /// Print in an human readable format the number of attempts and the
+    /// wall-clock time of each phase.
+    pub fn print(&self) {
+        println!("{:>20}: {:>10}", "attempts", self.attempts);
+        for (phase, duration) in &self.phase_durations {
+            println!(
+                "{:>20}: {:>10.3} s",
+                format!("{:?}", phase),
+                duration.as_secs_f64()
+            );
+        }
+        println!(
+            "{:>20}: {:>10.3} s",
+            "total",
+            self.total_duration.as_secs_f64()
+        );
+    }
+}
+
+/// Return `signature_bits` bits of a hash of a signature, to be stored in the
+/// lowest bits of a value to detect absent keys.
+///
+/// The bits are obtained by mixing both halves of the signature, so they are
+/// not correlated with the bits used to compute the chunk and the variables.
+#[inline(always)]
+fn sig_check<O: Word + CastableFrom<u64>>(sig: &[u64; 2], signature_bits: u32) -> O {
+    if signature_bits == 0 {
+        O::ZERO
+    } else {
+        let hash = (sig[0] ^ sig[1].rotate_left(32)).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        O::cast_from(hash >> (64 - signature_bits))
+    }
+}
+
+/// Shift a value to make room for the signature check, and add the check.
+#[inline(always)]
+fn with_check<O: Word + CastableFrom<u64>>(value: O, sig: &[u64; 2], signature_bits: u32) -> O {
+    if signature_bits == 0 {
+        value
+    } else {
+        value << signature_bits | sig_check(sig, signature_bits)
+    }
+}
+
+/// Check that the signature check fits in the output type.
+fn check_signature_bits<O: Word>(signature_bits: u32) -> anyhow::Result<()> {
+    if signature_bits > 64 || signature_bits as usize >= O::BITS {
+        bail!(
+            "The number of signature bits ({}) must be at most 64 and smaller than the number of bits of the output type ({})",
+            signature_bits,
+            O::BITS
+        );
+    }
+    Ok(())
+}
+
+/// Check that the values and the signature check fit in the output type.
+fn check_bit_width<O: Word>(bit_width: usize) -> anyhow::Result<()> {
+    if bit_width > O::BITS {
+        bail!(
+            "The values and the signature check need {} bits, but the output type has {} bits",
+            bit_width,
+            O::BITS
+        );
+    }
+    Ok(())
+}
+
+/// Return the minimum bit width sufficient to represent a value, which
+/// is zero for zero.
+fn bit_width_of<O: Word>(max_value: O) -> usize {
+    if max_value == O::ZERO {
+        0
+    } else {
+        max_value.len() as usize
+    }
+}
+
 fn compute_params(num_keys: usize, pl: &mut impl ProgressLog) -> (u32, usize, u32, f64) {
     let (chunk_high_bits, max_num_threads, log2_l, c);
 
@@ -233,7 +487,8 @@ fn compute_params(num_keys: usize, pl: &mut impl ProgressLog) -> (u32, usize, u3
 enum ParSolveResult<O: Word + IntoAtomic> {
     DuplicateSignature,
     CantPeel,
-    Ok(AtomicBitFieldVec<O>),
+    /// The values and the number of distinct signatures.
+    Ok(AtomicBitFieldVec<O>, usize),
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -249,16 +504,23 @@ fn par_solve<
     num_threads: usize,
     segment_size: usize,
     log2_l: u32,
+    remix_seed: u64,
+    stats: &mut BuildStats,
     main_pl: &mut (impl ProgressLog + Send),
 ) -> ParSolveResult<O>
 where
     O::AtomicType: AtomicUnsignedInt + AsBytes,
 {
     use crate::traits::bit_field_slice::AtomicHelper;
+    let start = Instant::now();
+    // Nanoseconds spent by the threads getting chunks, peeling and assigning
+    let thread_times: [AtomicU64; 3] = Default::default();
+    let elapsed = |start: Instant| start.elapsed().as_nanos() as u64;
     let data = AtomicBitFieldVec::<O>::new(bit_width, num_vertices * num_chunks);
     let chunk_iter = std::sync::Arc::new(Mutex::new(chunk_iter));
     let failed_peeling = AtomicBool::new(false);
     let duplicate_signature = AtomicBool::new(false);
+    let num_sigs = AtomicUsize::new(0);
     main_pl.info(format_args!("Using {} threads", num_threads));
     main_pl
         .item_name("chunk")
@@ -271,7 +533,10 @@ where
                 if failed_peeling.load(Relaxed) || duplicate_signature.load(Relaxed) {
                     return;
                 }
-                let (chunk, sigs) = match chunk_iter.lock().unwrap().next() {
+                let phase_start = Instant::now();
+                let next = chunk_iter.lock().unwrap().next();
+                thread_times[0].fetch_add(elapsed(phase_start), Relaxed);
+                let (chunk, sigs) = match next {
                     None => return,
                     Some((chunk, sigs)) => (chunk, sigs),
                 };
@@ -279,7 +544,9 @@ where
                     duplicate_signature.store(true, Ordering::Relaxed);
                     return;
                 }
+                num_sigs.fetch_add(sigs.len(), Relaxed);
                 let mut pl = main_pl.lock().unwrap().clone();
+                let phase_start = Instant::now();
                 pl.item_name("edge");
                 pl.start(format!(
                     "Generating graph for chunk {}/{}...",
@@ -289,7 +556,7 @@ where
                 let mut edge_lists = Vec::new();
                 edge_lists.resize_with(num_vertices, EdgeList::default);
                 sigs.iter().enumerate().for_each(|(edge_index, sig)| {
-                    for &v in edge(&sig.0, log2_l, segment_size).iter() {
+                    for &v in edge(&remix(&sig.0, remix_seed), log2_l, segment_size).iter() {
                         edge_lists[v].add(edge_index);
                     }
                 });
@@ -319,7 +586,13 @@ where
                         stack[curr] = v;
                         curr += 1;
                         // Degree is necessarily 0
-                        for &x in edge(&sigs[edge_index].0, log2_l, segment_size).iter() {
+                        for &x in edge(
+                            &remix(&sigs[edge_index].0, remix_seed),
+                            log2_l,
+                            segment_size,
+                        )
+                        .iter()
+                        {
                             if x != v {
                                 edge_lists[x].remove(edge_index);
                                 if edge_lists[x].degree() == 1 {
@@ -330,12 +603,14 @@ where
                     }
                     stack.truncate(curr);
                 }
+                thread_times[1].fetch_add(elapsed(phase_start), Relaxed);
                 if sigs.len() != stack.len() {
                     failed_peeling.store(true, Ordering::Relaxed);
                     return;
                 }
                 pl.done_with_count(sigs.len());
 
+                let phase_start = Instant::now();
                 pl.start(format!(
                     "Assigning values for chunk {}/{}...",
                     chunk + 1,
@@ -343,7 +618,11 @@ where
                 ));
                 while let Some(mut v) = stack.pop() {
                     let edge_index = edge_lists[v].edge_index();
-                    let mut edge = edge(&sigs[edge_index].0, log2_l, segment_size);
+                    let mut edge = edge(
+                        &remix(&sigs[edge_index].0, remix_seed),
+                        log2_l,
+                        segment_size,
+                    );
                     let chunk_offset = chunk * num_vertices;
                     v += chunk_offset;
                     edge.iter_mut().for_each(|v| {
@@ -366,6 +645,7 @@ where
                     );
                 }
                 pl.done_with_count(sigs.len());
+                thread_times[2].fetch_add(elapsed(phase_start), Relaxed);
 
                 pl.start(format!("Completed chunk {}/{}.", chunk + 1, num_chunks));
                 main_pl.lock().unwrap().update_and_display();
@@ -373,19 +653,29 @@ where
         }
     });
 
+    let [sorting, peeling, assignment] = thread_times.map(AtomicU64::into_inner);
+    stats.add_parallel(
+        start,
+        &[
+            (Phase::Sorting, sorting),
+            (Phase::Peeling, peeling),
+            (Phase::Assignment, assignment),
+        ],
+    );
+
     if failed_peeling.load(Relaxed) {
         ParSolveResult::CantPeel
     } else if duplicate_signature.load(Relaxed) {
         ParSolveResult::DuplicateSignature
     } else {
         main_pl.lock().unwrap().done();
-        ParSolveResult::Ok(data)
+        ParSolveResult::Ok(data, num_sigs.into_inner())
     }
 }
 
 impl<
         T: ToSig,
-        O: ZeroCopy + SerializeInner + DeserializeInner + Word + IntoAtomic,
+        O: ZeroCopy + SerializeInner + DeserializeInner + Word + IntoAtomic + CastableFrom<u64>,
         S: bit_field_slice::BitFieldSlice<O>,
     > VFunc<T, O, S>
 where
@@ -396,16 +686,138 @@ where
     ///
     /// This method is mainly useful in the construction of compound functions.
     pub fn get_by_sig(&self, sig: &[u64; 2]) -> O {
-        let edge = edge(sig, self.log2_l, self.segment_size);
+        self.get_with_check_by_sig(sig) >> self.signature_bits
+    }
+
+    /// Return the value associated with the given signature, or `None` if
+    /// the [signature check](VFuncBuilder::signature_bits) fails.
+    ///
+    /// This method is mainly useful in the construction of compound functions.
+    #[inline]
+    pub fn try_get_by_sig(&self, sig: &[u64; 2]) -> Option<O> {
+        let value = self.get_with_check_by_sig(sig);
+        let check_mask = (O::ONE << self.signature_bits) - O::ONE;
+        if value & check_mask == sig_check(sig, self.signature_bits) {
+            Some(value >> self.signature_bits)
+        } else {
+            None
+        }
+    }
+
+    /// Return the XOR of the three variables associated with the given
+    /// signature, which contains the value and the signature check.
+    #[inline(always)]
+    fn get_with_check_by_sig(&self, sig: &[u64; 2]) -> O {
+        self.get_with_check_by_vars(&self.vars_by_sig(sig))
+    }
+
+    /// Return the XOR of the given three variables, which contains the value
+    /// and the signature check.
+    #[inline(always)]
+    fn get_with_check_by_vars(&self, vars: &[usize; 3]) -> O {
+        // SAFETY: the variables are within bounds by construction
+        unsafe {
+            self.values.get_unchecked(vars[0])
+                ^ self.values.get_unchecked(vars[1])
+                ^ self.values.get_unchecked(vars[2])
+        }
+    }
+
+    /// Return the indices in the [array of values](VFunc::values) of the three
+    /// variables whose XOR is the value associated with the given signature.
+    ///
+    /// The indices are distinct and depend on the chunk and on the segment
+    /// the signature is mapped to.
+    #[inline]
+    pub fn vars_by_sig(&self, sig: &[u64; 2]) -> [usize; 3] {
+        let edge = edge(&remix(sig, self.remix), self.log2_l, self.segment_size);
         let chunk = chunk(sig, self.high_bits, self.chunk_mask);
         // chunk * self.segment_size * (2^log2_l + 2)
         let chunk_offset = chunk * ((self.segment_size << self.log2_l) + (self.segment_size << 1));
+        edge.map(|v| v + chunk_offset)
+    }
 
-        unsafe {
-            self.values.get_unchecked(edge[0] + chunk_offset)
-                ^ self.values.get_unchecked(edge[1] + chunk_offset)
-                ^ self.values.get_unchecked(edge[2] + chunk_offset)
-        }
+    /// Return the indices in the [array of values](VFunc::values) of the three
+    /// variables whose XOR is the value associated with the given key.
+    #[inline(always)]
+    pub fn vars(&self, key: &T) -> [usize; 3] {
+        self.vars_by_sig(&T::to_sig(key, self.seed))
+    }
+
+    /// Return the seed used to compute the signatures of the keys.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Return the number of bits of signature check stored with each value.
+    pub fn signature_bits(&self) -> u32 {
+        self.signature_bits
+    }
+
+    /// Return the key of the bijection used to remix the signatures, which is
+    /// nonzero only if the function was built by
+    /// [`build_from_stream`](VFuncBuilder::build_from_stream) and the first
+    /// construction attempt failed.
+    ///
+    /// Together with the [seed](VFunc::seed), this value identifies the
+    /// construction attempt that succeeded.
+    pub fn remix(&self) -> u64 {
+        self.remix
+    }
+
+    /// Return the base-2 logarithm of the number of buckets used at
+    /// construction time, or zero if the function was built in memory.
+    pub fn log2_buckets(&self) -> u32 {
+        self.log2_buckets
+    }
+
+    /// Return the number of chunks in which the keys are divided.
+    pub fn num_chunks(&self) -> usize {
+        1 << self.high_bits
+    }
+
+    /// Return the bit width of the [array of values](VFunc::values), which
+    /// includes the [signature bits](VFunc::signature_bits).
+    pub fn bit_width(&self) -> usize {
+        self.values.bit_width()
+    }
+
+    /// Return the number of bits per key used by the
+    /// [array of values](VFunc::values).
+    pub fn bits_per_key(&self) -> f64 {
+        self.values.len() as f64 * self.bit_width() as f64 / self.num_keys as f64
+    }
+
+    /// Print in an human readable format the parameters used to build
+    /// the function and its size.
+    pub fn print_stats(&self) {
+        println!("{:>20}: {:>10}", "keys", self.num_keys);
+        println!("{:>20}: {:>10}", "seed", self.seed);
+        println!("{:>20}: {:>10}", "remix", self.remix);
+        println!("{:>20}: {:>10}", "log2_buckets", self.log2_buckets);
+        println!("{:>20}: {:>10}", "chunks", self.num_chunks());
+        println!("{:>20}: {:>10}", "segment_size", self.segment_size);
+        println!("{:>20}: {:>10}", "bit_width", self.bit_width());
+        println!("{:>20}: {:>10}", "signature_bits", self.signature_bits);
+        println!("{:>20}: {:>10.3}", "bits/key", self.bits_per_key());
+    }
+
+    /// Return the array of values solved at construction time.
+    ///
+    /// Note that the indices of the array are not key ranks, but variables of
+    /// the system solved at construction time: the value associated
+    /// with a key is the XOR of the three values at the indices returned by
+    /// [`vars`](VFunc::vars), and the other values are not meaningful
+    /// in isolation. If [signature bits](VFunc::signature_bits) are
+    /// used, the lowest bits of each value are part of the signature check.
+    pub fn values(&self) -> &S {
+        &self.values
+    }
+
+    /// Consume the function and return the [array of values](VFunc::values)
+    /// solved at construction time.
+    pub fn into_values(self) -> S {
+        self.values
     }
 
     /// Return the value associated with the given key, or a random value if the key is not present.
@@ -414,6 +826,77 @@ where
         self.get_by_sig(&T::to_sig(key, self.seed))
     }
 
+    /// Return the value associated with the given key, or `None` if the
+    /// [signature check](VFuncBuilder::signature_bits) detects that the key
+    /// is not present.
+    ///
+    /// Keys that are not present are reported as present (with a random value)
+    /// with probability 2<sup>&minus;<var>s</var></sup>, where <var>s</var> is
+    /// the number of [signature bits](VFunc::signature_bits); in
+    /// particular, if the function has been built without signature bits, this
+    /// method always returns `Some`.
+    #[inline(always)]
+    pub fn try_get(&self, key: &T) -> Option<O> {
+        self.try_get_by_sig(&T::to_sig(key, self.seed))
+    }
+
+    /// Store in `out` the values associated with the given keys.
+    ///
+    /// The result is the same as calling [`get`](VFunc::get) on each key, but
+    /// keys are processed in slabs: first the signatures and the variables of
+    /// all the keys in a slab are computed, and then the values are accessed,
+    /// so that the memory accesses for different keys can overlap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `keys` and `out` have different lengths.
+    pub fn get_batch<K: Borrow<T>>(&self, keys: &[K], out: &mut [O]) {
+        assert_eq!(
+            keys.len(),
+            out.len(),
+            "The number of keys ({}) and the length of the output ({}) differ",
+            keys.len(),
+            out.len()
+        );
+        let mut vars = [[0; 3]; BATCH_SLAB];
+        for (keys, out) in keys.chunks(BATCH_SLAB).zip(out.chunks_mut(BATCH_SLAB)) {
+            for (v, key) in vars.iter_mut().zip(keys) {
+                *v = self.vars(key.borrow());
+            }
+            for (v, o) in vars.iter().zip(out) {
+                *o = self.get_with_check_by_vars(v) >> self.signature_bits;
+            }
+        }
+    }
+
+    /// Store in `out` the values associated with the given keys, in parallel.
+    ///
+    /// This method splits the keys in chunks and calls
+    /// [`get_batch`](VFunc::get_batch) on each chunk in parallel. If the
+    /// feature "rayon" is not enabled, the chunks are processed sequentially.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `keys` and `out` have different lengths.
+    pub fn par_get_batch<K: Borrow<T> + Sync>(&self, keys: &[K], out: &mut [O])
+    where
+        Self: Sync,
+    {
+        assert_eq!(
+            keys.len(),
+            out.len(),
+            "The number of keys ({}) and the length of the output ({}) differ",
+            keys.len(),
+            out.len()
+        );
+        #[cfg(feature = "rayon")]
+        keys.par_chunks(PAR_BATCH_CHUNK)
+            .zip(out.par_chunks_mut(PAR_BATCH_CHUNK))
+            .for_each(|(keys, out)| self.get_batch(keys, out));
+        #[cfg(not(feature = "rayon"))]
+        self.get_batch(keys, out);
+    }
+
     /// Return the number of keys in the function.
     pub fn len(&self) -> usize {
         self.num_keys
@@ -425,13 +908,28 @@ where
     }
 }
 
-impl<T: ToSig, O: ZeroCopy + SerializeInner + DeserializeInner + Word + IntoAtomic>
-    VFuncBuilder<T, O>
+impl<
+        T: ToSig,
+        O: ZeroCopy + SerializeInner + DeserializeInner + Word + IntoAtomic + CastableFrom<u64>,
+    > VFuncBuilder<T, O>
 where
     O::AtomicType: AtomicUnsignedInt + AsBytes,
     BitFieldVec<O>: From<AtomicBitFieldVec<O, Vec<O::AtomicType>>>,
 {
     /// Build and return a new function with given keys and values.
+    ///
+    /// The bit width of the output is the minimum bit width
+    /// sufficient to represent all values.
+    ///
+    /// Repeated keys associated with the same value are harmless, and
+    /// they are silently deduplicated; repeated keys associated with different
+    /// values cause a [`VFuncError::DuplicateKey`] error, unless
+    /// [`allow_duplicates`](VFuncBuilder::allow_duplicates) is set.
+    ///
+    /// Values are associated with keys in iteration order, and must be at
+    /// least as many as the keys, or an error will be returned (additional
+    /// values are ignored). To map each key to its rank, pass `&(0..)`
+    /// as values. If all values are zero, the bit width is zero.
     pub fn build<
         I: std::iter::IntoIterator<Item = T> + Clone,
         V: std::iter::IntoIterator<Item = O> + Clone,
@@ -441,6 +939,164 @@ where
         into_values: &V,
         pl: &mut (impl ProgressLog + Send),
     ) -> anyhow::Result<VFunc<T, O>> {
+        Ok(self.build_with_width(keys, into_values, None, pl)?.0)
+    }
+
+    /// Like [`build`](VFuncBuilder::build), but return also statistics
+    /// about the construction, such as the time spent in each [phase](Phase).
+    pub fn build_with_stats<
+        I: std::iter::IntoIterator<Item = T> + Clone,
+        V: std::iter::IntoIterator<Item = O> + Clone,
+    >(
+        self,
+        keys: I,
+        into_values: &V,
+        pl: &mut (impl ProgressLog + Send),
+    ) -> anyhow::Result<(VFunc<T, O>, BuildStats)> {
+        self.build_with_width(keys, into_values, None, pl)
+    }
+
+    /// Build and return a new function reading pairs of keys and values
+    /// in a single pass.
+    ///
+    /// Differently from [`build`](VFuncBuilder::build), which reads the keys
+    /// once per construction attempt, this method consumes `pairs` exactly once,
+    /// writing the signatures of the keys and the values to disk-based buckets,
+    /// as in [offline](VFuncBuilder::offline) mode (which is thus implied).
+    /// If the construction fails, the signatures on disk are remixed by a
+    /// bijection rather than recomputed from the keys with a different seed.
+    ///
+    /// No key is kept in memory. Besides the output, the peak memory usage is given
+    /// by the buffers of the 2<sup>`log2_buckets`</sup> buckets and, for each
+    /// thread, by the signature/value pairs of a chunk being solved, or
+    /// of a whole bucket, whichever is larger; the number of chunks depends on
+    /// the number of keys, and is at most 2<sup>10</sup>, so increasing
+    /// [`log2_buckets`](VFuncBuilder::log2_buckets) reduces the peak memory
+    /// usage down to the size of a chunk.
+    ///
+    /// To map each key to its rank, zip the keys with `0..`.
+    pub fn build_from_stream(
+        self,
+        pairs: impl IntoIterator<Item = (T, O)>,
+        pl: &mut (impl ProgressLog + Send),
+    ) -> anyhow::Result<VFunc<T, O>> {
+        Ok(self.build_from_stream_with_stats(pairs, pl)?.0)
+    }
+
+    /// Like [`build_from_stream`](VFuncBuilder::build_from_stream), but return
+    /// also statistics about the construction, such as the time spent in each
+    /// [phase](Phase).
+    pub fn build_from_stream_with_stats(
+        self,
+        pairs: impl IntoIterator<Item = (T, O)>,
+        pl: &mut (impl ProgressLog + Send),
+    ) -> anyhow::Result<(VFunc<T, O>, BuildStats)> {
+        let start = Instant::now();
+        let mut stats = BuildStats::default();
+        let signature_bits = self.signature_bits;
+        check_signature_bits::<O>(signature_bits)?;
+        let log2_buckets = self.log2_buckets.unwrap_or(8);
+        pl.info(format_args!("Using {} buckets", 1 << log2_buckets));
+        let mut phase_start = Instant::now();
+        pl.item_name("key");
+        pl.start("Reading input...");
+        let mut sig_sorter = SigStore::<O>::new(log2_buckets, 12, true)?;
+        let mut max_value = O::ZERO;
+        for (key, value) in pairs {
+            pl.light_update();
+            max_value = Ord::max(max_value, value);
+            let sig = T::to_sig(&key, 0);
+            sig_sorter.push(&(sig, with_check(value, &sig, signature_bits)))?;
+        }
+        pl.done();
+        stats.add(Phase::Hashing, phase_start);
+
+        let (chunk_high_bits, max_num_threads, log2_l, c) = compute_params(sig_sorter.len(), pl);
+        let num_chunks = 1 << chunk_high_bits;
+        let chunk_mask = (1u32 << chunk_high_bits) - 1;
+        phase_start = Instant::now();
+        let mut chunk_store = sig_sorter.into_chunk_store(chunk_high_bits)?;
+        chunk_store.allow_duplicates(self.allow_duplicates);
+        stats.add(Phase::Sorting, phase_start);
+
+        let bit_width = bit_width_of(max_value) + signature_bits as usize;
+        check_bit_width::<O>(bit_width)?;
+        pl.info(format_args!(
+            "max value = {}, bit width = {}",
+            max_value, bit_width
+        ));
+
+        let l = 1 << log2_l;
+        let segment_size =
+            ((*chunk_store.chunk_sizes().iter().max().unwrap() as f64 * c).ceil() as usize + l + 1)
+                / (l + 2);
+        let num_vertices = segment_size * (l + 2);
+
+        let mut remix_seed = 0;
+        let (data, num_keys) = loop {
+            stats.attempts += 1;
+            match par_solve(
+                chunk_store.iter()?,
+                bit_width,
+                num_chunks,
+                num_vertices,
+                match self.num_threads {
+                    0 => max_num_threads,
+                    _ => self.num_threads,
+                },
+                segment_size,
+                log2_l,
+                remix_seed,
+                &mut stats,
+                pl,
+            ) {
+                ParSolveResult::DuplicateSignature => {
+                    bail!(VFuncError::DuplicateKey);
+                }
+                ParSolveResult::CantPeel => {
+                    warn!("Peeling failed, remixing signatures and trying again...");
+                    remix_seed += 1;
+                }
+                ParSolveResult::Ok(data, num_sigs) => break (data, num_sigs),
+            }
+        };
+
+        let func = VFunc {
+            seed: 0,
+            log2_l,
+            high_bits: chunk_high_bits,
+            chunk_mask,
+            num_keys,
+            segment_size,
+            signature_bits,
+            remix: remix_seed,
+            log2_buckets,
+            values: data.into(),
+            _marker_t: std::marker::PhantomData,
+            _marker_o: std::marker::PhantomData,
+        };
+        stats.total_duration = start.elapsed();
+        Ok((func, stats))
+    }
+
+    /// Build and return a new function with given keys and values, using
+    /// the given bit width for the output, or the minimum bit width
+    /// sufficient to represent all values if `bit_width` is `None`,
+    /// together with statistics about the construction.
+    fn build_with_width<
+        I: std::iter::IntoIterator<Item = T> + Clone,
+        V: std::iter::IntoIterator<Item = O> + Clone,
+    >(
+        self,
+        keys: I,
+        into_values: &V,
+        fixed_bit_width: Option<usize>,
+        pl: &mut (impl ProgressLog + Send),
+    ) -> anyhow::Result<(VFunc<T, O>, BuildStats)> {
+        let start = Instant::now();
+        let mut stats = BuildStats::default();
+        let signature_bits = self.signature_bits;
+        check_signature_bits::<O>(signature_bits)?;
         // Loop until success or duplicate detection
         let mut dup_count = 0;
         let mut seed = 0;
@@ -453,6 +1109,8 @@ where
             mut log2_l,
         );
         let data = loop {
+            stats.attempts += 1;
+            let mut phase_start = Instant::now();
             pl.item_name("key");
             pl.start("Reading input...");
             let mut max_value = O::ZERO;
@@ -465,26 +1123,40 @@ where
                 let max_chunk_high_bits = 12;
                 let log2_buckets = self.log2_buckets.unwrap_or(8);
                 pl.info(format_args!("Using {} buckets", 1 << log2_buckets));
-                let mut sig_sorter = SigStore::<O>::new(log2_buckets, max_chunk_high_bits).unwrap();
+                let mut sig_sorter = SigStore::<O>::new(log2_buckets, max_chunk_high_bits, true)?;
                 let mut values = into_values.clone().into_iter();
-                sig_sorter.extend(keys.clone().into_iter().map(|x| {
+                let mut not_enough_values = false;
+                sig_sorter.extend(keys.clone().into_iter().map_while(|x| {
                     pl.light_update();
-                    let v = values.next().expect("Not enough values");
+                    let Some(v) = values.next() else {
+                        not_enough_values = true;
+                        return None;
+                    };
                     max_value = Ord::max(max_value, v);
-                    (T::to_sig(&x, seed), v)
+                    let sig = T::to_sig(&x, seed);
+                    Some((sig, with_check(v, &sig, signature_bits)))
                 }))?;
                 num_keys = sig_sorter.len();
+                if not_enough_values {
+                    bail!("There are fewer values than keys ({} values)", num_keys);
+                }
                 pl.done();
+                stats.add(Phase::Hashing, phase_start);
 
                 (chunk_high_bits, max_num_threads, log2_l, c) = compute_params(num_keys, pl);
 
                 let num_chunks = 1 << chunk_high_bits;
                 chunk_mask = (1u32 << chunk_high_bits) - 1;
 
+                phase_start = Instant::now();
                 let mut chunk_store = sig_sorter.into_chunk_store(chunk_high_bits)?;
+                chunk_store.allow_duplicates(self.allow_duplicates);
                 let chunk_sizes = chunk_store.chunk_sizes();
+                stats.add(Phase::Sorting, phase_start);
 
-                bit_width = max_value.len() as usize;
+                bit_width =
+                    fixed_bit_width.unwrap_or(bit_width_of(max_value)) + signature_bits as usize;
+                check_bit_width::<O>(bit_width)?;
                 pl.info(format_args!(
                     "max value = {}, bit width = {}",
                     max_value, bit_width
@@ -500,7 +1172,7 @@ where
                     (100.0 * (num_vertices * num_chunks) as f64) / (num_keys as f64 * c)
                 ));
 
-                match par_solve(
+                let result = par_solve(
                     chunk_store.iter().unwrap(),
                     bit_width,
                     num_chunks,
@@ -511,32 +1183,41 @@ where
                     },
                     segment_size,
                     log2_l,
+                    0,
+                    &mut stats,
                     pl,
-                ) {
+                );
+                match result {
                     ParSolveResult::DuplicateSignature => {
                         if dup_count >= 3 {
-                            bail!("Duplicate keys (duplicate 128-bit signatures with four different seeds");
+                            bail!(VFuncError::DuplicateKey);
                         }
                         warn!("Duplicate 128-bit signature, trying again...");
                         dup_count += 1;
+                        seed += 1;
                         continue;
                     }
                     ParSolveResult::CantPeel => {}
-                    ParSolveResult::Ok(data) => break data,
+                    ParSolveResult::Ok(data, num_sigs) => {
+                        // Identical signature/value pairs have been removed
+                        num_keys = num_sigs;
+                        break data;
+                    }
                 }
             } else {
                 let mut values = into_values.clone().into_iter();
-                let mut sigs = keys
-                    .clone()
-                    .into_iter()
-                    .map(|x| {
-                        let v = values.next().expect("Not enough values");
-                        pl.light_update();
-                        max_value = Ord::max(max_value, v);
-                        (T::to_sig(&x, seed), v)
-                    })
-                    .collect::<Vec<_>>();
+                let mut sigs = Vec::new();
+                for x in keys.clone() {
+                    let Some(v) = values.next() else {
+                        bail!("There are fewer values than keys ({} values)", sigs.len());
+                    };
+                    pl.light_update();
+                    max_value = Ord::max(max_value, v);
+                    let sig = T::to_sig(&x, seed);
+                    sigs.push((sig, with_check(v, &sig, signature_bits)));
+                }
                 pl.done();
+                stats.add(Phase::Hashing, phase_start);
                 num_keys = sigs.len();
 
                 (chunk_high_bits, max_num_threads, log2_l, c) = compute_params(num_keys, pl);
@@ -544,16 +1225,31 @@ where
                 let num_chunks = 1 << chunk_high_bits;
                 chunk_mask = (1u32 << chunk_high_bits) - 1;
 
+                phase_start = Instant::now();
                 pl.start("Sorting...");
+                #[cfg(feature = "rayon")]
                 sigs.par_sort_unstable();
+                #[cfg(not(feature = "rayon"))]
+                sigs.sort_unstable();
                 pl.done_with_count(num_keys);
 
                 pl.start("Checking for duplicates...");
 
+                if self.allow_duplicates {
+                    // Keep the first pair with a given signature
+                    sigs.dedup_by(|a, b| a.0 == b.0);
+                } else {
+                    // Identical signature/value pairs are harmless
+                    sigs.dedup();
+                }
+                num_keys = sigs.len();
+
                 let mut chunk_sizes = vec![0_usize; num_chunks];
                 let mut dup = false;
 
-                chunk_sizes[chunk(&sigs[0].0, chunk_high_bits, chunk_mask)] += 1;
+                if let Some(first) = sigs.first() {
+                    chunk_sizes[chunk(&first.0, chunk_high_bits, chunk_mask)] += 1;
+                }
 
                 for w in sigs.windows(2) {
                     chunk_sizes[chunk(&w[1].0, chunk_high_bits, chunk_mask)] += 1;
@@ -564,17 +1260,21 @@ where
                 }
 
                 pl.done_with_count(num_keys);
+                stats.add(Phase::Sorting, phase_start);
 
                 if dup {
                     if dup_count >= 3 {
-                        bail!("Duplicate keys (duplicate 128-bit signatures with four different seeds)");
+                        bail!(VFuncError::DuplicateKey);
                     }
                     warn!("Duplicate 128-bit signature, trying again...");
                     dup_count += 1;
+                    seed += 1;
                     continue;
                 }
 
-                bit_width = max_value.len() as usize;
+                bit_width =
+                    fixed_bit_width.unwrap_or(bit_width_of(max_value)) + signature_bits as usize;
+                check_bit_width::<O>(bit_width)?;
                 pl.info(format_args!(
                     "max value = {}, bit width = {}",
                     max_value, bit_width
@@ -590,7 +1290,7 @@ where
                     (100.0 * (num_vertices * num_chunks) as f64) / (sigs.len() as f64 * c)
                 ));
 
-                match par_solve(
+                let result = par_solve(
                     sigs.arbitrary_chunks(&chunk_sizes)
                         .map(Cow::Borrowed)
                         .enumerate(),
@@ -603,13 +1303,20 @@ where
                     },
                     segment_size,
                     log2_l,
+                    0,
+                    &mut stats,
                     pl,
-                ) {
+                );
+                match result {
                     ParSolveResult::DuplicateSignature => {
                         unreachable!("Already checked for duplicates")
                     }
                     ParSolveResult::CantPeel => {}
-                    ParSolveResult::Ok(data) => break data,
+                    ParSolveResult::Ok(data, num_sigs) => {
+                        // Identical signature/value pairs have been removed
+                        num_keys = num_sigs;
+                        break data;
+                    }
                 }
             }
 
@@ -621,16 +1328,56 @@ where
             data.len() as f64 * bit_width as f64 / num_keys as f64
         ));
 
-        Ok(VFunc {
+        let func = VFunc {
             seed,
             log2_l,
             high_bits: chunk_high_bits,
             chunk_mask,
             num_keys,
             segment_size,
+            signature_bits,
+            remix: 0,
+            log2_buckets: if self.offline {
+                self.log2_buckets.unwrap_or(8)
+            } else {
+                0
+            },
             values: data.into(),
             _marker_t: std::marker::PhantomData,
             _marker_o: std::marker::PhantomData,
-        })
+        };
+        stats.total_duration = start.elapsed();
+        Ok((func, stats))
+    }
+}
+
+impl<T: ToSig> VFuncBuilder<T, u8> {
+    /// Build and return a new function mapping keys to labels of
+    /// `bits` bits.
+    ///
+    /// This method is intended for compact classification functions, where
+    /// each key is mapped to a small label (e.g., `bits` equal to one
+    /// gives a boolean classifier). Differently from [`VFuncBuilder::build`],
+    /// the bit width of the output is exactly `bits`, independently of the
+    /// actual labels. The function will use about 1.1&ndash;1.23 &middot; `bits` bits per key,
+    /// depending on the number of keys, plus a constant overhead.
+    ///
+    /// Returns an error if a label does not fit in `bits` bits.
+    ///
+    /// # Panic
+    /// Panics if `bits` is larger than 8.
+    pub fn build_small<I: std::iter::IntoIterator<Item = T> + Clone>(
+        self,
+        keys: I,
+        labels: impl std::iter::IntoIterator<Item = u8>,
+        bits: usize,
+        pl: &mut (impl ProgressLog + Send),
+    ) -> anyhow::Result<VFunc<T, u8>> {
+        assert!(bits <= 8, "Labels cannot have more than 8 bits ({})", bits);
+        let labels = labels.into_iter().collect::<Vec<_>>();
+        if let Some(label) = labels.iter().find(|&&label| (label as usize) >> bits != 0) {
+            bail!("Label {} does not fit in {} bits", label, bits);
+        }
+        Ok(self.build_with_width(keys, &labels, Some(bits), pl)?.0)
     }
 }
@@ -0,0 +1,115 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::func::{VFunc, VFuncBuilder};
+use crate::prelude::*;
+use anyhow::Result;
+use dsi_progress_logger::ProgressLog;
+use epserde::prelude::*;
+
+/**
+
+Static maps from keys to variable-length values.
+
+A [`VFuncMap`] is the static equivalent of a `HashMap<T, Vec<u8>>`: it
+combines a [`VFunc`] mapping each key to an index, a structure
+storing the offsets of the values (by default, an [Elias–Fano](crate::dict::elias_fano::EliasFano)
+representation), and a byte arena containing the concatenation of the values.
+
+Instances are built using [`VFuncBuilder::build_map`].
+
+As with [`VFunc`], keys that were not part of the original set will
+return an arbitrary value (possibly empty).
+
+*/
+
+#[derive(Epserde, Debug)]
+pub struct VFuncMap<
+    T: ToSig,
+    O: IndexedDict<Input = usize, Output = usize> = EliasFano<QuantumIndex<CountBitVec>>,
+    D: AsRef<[u8]> = Vec<u8>,
+> {
+    /// The function mapping keys to indices.
+    func: VFunc<T>,
+    /// The `len` + 1 offsets of the values in `data`.
+    offsets: O,
+    /// The concatenation of the values.
+    data: D,
+}
+
+impl<T: ToSig, O: IndexedDict<Input = usize, Output = usize>, D: AsRef<[u8]>> VFuncMap<T, O, D> {
+    /// Return the value associated with the given key, or an arbitrary value
+    /// if the key is not present.
+    pub fn get(&self, key: &T) -> &[u8] {
+        let index = self.func.get(key);
+        if index >= self.len() {
+            return &[];
+        }
+        // SAFETY: there are len + 1 offsets
+        let (start, end) = unsafe {
+            (
+                self.offsets.get_unchecked(index),
+                self.offsets.get_unchecked(index + 1),
+            )
+        };
+        &self.data.as_ref()[start..end]
+    }
+
+    /// Return the number of keys in the map.
+    pub fn len(&self) -> usize {
+        self.func.len()
+    }
+
+    /// Return whether the map has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: ToSig> VFuncBuilder<T, usize> {
+    /// Build and return a new map with given keys and variable-length values.
+    ///
+    /// The keys are mapped by a [`VFunc`] to their position in the
+    /// iteration, the values are concatenated in a byte arena, and their
+    /// offsets are stored using the Elias–Fano representation.
+    pub fn build_map<I: IntoIterator<Item = T> + Clone, V: AsRef<[u8]>>(
+        self,
+        keys: I,
+        values: impl IntoIterator<Item = V>,
+        pl: &mut (impl ProgressLog + Send),
+    ) -> Result<VFuncMap<T>> {
+        let mut data = vec![];
+        let mut offsets = vec![0];
+        for value in values {
+            data.extend_from_slice(value.as_ref());
+            offsets.push(data.len());
+        }
+        let num_values = offsets.len() - 1;
+
+        let func = self.build(keys, &(0..), pl)?;
+        if func.len() != num_values {
+            anyhow::bail!(
+                "The number of keys ({}) is different from the number of values ({})",
+                func.len(),
+                num_values
+            );
+        }
+
+        let mut efb = EliasFanoBuilder::new(offsets.len(), data.len() + 1);
+        for offset in offsets {
+            efb.push(offset)?;
+        }
+        let offsets: EliasFano<QuantumIndex<CountBitVec>> = efb.build().convert_to()?;
+
+        data.shrink_to_fit();
+        Ok(VFuncMap {
+            func,
+            offsets,
+            data,
+        })
+    }
+}
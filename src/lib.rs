@@ -7,9 +7,13 @@
 
 #![doc = include_str!("../README.md")]
 #![deny(unconditional_recursion)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 pub mod bits;
 pub mod dict;
+#[cfg(feature = "std")]
 pub mod func;
 pub mod rank_sel;
 pub mod traits;
@@ -18,6 +22,7 @@ pub mod utils;
 pub mod prelude {
     pub use crate::bits::*;
     pub use crate::dict::*;
+    #[cfg(feature = "std")]
     pub use crate::func::*;
     pub use crate::rank_sel::*;
     pub use crate::traits::bit_field_slice;
@@ -15,3 +15,12 @@ pub use quantum_zero_index::*;
 
 mod simple_select_half;
 pub use simple_select_half::*;
+
+mod uniform_select;
+pub use uniform_select::*;
+
+mod rank9;
+pub use rank9::*;
+
+mod select_via_rank;
+pub use select_via_rank::*;
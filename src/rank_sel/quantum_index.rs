@@ -10,18 +10,29 @@
 use crate::prelude::*;
 use crate::traits::bit_field_slice::BitFieldSlice;
 use crate::traits::bit_field_slice::BitFieldSliceMut;
-use anyhow::Result;
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use anyhow::{bail, Result};
 use common_traits::SelectInWord;
+#[cfg(feature = "std")]
 use epserde::*;
 
 /// An index that records the position of the ones in a bit vector at a fixed
 /// set of positions.
 ///
-/// More precisely, given a constant quantum <var>q</var>, this index records the position
+/// More precisely, given a quantum <var>q</var>, this index records the position
 /// of the ones at positions 0, <var>q</var>, <var>2q</var>, &hellip;, and so on.
 /// The positions are recorded in a provided [`BitFieldSliceMut`] whose [bit width](BitFieldSliceCore::bit_width)
 /// must be sufficient to record all the positions.
 ///
+/// The quantum is a power of two chosen at construction time: [`QuantumIndex::new`]
+/// and [`ConvertTo`] use 2<sup>[`DEFAULT_LOG2_QUANTUM`]</sup>, whereas
+/// [`QuantumIndex::with_quantum`] makes it possible to use a denser quantum
+/// (faster selection, larger index) or a sparser one (slower selection, smaller
+/// index). Each hint uses a word, so the index uses about
+/// 64 / <var>q</var> bits per one.
+///
 /// The index takes a backend parameter `B` that can be any type that implements
 /// [`SelectHinted`]. This will usually be something like [`CountBitVec`](crate::bits::bit_vec::CountBitVec), or possibly
 /// a [`CountBitVec`](crate::bits::bit_vec::CountBitVec) wrapped in another index structure for which
@@ -29,36 +40,86 @@ use epserde::*;
 /// of [`EliasFano`](crate::dict::elias_fano::EliasFano) for an example of this approach.
 ///
 /// See [`QuantumZeroIndex`](crate::rank_sel::QuantumZeroIndex) for the same index for zeros.
-#[derive(Epserde, Debug, Clone, PartialEq, Eq, Hash)]
-pub struct QuantumIndex<
-    B: SelectHinted = CountBitVec,
-    O: BitFieldSlice<usize> = Vec<usize>,
-    const QUANTUM_LOG2: usize = 8,
-> {
+#[cfg_attr(feature = "std", derive(Epserde))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QuantumIndex<B: SelectHinted = CountBitVec, O: BitFieldSlice<usize> = Vec<usize>> {
     bits: B,
     ones: O,
-    _marker: core::marker::PhantomData<[(); QUANTUM_LOG2]>,
+    /// The base-2 logarithm of the quantum.
+    log2_quantum: usize,
 }
 
-impl<B: SelectHinted + AsRef<[usize]>, const QUANTUM_LOG2: usize>
-    QuantumIndex<B, Vec<usize>, QUANTUM_LOG2>
-{
+/// The base-2 logarithm of the quantum used by [`QuantumIndex::new`] and
+/// by [`ConvertTo`].
+pub const DEFAULT_LOG2_QUANTUM: u32 = 8;
+
+impl<B: SelectHinted + AsRef<[usize]>> QuantumIndex<B, Vec<usize>> {
+    /// Create a new index on the given bit vector containing `number_of_ones`
+    /// ones, using a quantum of 2<sup>[`DEFAULT_LOG2_QUANTUM`]</sup>.
     pub fn new(bitvec: B, number_of_ones: usize) -> Result<Self> {
+        Self::build(bitvec, number_of_ones, DEFAULT_LOG2_QUANTUM)
+    }
+
+    /// Create a new index on the given bit vector using a quantum
+    /// of 2<sup>`log2_quantum`</sup>.
+    ///
+    /// Returns an error if `log2_quantum` is not smaller than `usize::BITS`.
+    pub fn with_quantum(bitvec: B, log2_quantum: u32) -> Result<Self> {
+        let number_of_ones = bitvec.count();
+        Self::build(bitvec, number_of_ones, log2_quantum)
+    }
+
+    fn build(bitvec: B, number_of_ones: usize, log2_quantum: u32) -> Result<Self> {
+        if log2_quantum >= usize::BITS {
+            bail!(
+                "The base-2 logarithm of the quantum must be smaller than {}, but it is {}",
+                usize::BITS,
+                log2_quantum
+            );
+        }
+        let log2_quantum = log2_quantum as usize;
         let mut res = QuantumIndex {
-            ones: vec![0; (number_of_ones + (1 << QUANTUM_LOG2) - 1) >> QUANTUM_LOG2],
+            ones: vec![0; number_of_ones.div_ceil(1 << log2_quantum)],
             bits: bitvec,
-            _marker: core::marker::PhantomData,
+            log2_quantum,
         };
         res.build_ones()?;
         Ok(res)
     }
 }
 
-impl<
-        B: SelectHinted + AsRef<[usize]>,
-        O: BitFieldSlice<usize> + BitFieldSliceMut<usize>,
-        const QUANTUM_LOG2: usize,
-    > QuantumIndex<B, O, QUANTUM_LOG2>
+impl<B: SelectHinted, O: BitFieldSlice<usize>> QuantumIndex<B, O> {
+    /// Return the base-2 logarithm of the quantum.
+    #[inline(always)]
+    pub fn log2_quantum(&self) -> u32 {
+        self.log2_quantum as u32
+    }
+
+    /// Return the number of hints stored in the index.
+    #[inline(always)]
+    pub fn num_hints(&self) -> usize {
+        self.ones.len()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<B: SelectHinted + SpaceUsage, O: BitFieldSlice<usize> + SpaceUsage> QuantumIndex<B, O> {
+    /// Print in an human readable format the quantum and the size of the index.
+    pub fn print_stats(&self) {
+        let index_size = self.mem_size() - self.bits.mem_size();
+        println!("{:>20}: {:>10}", "quantum", 1_usize << self.log2_quantum);
+        println!("{:>20}: {:>10}", "hints", self.num_hints());
+        println!("{:>20}: {:>10}", "index_bytes", index_size);
+        println!(
+            "{:>20}: {:>10.3}",
+            "bits/one",
+            index_size as f64 * 8.0 / self.bits.count() as f64
+        );
+    }
+}
+
+impl<B: SelectHinted + AsRef<[usize]>, O: BitFieldSlice<usize> + BitFieldSliceMut<usize>>
+    QuantumIndex<B, O>
 {
     fn build_ones(&mut self) -> Result<()> {
         let mut number_of_ones = 0;
@@ -72,7 +133,7 @@ impl<
                 let in_word_index = word.select_in_word((next_quantum - number_of_ones) as usize);
                 let index = (i * usize::BITS as usize) + in_word_index;
                 self.ones.set(ones_index, index);
-                next_quantum += 1 << QUANTUM_LOG2;
+                next_quantum += 1 << self.log2_quantum;
                 ones_index += 1;
             }
 
@@ -83,23 +144,19 @@ impl<
 }
 
 /// Provide the hint to the underlying structure
-impl<B: SelectHinted, O: BitFieldSlice<usize>, const QUANTUM_LOG2: usize> Select
-    for QuantumIndex<B, O, QUANTUM_LOG2>
-{
+impl<B: SelectHinted, O: BitFieldSlice<usize>> Select for QuantumIndex<B, O> {
     #[inline(always)]
     unsafe fn select_unchecked(&self, rank: usize) -> usize {
-        let index = rank >> QUANTUM_LOG2;
+        let index = rank >> self.log2_quantum;
         let pos = self.ones.get_unchecked(index);
-        let rank_at_pos = index << QUANTUM_LOG2;
+        let rank_at_pos = index << self.log2_quantum;
 
         self.bits.select_hinted_unchecked(rank, pos, rank_at_pos)
     }
 }
 
 /// If the underlying implementation has select zero, forward the methods.
-impl<B: SelectHinted + SelectZero, O: BitFieldSlice<usize>, const QUANTUM_LOG2: usize> SelectZero
-    for QuantumIndex<B, O, QUANTUM_LOG2>
-{
+impl<B: SelectHinted + SelectZero, O: BitFieldSlice<usize>> SelectZero for QuantumIndex<B, O> {
     #[inline(always)]
     fn select_zero(&self, rank: usize) -> Option<usize> {
         self.bits.select_zero(rank)
@@ -111,8 +168,8 @@ impl<B: SelectHinted + SelectZero, O: BitFieldSlice<usize>, const QUANTUM_LOG2:
 }
 
 /// If the underlying implementation has hint for select zero, forward the methods.
-impl<B: SelectHinted + SelectZeroHinted, O: BitFieldSlice<usize>, const QUANTUM_LOG2: usize>
-    SelectZeroHinted for QuantumIndex<B, O, QUANTUM_LOG2>
+impl<B: SelectHinted + SelectZeroHinted, O: BitFieldSlice<usize>> SelectZeroHinted
+    for QuantumIndex<B, O>
 {
     #[inline(always)]
     unsafe fn select_zero_hinted_unchecked(
@@ -131,18 +188,14 @@ impl<B: SelectHinted + SelectZeroHinted, O: BitFieldSlice<usize>, const QUANTUM_
     }
 }
 
-impl<B: SelectHinted + BitLength, O: BitFieldSlice<usize>, const QUANTUM_LOG2: usize> BitLength
-    for QuantumIndex<B, O, QUANTUM_LOG2>
-{
+impl<B: SelectHinted + BitLength, O: BitFieldSlice<usize>> BitLength for QuantumIndex<B, O> {
     #[inline(always)]
     fn len(&self) -> usize {
         self.bits.len()
     }
 }
 
-impl<B: SelectHinted, O: BitFieldSlice<usize>, const QUANTUM_LOG2: usize> BitCount
-    for QuantumIndex<B, O, QUANTUM_LOG2>
-{
+impl<B: SelectHinted, O: BitFieldSlice<usize>> BitCount for QuantumIndex<B, O> {
     #[inline(always)]
     fn count(&self) -> usize {
         self.bits.count()
@@ -150,8 +203,7 @@ impl<B: SelectHinted, O: BitFieldSlice<usize>, const QUANTUM_LOG2: usize> BitCou
 }
 
 /// Forget the index.
-impl<B: SelectHinted, T, const QUANTUM_LOG2: usize> ConvertTo<B>
-    for QuantumIndex<B, T, QUANTUM_LOG2>
+impl<B: SelectHinted, T> ConvertTo<B> for QuantumIndex<B, T>
 where
     T: AsRef<[usize]>,
 {
@@ -162,22 +214,31 @@ where
 }
 
 /// Create and add a quantum index.
-impl<B: SelectHinted + AsRef<[usize]>, const QUANTUM_LOG2: usize>
-    ConvertTo<QuantumIndex<B, Vec<usize>, QUANTUM_LOG2>> for B
-{
+impl<B: SelectHinted + AsRef<[usize]>> ConvertTo<QuantumIndex<B, Vec<usize>>> for B {
     #[inline(always)]
-    fn convert_to(self) -> Result<QuantumIndex<B, Vec<usize>, QUANTUM_LOG2>> {
-        let mut res = QuantumIndex {
-            ones: vec![0; (self.count() + (1 << QUANTUM_LOG2) - 1) >> QUANTUM_LOG2],
-            bits: self,
-            _marker: core::marker::PhantomData,
-        };
-        res.build_ones()?;
-        Ok(res)
+    fn convert_to(self) -> Result<QuantumIndex<B, Vec<usize>>> {
+        QuantumIndex::with_quantum(self, DEFAULT_LOG2_QUANTUM)
+    }
+}
+
+/// The space of the index is reported as `select_index`.
+impl<B: SelectHinted + SpaceUsage, O: BitFieldSlice<usize> + SpaceUsage> SpaceUsage
+    for QuantumIndex<B, O>
+{
+    fn mem_size(&self) -> usize {
+        core::mem::size_of::<Self>() - core::mem::size_of::<B>() - core::mem::size_of::<O>()
+            + self.bits.mem_size()
+            + self.ones.mem_size()
+    }
+
+    fn space_breakdown(&self) -> BTreeMap<&'static str, usize> {
+        let mut breakdown = self.bits.space_breakdown();
+        *breakdown.entry("select_index").or_insert(0) += self.mem_size() - self.bits.mem_size();
+        breakdown
     }
 }
 
-impl<B, O, const QUANTUM_LOG2: usize> AsRef<[usize]> for QuantumIndex<B, O, QUANTUM_LOG2>
+impl<B, O> AsRef<[usize]> for QuantumIndex<B, O>
 where
     B: AsRef<[usize]> + SelectHinted,
     O: BitFieldSlice<usize>,
@@ -10,18 +10,27 @@
 use crate::prelude::*;
 use crate::traits::bit_field_slice::BitFieldSlice;
 use crate::traits::bit_field_slice::BitFieldSliceMut;
-use anyhow::Result;
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use anyhow::{bail, Result};
 use common_traits::SelectInWord;
+#[cfg(feature = "std")]
 use epserde::*;
 
 /// An index that records the position of the zeros in a bit vector at a fixed
 /// set of positions.
 ///
-/// More precisely, given a constant quantum <var>q</var>, this index records the position
+/// More precisely, given a quantum <var>q</var>, this index records the position
 /// of the zeros at positions 0, <var>q</var>, <var>2q</var>, &hellip;, and so on.
 /// The positions are recorded in a provided [`BitFieldSliceMut`] whose [bit width](BitFieldSliceCore::bit_width)
 /// must be sufficient to record all the positions.
 ///
+/// As in the case of [`QuantumIndex`](crate::rank_sel::QuantumIndex), the quantum is a power of two
+/// chosen at construction time: [`QuantumZeroIndex::new`] and [`ConvertTo`] use
+/// 2<sup>[`DEFAULT_LOG2_QUANTUM`]</sup>, whereas [`QuantumZeroIndex::with_quantum`]
+/// makes it possible to choose a different quantum.
+///
 /// The index takes a backend parameter `B` that can be any type that implements
 /// [`SelectHinted`]. This will usually be something like [`CountBitVec`](crate::bits::bit_vec::CountBitVec), or possibly
 /// a [`CountBitVec`](crate::bits::bit_vec::CountBitVec) wrapped in another index structure for which
@@ -29,22 +38,63 @@ use epserde::*;
 /// of [`EliasFano`](crate::dict::elias_fano::EliasFano) for an example of this approach.
 ///
 /// See [`QuantumIndex`](crate::rank_sel::QuantumIndex) for the same index for ones.
-#[derive(Epserde, Debug, Clone, PartialEq, Eq, Hash)]
-pub struct QuantumZeroIndex<
-    B: SelectZeroHinted = CountBitVec,
-    O: BitFieldSlice<usize> = Vec<usize>,
-    const QUANTUM_LOG2: usize = 8,
-> {
+#[cfg_attr(feature = "std", derive(Epserde))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QuantumZeroIndex<B: SelectZeroHinted = CountBitVec, O: BitFieldSlice<usize> = Vec<usize>>
+{
     bits: B,
     zeros: O,
-    _marker: core::marker::PhantomData<[(); QUANTUM_LOG2]>,
+    /// The base-2 logarithm of the quantum.
+    log2_quantum: usize,
+}
+
+impl<B: SelectZeroHinted + AsRef<[usize]>> QuantumZeroIndex<B, Vec<usize>> {
+    /// Create a new index on the given bit vector using a quantum
+    /// of 2<sup>[`DEFAULT_LOG2_QUANTUM`]</sup>.
+    pub fn new(bitvec: B) -> Result<Self> {
+        Self::with_quantum(bitvec, DEFAULT_LOG2_QUANTUM)
+    }
+
+    /// Create a new index on the given bit vector using a quantum
+    /// of 2<sup>`log2_quantum`</sup>.
+    ///
+    /// Returns an error if `log2_quantum` is not smaller than `usize::BITS`.
+    pub fn with_quantum(bitvec: B, log2_quantum: u32) -> Result<Self> {
+        if log2_quantum >= usize::BITS {
+            bail!(
+                "The base-2 logarithm of the quantum must be smaller than {}, but it is {}",
+                usize::BITS,
+                log2_quantum
+            );
+        }
+        let log2_quantum = log2_quantum as usize;
+        let number_of_zeros = bitvec.len() - bitvec.count();
+        let mut res = QuantumZeroIndex {
+            zeros: vec![0; number_of_zeros.div_ceil(1 << log2_quantum)],
+            bits: bitvec,
+            log2_quantum,
+        };
+        res.build_zeros()?;
+        Ok(res)
+    }
 }
 
-impl<
-        B: SelectZeroHinted + AsRef<[usize]>,
-        O: BitFieldSlice<usize> + BitFieldSliceMut<usize>,
-        const QUANTUM_LOG2: usize,
-    > QuantumZeroIndex<B, O, QUANTUM_LOG2>
+impl<B: SelectZeroHinted, O: BitFieldSlice<usize>> QuantumZeroIndex<B, O> {
+    /// Return the base-2 logarithm of the quantum.
+    #[inline(always)]
+    pub fn log2_quantum(&self) -> u32 {
+        self.log2_quantum as u32
+    }
+
+    /// Return the number of hints stored in the index.
+    #[inline(always)]
+    pub fn num_hints(&self) -> usize {
+        self.zeros.len()
+    }
+}
+
+impl<B: SelectZeroHinted + AsRef<[usize]>, O: BitFieldSlice<usize> + BitFieldSliceMut<usize>>
+    QuantumZeroIndex<B, O>
 {
     fn build_zeros(&mut self) -> Result<()> {
         let mut number_of_zeros = 0;
@@ -61,7 +111,7 @@ impl<
                     return Ok(());
                 }
                 self.zeros.set(zeros_index, index);
-                next_quantum += 1 << QUANTUM_LOG2;
+                next_quantum += 1 << self.log2_quantum;
                 zeros_index += 1;
             }
 
@@ -73,14 +123,12 @@ impl<
 }
 
 /// Provide the hint to the underlying structure
-impl<B: SelectZeroHinted, O: BitFieldSlice<usize>, const QUANTUM_LOG2: usize> SelectZero
-    for QuantumZeroIndex<B, O, QUANTUM_LOG2>
-{
+impl<B: SelectZeroHinted, O: BitFieldSlice<usize>> SelectZero for QuantumZeroIndex<B, O> {
     #[inline(always)]
     unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
-        let index = rank >> QUANTUM_LOG2;
+        let index = rank >> self.log2_quantum;
         let pos = self.zeros.get_unchecked(index);
-        let rank_at_pos = index << QUANTUM_LOG2;
+        let rank_at_pos = index << self.log2_quantum;
 
         self.bits
             .select_zero_hinted_unchecked(rank, pos, rank_at_pos)
@@ -88,9 +136,7 @@ impl<B: SelectZeroHinted, O: BitFieldSlice<usize>, const QUANTUM_LOG2: usize> Se
 }
 
 /// If the underlying implementation has select, forward the methods
-impl<B: SelectZeroHinted + Select, O: BitFieldSlice<usize>, const QUANTUM_LOG2: usize> Select
-    for QuantumZeroIndex<B, O, QUANTUM_LOG2>
-{
+impl<B: SelectZeroHinted + Select, O: BitFieldSlice<usize>> Select for QuantumZeroIndex<B, O> {
     #[inline(always)]
     fn select(&self, rank: usize) -> Option<usize> {
         self.bits.select(rank)
@@ -101,18 +147,14 @@ impl<B: SelectZeroHinted + Select, O: BitFieldSlice<usize>, const QUANTUM_LOG2:
     }
 }
 
-impl<B: SelectZeroHinted, O: BitFieldSlice<usize>, const QUANTUM_LOG2: usize> BitLength
-    for QuantumZeroIndex<B, O, QUANTUM_LOG2>
-{
+impl<B: SelectZeroHinted, O: BitFieldSlice<usize>> BitLength for QuantumZeroIndex<B, O> {
     #[inline(always)]
     fn len(&self) -> usize {
         self.bits.len()
     }
 }
 
-impl<B: SelectZeroHinted, O: BitFieldSlice<usize>, const QUANTUM_LOG2: usize> BitCount
-    for QuantumZeroIndex<B, O, QUANTUM_LOG2>
-{
+impl<B: SelectZeroHinted, O: BitFieldSlice<usize>> BitCount for QuantumZeroIndex<B, O> {
     #[inline(always)]
     fn count(&self) -> usize {
         self.bits.count()
@@ -120,9 +162,7 @@ impl<B: SelectZeroHinted, O: BitFieldSlice<usize>, const QUANTUM_LOG2: usize> Bi
 }
 
 /// Forget the index.
-impl<B: SelectZeroHinted, const QUANTUM_LOG2: usize> ConvertTo<B>
-    for QuantumZeroIndex<B, Vec<usize>, QUANTUM_LOG2>
-{
+impl<B: SelectZeroHinted> ConvertTo<B> for QuantumZeroIndex<B, Vec<usize>> {
     #[inline(always)]
     fn convert_to(self) -> Result<B> {
         Ok(self.bits)
@@ -130,22 +170,32 @@ impl<B: SelectZeroHinted, const QUANTUM_LOG2: usize> ConvertTo<B>
 }
 
 /// Create and add a quantum index.
-impl<B: SelectZeroHinted + AsRef<[usize]>, const QUANTUM_LOG2: usize>
-    ConvertTo<QuantumZeroIndex<B, Vec<usize>, QUANTUM_LOG2>> for B
-{
+impl<B: SelectZeroHinted + AsRef<[usize]>> ConvertTo<QuantumZeroIndex<B, Vec<usize>>> for B {
     #[inline(always)]
-    fn convert_to(self) -> Result<QuantumZeroIndex<B, Vec<usize>, QUANTUM_LOG2>> {
-        let mut res = QuantumZeroIndex {
-            zeros: vec![0; (self.len() - self.count() + (1 << QUANTUM_LOG2) - 1) >> QUANTUM_LOG2],
-            bits: self,
-            _marker: core::marker::PhantomData,
-        };
-        res.build_zeros()?;
-        Ok(res)
+    fn convert_to(self) -> Result<QuantumZeroIndex<B, Vec<usize>>> {
+        QuantumZeroIndex::with_quantum(self, DEFAULT_LOG2_QUANTUM)
+    }
+}
+
+/// The space of the index is reported as `select_zero_index`.
+impl<B: SelectZeroHinted + SpaceUsage, O: BitFieldSlice<usize> + SpaceUsage> SpaceUsage
+    for QuantumZeroIndex<B, O>
+{
+    fn mem_size(&self) -> usize {
+        core::mem::size_of::<Self>() - core::mem::size_of::<B>() - core::mem::size_of::<O>()
+            + self.bits.mem_size()
+            + self.zeros.mem_size()
+    }
+
+    fn space_breakdown(&self) -> BTreeMap<&'static str, usize> {
+        let mut breakdown = self.bits.space_breakdown();
+        *breakdown.entry("select_zero_index").or_insert(0) +=
+            self.mem_size() - self.bits.mem_size();
+        breakdown
     }
 }
 
-impl<B, O, const QUANTUM_LOG2: usize> AsRef<[usize]> for QuantumZeroIndex<B, O, QUANTUM_LOG2>
+impl<B, O> AsRef<[usize]> for QuantumZeroIndex<B, O>
 where
     B: AsRef<[usize]> + SelectZeroHinted,
     O: BitFieldSlice<usize>,
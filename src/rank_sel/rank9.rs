@@ -0,0 +1,202 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use anyhow::Result;
+#[cfg(feature = "std")]
+use epserde::*;
+
+const BITS: usize = usize::BITS as usize;
+/// The base-2 logarithm of the number of words in a block.
+const LOG2_WORDS_PER_BLOCK: usize = 3;
+const WORDS_PER_BLOCK: usize = 1 << LOG2_WORDS_PER_BLOCK;
+
+/// A constant-time rank structure based on the `rank9` algorithm.
+///
+/// The underlying bit vector is divided in blocks of eight words (512 bits on
+/// 64-bit platforms). For each block, the structure stores in a pair of
+/// 64-bit words the number of ones preceding the block, and the seven
+/// numbers of ones preceding each word of the block other than the first
+/// one, relative to the beginning of the block, packed in 9 bits each.
+/// Thus, [`rank`](Rank::rank) accesses a single pair of adjacent counts
+/// and a single word of the bit vector.
+///
+/// The structure uses 128 bits every 512 bits, that is, 25% of the
+/// space of the underlying bit vector: see [`Rank9::print_stats`] and the
+/// implementation of [`SpaceUsage`].
+///
+/// This structure is the rank part of the `rank9`/`select9` pair described
+/// by Sebastiano Vigna in &ldquo;[Broadword implementation of rank/select
+/// queries](https://doi.org/10.1007/978-3-540-68552-4_12)&rdquo;, _Proc. WEA
+/// 2008_, LNCS 5038, pages 154&minus;168, Springer, 2008.
+#[cfg_attr(feature = "std", derive(Epserde))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rank9<B: AsRef<[usize]> + BitLength = BitVec, C: AsRef<[u64]> = Vec<u64>> {
+    bits: B,
+    /// For each block, the number of ones preceding the block, followed by
+    /// the relative counts of the words of the block.
+    counts: C,
+}
+
+impl<B: AsRef<[usize]> + BitLength> Rank9<B, Vec<u64>> {
+    /// Create a new rank structure on the given bit vector.
+    pub fn new(bits: B) -> Self {
+        let words = bits.as_ref();
+        // The last block might be empty, as rank(len) must be answerable
+        // when len is a multiple of the block size
+        let num_blocks = words.len() / WORDS_PER_BLOCK + 1;
+        let mut counts = Vec::with_capacity(num_blocks * 2);
+        let mut number_of_ones = 0;
+        for block in 0..num_blocks {
+            counts.push(number_of_ones);
+            let mut relative = 0;
+            let mut packed = 0;
+            for i in 0..WORDS_PER_BLOCK {
+                if i != 0 {
+                    packed |= relative << (9 * (i - 1));
+                }
+                if let Some(word) = words.get(block * WORDS_PER_BLOCK + i) {
+                    relative += word.count_ones() as u64;
+                }
+            }
+            counts.push(packed);
+            number_of_ones += relative;
+        }
+        Self { bits, counts }
+    }
+}
+
+impl<B: AsRef<[usize]> + BitLength, C: AsRef<[u64]>> Rank9<B, C> {
+    /// Return the number of blocks of the structure.
+    #[inline(always)]
+    pub fn num_blocks(&self) -> usize {
+        self.counts.as_ref().len() / 2
+    }
+}
+
+#[cfg(feature = "std")]
+impl<B: AsRef<[usize]> + BitLength + SpaceUsage, C: AsRef<[u64]> + SpaceUsage> Rank9<B, C> {
+    /// Print in an human readable format the size of the structure and its
+    /// overhead with respect to the underlying bit vector.
+    pub fn print_stats(&self) {
+        let index_size = self.mem_size() - self.bits.mem_size();
+        println!("{:>20}: {:>10}", "blocks", self.num_blocks());
+        println!("{:>20}: {:>10}", "index_bytes", index_size);
+        println!(
+            "{:>20}: {:>10.3}",
+            "bits/bit",
+            index_size as f64 * 8.0 / self.bits.len() as f64
+        );
+        println!(
+            "{:>20}: {:>9.3}%",
+            "overhead",
+            index_size as f64 * 100.0 / self.bits.mem_size() as f64
+        );
+    }
+}
+
+impl<B: AsRef<[usize]> + BitLength, C: AsRef<[u64]>> Rank for Rank9<B, C> {
+    #[inline(always)]
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        let word = pos / BITS;
+        let block = word >> LOG2_WORDS_PER_BLOCK;
+        let offset = word & (WORDS_PER_BLOCK - 1);
+        let counts = self.counts.as_ref();
+        let mut rank = *counts.get_unchecked(block * 2) as usize;
+        if offset != 0 {
+            rank += (*counts.get_unchecked(block * 2 + 1) >> (9 * (offset - 1)) & 0x1FF) as usize;
+        }
+        let bit_index = pos % BITS;
+        if bit_index != 0 {
+            rank += (self.bits.as_ref().get_unchecked(word) & ((1 << bit_index) - 1)).count_ones()
+                as usize;
+        }
+        rank
+    }
+}
+
+impl<B: AsRef<[usize]> + BitLength, C: AsRef<[u64]>> RankZero for Rank9<B, C> {}
+
+impl<B: AsRef<[usize]> + BitLength, C: AsRef<[u64]>> BitLength for Rank9<B, C> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.bits.len()
+    }
+}
+
+impl<B: AsRef<[usize]> + BitLength, C: AsRef<[u64]>> BitCount for Rank9<B, C> {
+    #[inline(always)]
+    fn count(&self) -> usize {
+        // SAFETY: the length is a valid position
+        unsafe { self.rank_unchecked(BitLength::len(self)) }
+    }
+}
+
+/// If the underlying implementation has select, forward the methods.
+impl<B: AsRef<[usize]> + BitLength + Select, C: AsRef<[u64]>> Select for Rank9<B, C> {
+    #[inline(always)]
+    fn select(&self, rank: usize) -> Option<usize> {
+        self.bits.select(rank)
+    }
+    #[inline(always)]
+    unsafe fn select_unchecked(&self, rank: usize) -> usize {
+        self.bits.select_unchecked(rank)
+    }
+}
+
+/// If the underlying implementation has select zero, forward the methods.
+impl<B: AsRef<[usize]> + BitLength + SelectZero, C: AsRef<[u64]>> SelectZero for Rank9<B, C> {
+    #[inline(always)]
+    fn select_zero(&self, rank: usize) -> Option<usize> {
+        self.bits.select_zero(rank)
+    }
+    #[inline(always)]
+    unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
+        self.bits.select_zero_unchecked(rank)
+    }
+}
+
+/// Forget the structure.
+impl<B: AsRef<[usize]> + BitLength> ConvertTo<B> for Rank9<B, Vec<u64>> {
+    #[inline(always)]
+    fn convert_to(self) -> Result<B> {
+        Ok(self.bits)
+    }
+}
+
+/// Create and add a rank structure.
+impl<B: AsRef<[usize]> + BitLength> ConvertTo<Rank9<B, Vec<u64>>> for B {
+    #[inline(always)]
+    fn convert_to(self) -> Result<Rank9<B, Vec<u64>>> {
+        Ok(Rank9::new(self))
+    }
+}
+
+/// The space of the structure is reported as `rank_index`.
+impl<B: AsRef<[usize]> + BitLength + SpaceUsage, C: AsRef<[u64]> + SpaceUsage> SpaceUsage
+    for Rank9<B, C>
+{
+    fn mem_size(&self) -> usize {
+        core::mem::size_of::<Self>() - core::mem::size_of::<B>() - core::mem::size_of::<C>()
+            + self.bits.mem_size()
+            + self.counts.mem_size()
+    }
+
+    fn space_breakdown(&self) -> BTreeMap<&'static str, usize> {
+        let mut breakdown = self.bits.space_breakdown();
+        *breakdown.entry("rank_index").or_insert(0) += self.mem_size() - self.bits.mem_size();
+        breakdown
+    }
+}
+
+impl<B: AsRef<[usize]> + BitLength, C: AsRef<[u64]>> AsRef<[usize]> for Rank9<B, C> {
+    fn as_ref(&self) -> &[usize] {
+        self.bits.as_ref()
+    }
+}
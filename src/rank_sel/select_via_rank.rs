@@ -0,0 +1,89 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+use anyhow::Result;
+#[cfg(feature = "std")]
+use epserde::*;
+
+/// An adapter implementing [`Select`] on a [`Rank`] structure by binary search.
+///
+/// Each selection performs &Theta;(log <var>len</var>) rank queries, so it is
+/// much slower than using a selection index such as [`QuantumIndex`]; on the
+/// other hand, the only additional space is the number of ones, which is
+/// computed at construction. This adapter is thus useful for bit vectors
+/// on which selection is rare.
+#[cfg_attr(feature = "std", derive(Epserde))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SelectViaRank<R> {
+    bits: R,
+    number_of_ones: usize,
+}
+
+impl<R: Rank> SelectViaRank<R> {
+    /// Create a new adapter on the given rank structure.
+    pub fn new(bits: R) -> Self {
+        let number_of_ones = bits.rank(bits.len());
+        Self {
+            bits,
+            number_of_ones,
+        }
+    }
+}
+
+/// Select by binary search on rank.
+impl<R: Rank> Select for SelectViaRank<R> {
+    unsafe fn select_unchecked(&self, rank: usize) -> usize {
+        // Invariant: rank(low) <= rank < rank(high)
+        let mut low = 0;
+        let mut high = self.bits.len();
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if self.bits.rank_unchecked(mid) <= rank {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+}
+
+impl<R: Rank> Rank for SelectViaRank<R> {
+    #[inline(always)]
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        self.bits.rank_unchecked(pos)
+    }
+}
+
+impl<R: BitLength> BitLength for SelectViaRank<R> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.bits.len()
+    }
+}
+
+impl<R> BitCount for SelectViaRank<R> {
+    #[inline(always)]
+    fn count(&self) -> usize {
+        self.number_of_ones
+    }
+}
+
+/// Forget the adapter.
+impl<R> ConvertTo<R> for SelectViaRank<R> {
+    #[inline(always)]
+    fn convert_to(self) -> Result<R> {
+        Ok(self.bits)
+    }
+}
+
+impl<R: AsRef<[usize]>> AsRef<[usize]> for SelectViaRank<R> {
+    fn as_ref(&self) -> &[usize] {
+        self.bits.as_ref()
+    }
+}
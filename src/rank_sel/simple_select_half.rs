@@ -6,7 +6,10 @@
  */
 
 use crate::{bits::CountBitVec, traits::*};
+use alloc::vec;
+use alloc::vec::Vec;
 use common_traits::SelectInWord;
+#[cfg(feature = "std")]
 use epserde::*;
 //#[cfg(feature = "rayon")]
 //use rayon::prelude::*;
@@ -14,7 +17,8 @@ use epserde::*;
 /// Two layer index (with interleaved layers) optimized for
 /// a bitmap with approximately half ones and half zeros.
 /// This is meant for elias-fano high-bits.
-#[derive(Epserde, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "std", derive(Epserde))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SimpleSelectHalf<
     B: SelectHinted = CountBitVec,
     I: AsRef<[u64]> = Vec<u64>,
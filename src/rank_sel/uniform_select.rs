@@ -0,0 +1,178 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+use alloc::vec::Vec;
+use anyhow::Result;
+use common_traits::SelectInWord;
+#[cfg(feature = "std")]
+use epserde::*;
+
+/// A selection structure specialized for bit vectors whose ones are
+/// approximately uniformly distributed (e.g., bitmaps derived from hashes).
+///
+/// The structure records the number of ones preceding each block of
+/// 2<sup>`LOG2_WORDS_PER_BLOCK`</sup> words. To select the one of rank <var>r</var>,
+/// it interpolates the expected position <var>r</var> &middot; <var>len</var> / <var>count</var>,
+/// corrects the guess by interpolation on the recorded counts, and then
+/// completes the search with a short linear scan inside a block. Under
+/// the uniformity assumption, a constant number of steps is sufficient
+/// on average.
+///
+/// If the interpolation does not converge in a few steps (e.g., because the
+/// ones are clustered), the structure falls back to the selection structure
+/// of the backend `B`, which will usually be an index such as
+/// [`QuantumIndex`]: thus, results are always correct, but on non-uniform bit
+/// vectors queries will be slower than using the backend directly.
+///
+/// The structure uses a word every block, that is, with the default
+/// `LOG2_WORDS_PER_BLOCK` of 3, 64 bits every 512 bits, or 12.5% of the
+/// space of the underlying bit vector, in addition to the space used by the
+/// backend. The counts cannot be derived from the backend: the inventory of a
+/// selection index such as [`QuantumIndex`] records the positions of ones of
+/// given ranks, rather than the ranks at given positions, so it cannot tell
+/// whether an interpolated guess is correct without a search, which is what
+/// the structure is trying to avoid. The overhead can be reduced by
+/// increasing `LOG2_WORDS_PER_BLOCK`, at the price of longer linear scans.
+#[cfg_attr(feature = "std", derive(Epserde))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UniformSelect<
+    B: Select + BitLength + AsRef<[usize]> = QuantumIndex<CountBitVec>,
+    O: AsRef<[usize]> = Vec<usize>,
+    const LOG2_WORDS_PER_BLOCK: usize = 3,
+> {
+    bits: B,
+    block_ranks: O,
+}
+
+impl<B: Select + BitLength + AsRef<[usize]>, const LOG2_WORDS_PER_BLOCK: usize>
+    UniformSelect<B, Vec<usize>, LOG2_WORDS_PER_BLOCK>
+{
+    /// Create a new structure on the given bit vector, assuming that its ones
+    /// are approximately uniformly distributed.
+    ///
+    /// The assumption affects only speed, not correctness: see the
+    /// [type documentation](UniformSelect).
+    pub fn assuming_uniform(bits: B) -> Result<Self> {
+        let words = bits.as_ref();
+        let num_blocks = (words.len() + (1 << LOG2_WORDS_PER_BLOCK) - 1) >> LOG2_WORDS_PER_BLOCK;
+        let mut block_ranks = Vec::with_capacity(num_blocks);
+        let mut number_of_ones = 0;
+        for block in words.chunks(1 << LOG2_WORDS_PER_BLOCK) {
+            block_ranks.push(number_of_ones);
+            number_of_ones += block.iter().map(|w| w.count_ones() as usize).sum::<usize>();
+        }
+        Ok(Self { bits, block_ranks })
+    }
+}
+
+impl<
+        B: Select + BitLength + AsRef<[usize]>,
+        O: AsRef<[usize]>,
+        const LOG2_WORDS_PER_BLOCK: usize,
+    > UniformSelect<B, O, LOG2_WORDS_PER_BLOCK>
+{
+    const BITS_PER_BLOCK: usize = (usize::BITS as usize) << LOG2_WORDS_PER_BLOCK;
+    /// The maximum number of interpolation steps before falling back.
+    const MAX_STEPS: usize = 4;
+}
+
+/// Select by interpolation, falling back to the underlying structure.
+impl<
+        B: Select + BitLength + AsRef<[usize]>,
+        O: AsRef<[usize]>,
+        const LOG2_WORDS_PER_BLOCK: usize,
+    > Select for UniformSelect<B, O, LOG2_WORDS_PER_BLOCK>
+{
+    unsafe fn select_unchecked(&self, rank: usize) -> usize {
+        let block_ranks = self.block_ranks.as_ref();
+        let num_blocks = block_ranks.len();
+        let len = self.bits.len() as u128;
+        let count = self.bits.count() as u128;
+
+        // The expected position of the one of given rank
+        let expected = (rank as u128 * len / count) as usize;
+        let mut block = (expected / Self::BITS_PER_BLOCK).min(num_blocks - 1);
+
+        for _ in 0..Self::MAX_STEPS {
+            let rank_at_block = *block_ranks.get_unchecked(block);
+            if rank_at_block <= rank {
+                if block + 1 == num_blocks || *block_ranks.get_unchecked(block + 1) > rank {
+                    // Found the block: linear scan
+                    let words = self.bits.as_ref();
+                    let mut word_index = block << LOG2_WORDS_PER_BLOCK;
+                    let mut residual = rank - rank_at_block;
+                    loop {
+                        let word = *words.get_unchecked(word_index);
+                        let bit_count = word.count_ones() as usize;
+                        if residual < bit_count {
+                            return word_index * usize::BITS as usize
+                                + word.select_in_word(residual);
+                        }
+                        residual -= bit_count;
+                        word_index += 1;
+                    }
+                }
+                // Interpolate forward, moving at least one block
+                let delta = ((rank - rank_at_block) as u128 * len / count) as usize;
+                block = (block + (delta / Self::BITS_PER_BLOCK).max(1)).min(num_blocks - 1);
+            } else {
+                // Interpolate backward, moving at least one block
+                let delta = ((rank_at_block - rank) as u128 * len / count) as usize;
+                block -= (delta / Self::BITS_PER_BLOCK).clamp(1, block);
+            }
+        }
+
+        // The ones are not uniformly distributed around this rank
+        self.bits.select_unchecked(rank)
+    }
+}
+
+impl<
+        B: Select + BitLength + AsRef<[usize]>,
+        O: AsRef<[usize]>,
+        const LOG2_WORDS_PER_BLOCK: usize,
+    > BitLength for UniformSelect<B, O, LOG2_WORDS_PER_BLOCK>
+{
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.bits.len()
+    }
+}
+
+impl<
+        B: Select + BitLength + AsRef<[usize]>,
+        O: AsRef<[usize]>,
+        const LOG2_WORDS_PER_BLOCK: usize,
+    > BitCount for UniformSelect<B, O, LOG2_WORDS_PER_BLOCK>
+{
+    #[inline(always)]
+    fn count(&self) -> usize {
+        self.bits.count()
+    }
+}
+
+/// Forget the index.
+impl<B: Select + BitLength + AsRef<[usize]>, const LOG2_WORDS_PER_BLOCK: usize> ConvertTo<B>
+    for UniformSelect<B, Vec<usize>, LOG2_WORDS_PER_BLOCK>
+{
+    #[inline(always)]
+    fn convert_to(self) -> Result<B> {
+        Ok(self.bits)
+    }
+}
+
+impl<
+        B: Select + BitLength + AsRef<[usize]>,
+        O: AsRef<[usize]>,
+        const LOG2_WORDS_PER_BLOCK: usize,
+    > AsRef<[usize]> for UniformSelect<B, O, LOG2_WORDS_PER_BLOCK>
+{
+    fn as_ref(&self) -> &[usize] {
+        self.bits.as_ref()
+    }
+}
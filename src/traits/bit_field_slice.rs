@@ -53,7 +53,7 @@ of ambiguities in trait resolution will arise. In particular, if you plan to use
 suggest that you do not import the prelude.
 ```rust
 use sux::traits::bit_field_slice::{AtomicBitFieldSlice,AtomicHelper};
-use std::sync::atomic::Ordering;
+use core::sync::atomic::Ordering;
 
 let slice = sux::bits::AtomicBitFieldVec::<usize>::new(3, 3);
 slice.set(0, 1, Ordering::Relaxed);
@@ -62,8 +62,8 @@ assert_eq!(slice.get(0, Ordering::Relaxed), 1);
 
 */
 use common_traits::*;
+use core::marker::PhantomData;
 use core::sync::atomic::*;
-use std::marker::PhantomData;
 
 /// A derived trait that the types used as a parameter for [`BitFieldSlice`] must satisfy.
 /// To be usable in an [`AtomicBitFieldSlice`], the type must also implement [`IntoAtomic`].
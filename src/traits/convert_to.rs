@@ -7,8 +7,9 @@
 
 //! Custom conversion trait.
 
+use alloc::vec::Vec;
 use anyhow::Result;
-use std::sync::atomic::*;
+use core::sync::atomic::*;
 
 /// Like [`Into`], but we need to avoid the orphan rule and error
 /// [E0210](https://github.com/rust-lang/rust/blob/master/compiler/rustc_error_codes/src/error_codes/E0210.md).
@@ -39,37 +40,37 @@ macro_rules! convert_to {
         impl ConvertTo<Vec<$std>> for Vec<$atomic> {
             #[inline(always)]
             fn convert_to(self) -> Result<Vec<$std>> {
-                Ok(unsafe { std::mem::transmute::<Vec<$atomic>, Vec<$std>>(self) })
+                Ok(unsafe { core::mem::transmute::<Vec<$atomic>, Vec<$std>>(self) })
             }
         }
         impl ConvertTo<Vec<$atomic>> for Vec<$std> {
             #[inline(always)]
             fn convert_to(self) -> Result<Vec<$atomic>> {
-                Ok(unsafe { std::mem::transmute::<Vec<$std>, Vec<$atomic>>(self) })
+                Ok(unsafe { core::mem::transmute::<Vec<$std>, Vec<$atomic>>(self) })
             }
         }
         impl<'a> ConvertTo<&'a [$atomic]> for &'a [$std] {
             #[inline(always)]
             fn convert_to(self) -> Result<&'a [$atomic]> {
-                Ok(unsafe { std::mem::transmute::<&'a [$std], &'a [$atomic]>(self) })
+                Ok(unsafe { core::mem::transmute::<&'a [$std], &'a [$atomic]>(self) })
             }
         }
         impl<'a> ConvertTo<&'a [$std]> for &'a [$atomic] {
             #[inline(always)]
             fn convert_to(self) -> Result<&'a [$std]> {
-                Ok(unsafe { std::mem::transmute::<&'a [$atomic], &'a [$std]>(self) })
+                Ok(unsafe { core::mem::transmute::<&'a [$atomic], &'a [$std]>(self) })
             }
         }
         impl<'a> ConvertTo<&'a mut [$atomic]> for &'a mut [$std] {
             #[inline(always)]
             fn convert_to(self) -> Result<&'a mut [$atomic]> {
-                Ok(unsafe { std::mem::transmute::<&'a mut [$std], &'a mut [$atomic]>(self) })
+                Ok(unsafe { core::mem::transmute::<&'a mut [$std], &'a mut [$atomic]>(self) })
             }
         }
         impl<'a> ConvertTo<&'a mut [$std]> for &'a mut [$atomic] {
             #[inline(always)]
             fn convert_to(self) -> Result<&'a mut [$std]> {
-                Ok(unsafe { std::mem::transmute::<&'a mut [$atomic], &'a mut [$std]>(self) })
+                Ok(unsafe { core::mem::transmute::<&'a mut [$atomic], &'a mut [$std]>(self) })
             }
         }
     };
@@ -8,11 +8,18 @@
 /*!
 
 Traits for indexed dictionaries, possibly with support for additional
-operations such as predecessor and successor.
+operations such as search, predecessor and successor.
+
+Dictionaries that can return their values without copying them implement
+[`IndexedDictRef`].
 
 */
 
-use std::ops::Deref;
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::ops::Deref;
 
 /**
 
@@ -47,6 +54,16 @@ pub trait IndexedDict {
         }
     }
 
+    /// Return the value at the specified index, or `None` if the index
+    /// is not in [0..[len](`IndexedDict::len`)).
+    fn get_checked(&self, index: usize) -> Option<Self::Output> {
+        if index < self.len() {
+            Some(unsafe { self.get_unchecked(index) })
+        } else {
+            None
+        }
+    }
+
     /// Return the value at the specified index.
     ///
     /// # Safety
@@ -57,9 +74,12 @@ pub trait IndexedDict {
     ///
     /// The default implementations just checks iteratively
     /// if the value is equal to any of the values in the dictionary.
+    /// Dictionaries whose values are sorted should implement this method
+    /// using [`SortedIndexedDict::index_of`].
     fn contains(&self, value: &Self::Input) -> bool {
         for i in 0..self.len() {
-            if self.get(i) == *value {
+            // SAFETY: i is smaller than the length
+            if unsafe { self.get_unchecked(i) } == *value {
                 return true;
             }
         }
@@ -75,6 +95,102 @@ pub trait IndexedDict {
     }
 }
 
+/// An [`IndexedDict`] that can return its values without copying them.
+///
+/// [`IndexedDict::get`] returns an owned value, which might require an
+/// allocation (e.g., in the case of a slice of strings). Structures that store
+/// their values explicitly, or whose values are [`Copy`], can implement this trait,
+/// which makes it possible to access a value through a reference-like
+/// type borrowing the dictionary, and thus avoid per-access allocations in
+/// hot loops.
+///
+/// The value returned by [`get_ref`](IndexedDictRef::get_ref) borrows the
+/// dictionary, so the dictionary cannot be modified as long as the value
+/// is in use; if you need to keep the value around, use [`IndexedDict::get`]
+/// or [`ToOwned::to_owned`].
+///
+/// Compressed structures that decode their values into a buffer, such as
+/// [rear-coded lists](crate::dict::rear_coded_list::RearCodedList), cannot
+/// implement this trait; they usually provide methods that decode a value
+/// into a buffer provided by the caller, and iterators reusing an internal
+/// buffer.
+pub trait IndexedDictRef: IndexedDict {
+    /// A type borrowing the value stored in the dictionary.
+    type OutputRef<'a>: Borrow<Self::Output>
+    where
+        Self: 'a;
+
+    /// Return a borrowed view of the value at the specified index.
+    ///
+    /// # Panics
+    /// May panic if the index is not in in [0..[len](`IndexedDict::len`)).
+    fn get_ref(&self, index: usize) -> Self::OutputRef<'_> {
+        if index >= self.len() {
+            panic!("Index out of bounds: {} >= {}", index, self.len())
+        } else {
+            unsafe { self.get_ref_unchecked(index) }
+        }
+    }
+
+    /// Return a borrowed view of the value at the specified index.
+    ///
+    /// # Safety
+    /// `index` must be in [0..[len](`IndexedDict::len`)). No bounds checking is performed.
+    unsafe fn get_ref_unchecked(&self, index: usize) -> Self::OutputRef<'_>;
+}
+
+/// Search in dictionaries whose values are monotonically increasing.
+///
+/// Since the input and output values of an [`IndexedDict`] may be different
+/// (e.g., `str` and `String`), comparisons are performed by
+/// [borrowing](Borrow) the output as an input, which is required to be [`Ord`].
+pub trait SortedIndexedDict: IndexedDict
+where
+    Self::Input: Ord,
+    Self::Output: Borrow<Self::Input>,
+{
+    /// Search for a value in the dictionary.
+    ///
+    /// Return `Ok(index)` if the value is in the dictionary, where `index`
+    /// is the index of its first occurrence, and `Err(index)`
+    /// otherwise, where `index` is the position at which the value could be
+    /// inserted maintaining the order (i.e., the number of values in the
+    /// dictionary smaller than the given value). Differently from
+    /// [`slice::binary_search`], the result is fully specified
+    /// in the presence of repeated values.
+    ///
+    /// The default implementation performs a binary search using
+    /// [`get_unchecked`](IndexedDict::get_unchecked). Implementations with faster
+    /// probing strategies can override this method.
+    fn binary_search(&self, value: &Self::Input) -> Result<usize, usize> {
+        let (mut lo, mut hi) = (0, self.len());
+        // Invariant: values before lo are smaller than value,
+        // values from hi onwards are greater than or equal to value.
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if unsafe { self.get_unchecked(mid) }.borrow() < value {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        if lo < self.len() && unsafe { self.get_unchecked(lo) }.borrow() == value {
+            Ok(lo)
+        } else {
+            Err(lo)
+        }
+    }
+
+    /// Return the index of the first occurrence of a value in the dictionary,
+    /// or `None` if the value is not in the dictionary.
+    ///
+    /// This method delegates to [`binary_search`](SortedIndexedDict::binary_search).
+    #[inline]
+    fn index_of(&self, value: &Self::Input) -> Option<usize> {
+        self.binary_search(value).ok()
+    }
+}
+
 /// Successor computation for dictionaries whose values are monotonically increasing.
 pub trait Succ: IndexedDict
 where
@@ -199,3 +315,33 @@ where
         self.deref().len()
     }
 }
+
+// We cannot use a blanket implementation on Deref<Target = [T]>, as the
+// bound Self: 'a would not imply T: 'a.
+
+impl<T: Clone + PartialEq> IndexedDictRef for Vec<T> {
+    type OutputRef<'a> = &'a T where Self: 'a;
+
+    #[inline(always)]
+    unsafe fn get_ref_unchecked(&self, index: usize) -> Self::OutputRef<'_> {
+        self.as_slice().get_unchecked(index)
+    }
+}
+
+impl<T: Clone + PartialEq> IndexedDictRef for Box<[T]> {
+    type OutputRef<'a> = &'a T where Self: 'a;
+
+    #[inline(always)]
+    unsafe fn get_ref_unchecked(&self, index: usize) -> Self::OutputRef<'_> {
+        self.as_ref().get_unchecked(index)
+    }
+}
+
+impl<'b, T: Clone + PartialEq> IndexedDictRef for &'b [T] {
+    type OutputRef<'a> = &'a T where Self: 'a;
+
+    #[inline(always)]
+    unsafe fn get_ref_unchecked(&self, index: usize) -> Self::OutputRef<'_> {
+        (*self).get_unchecked(index)
+    }
+}
@@ -33,3 +33,6 @@ pub use iter::*;
 pub mod rank_sel;
 pub use rank_sel::*;
 mod rank_sel_impl;
+
+pub mod space_usage;
+pub use space_usage::*;
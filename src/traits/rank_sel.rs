@@ -45,6 +45,7 @@ pub trait Rank: BitLength {
 pub trait RankZero: Rank {
     /// Return the number of zeros preceding the specified position.
     fn rank_zero(&self, pos: usize) -> usize {
+        let pos = pos.min(self.len());
         pos - self.rank(pos)
     }
     /// Return the number of zeros preceding the specified position.
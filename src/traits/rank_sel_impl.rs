@@ -1,6 +1,7 @@
 //! This module contains implementations of the rank and select traits for
 //! references to types that implement the traits.
 use crate::traits::*;
+use alloc::boxed::Box;
 
 macro_rules! impl_for_refs {
     ($ty:ty) => {
@@ -0,0 +1,64 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Trait reporting the space used by a structure.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// A trait for structures that can report the number of bytes they use,
+/// possibly broken down by component.
+///
+/// The size of a structure is the size of its fields, plus the size of the
+/// data they refer to: for example, the size of a `Vec<usize>` of length
+/// <var>n</var> is `size_of::<Vec<usize>>()` plus <var>n</var> &middot;
+/// `size_of::<usize>()`. Unused capacity is not counted.
+///
+/// Composite structures implement [`space_breakdown`](SpaceUsage::space_breakdown)
+/// by labelling the contribution of their components, merging the breakdowns of
+/// nested structures (e.g., a selection index reports the space of the index and
+/// of the underlying bit vector separately). The values of the breakdown always
+/// sum to [`mem_size`](SpaceUsage::mem_size).
+pub trait SpaceUsage {
+    /// Return the number of bytes used by the structure.
+    fn mem_size(&self) -> usize;
+
+    /// Return the number of bytes used by each component of the structure.
+    ///
+    /// The default implementation returns a single component labelled `data`.
+    fn space_breakdown(&self) -> BTreeMap<&'static str, usize> {
+        BTreeMap::from([("data", self.mem_size())])
+    }
+}
+
+impl<T: Copy> SpaceUsage for Vec<T> {
+    #[inline(always)]
+    fn mem_size(&self) -> usize {
+        core::mem::size_of::<Self>() + core::mem::size_of_val(self.as_slice())
+    }
+}
+
+impl<'a, T: Copy> SpaceUsage for &'a [T] {
+    #[inline(always)]
+    fn mem_size(&self) -> usize {
+        core::mem::size_of::<Self>() + core::mem::size_of_val(*self)
+    }
+}
+
+/// Add the components of `breakdown` to `into`, renaming the component
+/// `from` (if present) to `to`.
+pub(crate) fn merge_breakdown(
+    into: &mut BTreeMap<&'static str, usize>,
+    breakdown: BTreeMap<&'static str, usize>,
+    from: &'static str,
+    to: &'static str,
+) {
+    for (label, size) in breakdown {
+        let label = if label == from { to } else { label };
+        *into.entry(label).or_insert(0) += size;
+    }
+}
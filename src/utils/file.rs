@@ -9,11 +9,25 @@
 
 Utility wrappers for files.
 
+[`FilenameIntoIterator`] and [`FilenameZstdIntoIterator`] iterate over
+the lines of a file and panic on I/O errors. [`FilenameGzipIntoIterator`]
+and [`FilenameAutoIntoIterator`] return instead a [`FileLines`] iterator,
+which returns I/O errors (e.g., a missing file, malformed UTF-8, or a
+truncated compressed stream) as items. [`FileLines::plain`] and
+[`FileLines::zstd`] provide the same behavior for plain and
+Zstandard-compressed files.
+
 */
 
+use flate2::bufread::MultiGzDecoder;
 use std::{io::*, path::Path};
 use zstd::stream::read::Decoder;
 
+/// The magic bytes at the start of a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// The magic bytes at the start of a Zstandard frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
 /// Adapter to iterate over the lines of a file.
 #[derive(Clone)]
 pub struct FilenameIntoIterator<P: AsRef<Path>>(pub P);
@@ -49,3 +63,102 @@ impl<P: AsRef<Path>> IntoIterator for FilenameZstdIntoIterator<P> {
             .map(|line| line.unwrap())
     }
 }
+
+/// An iterator over the lines of a possibly compressed file returning
+/// I/O errors as items.
+///
+/// If the file cannot be opened, the iterator returns the error and then
+/// stops. Note that after an error from the underlying decoder the iterator
+/// might not make progress, so errors should usually end the iteration.
+pub struct FileLines {
+    lines: Option<Lines<Box<dyn BufRead + Send>>>,
+    error: Option<Error>,
+}
+
+impl FileLines {
+    fn new(reader: Result<Box<dyn BufRead + Send>>) -> Self {
+        match reader {
+            Ok(reader) => Self {
+                lines: Some(reader.lines()),
+                error: None,
+            },
+            Err(error) => Self {
+                lines: None,
+                error: Some(error),
+            },
+        }
+    }
+
+    /// Return an iterator over the lines of a file that is not compressed.
+    pub fn plain(path: impl AsRef<Path>) -> Self {
+        Self::new(
+            std::fs::File::open(path)
+                .map(|file| Box::new(BufReader::new(file)) as Box<dyn BufRead + Send>),
+        )
+    }
+
+    /// Return an iterator over the lines of a file compressed with Zstandard.
+    pub fn zstd(path: impl AsRef<Path>) -> Self {
+        Self::new(std::fs::File::open(path).and_then(|file| {
+            Ok(Box::new(BufReader::new(Decoder::new(file)?)) as Box<dyn BufRead + Send>)
+        }))
+    }
+}
+
+impl Iterator for FileLines {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
+        self.lines.as_mut()?.next()
+    }
+}
+
+/// Adapter to iterate over the lines of a file compressed with gzip.
+///
+/// Concatenated gzip members are decoded as a single stream.
+#[derive(Clone)]
+pub struct FilenameGzipIntoIterator<P: AsRef<Path>>(pub P);
+
+impl<P: AsRef<Path>> IntoIterator for FilenameGzipIntoIterator<P> {
+    type Item = Result<String>;
+    type IntoIter = FileLines;
+
+    fn into_iter(self) -> Self::IntoIter {
+        FileLines::new(std::fs::File::open(self.0).map(|file| {
+            Box::new(BufReader::new(MultiGzDecoder::new(BufReader::new(file))))
+                as Box<dyn BufRead + Send>
+        }))
+    }
+}
+
+/// Adapter to iterate over the lines of a file that is compressed with
+/// gzip, compressed with Zstandard, or not compressed at all.
+///
+/// The compression format is detected using the magic bytes at the start
+/// of the file; files not starting with the magic bytes of gzip or
+/// Zstandard are read as plain text.
+#[derive(Clone)]
+pub struct FilenameAutoIntoIterator<P: AsRef<Path>>(pub P);
+
+impl<P: AsRef<Path>> IntoIterator for FilenameAutoIntoIterator<P> {
+    type Item = Result<String>;
+    type IntoIter = FileLines;
+
+    fn into_iter(self) -> Self::IntoIter {
+        FileLines::new(std::fs::File::open(self.0).and_then(|file| {
+            let mut reader = BufReader::new(file);
+            let start = reader.fill_buf()?;
+            let reader: Box<dyn BufRead + Send> = if start.starts_with(&GZIP_MAGIC) {
+                Box::new(BufReader::new(MultiGzDecoder::new(reader)))
+            } else if start.starts_with(&ZSTD_MAGIC) {
+                Box::new(BufReader::new(Decoder::with_buffer(reader)?))
+            } else {
+                Box::new(reader)
+            };
+            Ok(reader)
+        }))
+    }
+}
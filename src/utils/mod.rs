@@ -11,10 +11,14 @@ Utility traits and implementations.
 
 */
 
+#[cfg(feature = "std")]
 pub mod file;
+#[cfg(feature = "std")]
 pub use crate::utils::file::*;
 
+#[cfg(feature = "std")]
 pub mod sig_store;
+#[cfg(feature = "std")]
 pub use crate::utils::sig_store::*;
 
 pub mod spooky;
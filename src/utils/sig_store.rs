@@ -18,14 +18,60 @@ accepts signature/value pairs in any order, and when you call
 that can [iterate on chunks of pairs, where chunks are defined
 by the highest bits of signatures](ChunkStore::iter).
 
+This is the engine behind the construction of [static functions](crate::func)
+in [offline](crate::func::VFuncBuilder::offline) mode, but it can be used
+to build any retrieval structure whose construction proceeds chunk by chunk:
+
+```rust
+# use sux::utils::sig_store::*;
+# fn main() -> anyhow::Result<()> {
+// 2^4 buckets on disk, chunks defined by at most 6 high bits
+let mut sig_store = SigStore::<u64>::new(4, 6, true)?;
+for i in 0..1000_u64 {
+    let sig = <u64 as ToSig>::to_sig(&i, 0);
+    sig_store.push(&(sig, i))?;
+}
+// Group the pairs in 2^2 chunks
+let mut chunk_store = sig_store.into_chunk_store(2)?;
+assert_eq!(chunk_store.chunk_sizes().iter().sum::<usize>(), 1000);
+for (chunk, pairs) in chunk_store.iter()? {
+    // Pairs are sorted by signature
+    assert!(pairs.windows(2).all(|w| w[0].0 < w[1].0));
+    assert!(pairs.iter().all(|(sig, _)| (sig[0] >> 62) as usize == chunk));
+}
+# Ok(())
+# }
+```
+
 The trait [`ToSig`] provides a standard way to generate signatures for a [`SigStore`].
 
+# Temporary files
+
+In [offline](SigStore::new) mode, each bucket is stored in a temporary file
+created in a directory generated by [`tempfile::TempDir`] in the default
+temporary directory. The directory is removed as soon as the files have been
+created, so on Unix-like systems the files have no name and their space is
+reclaimed by the operating system as soon as they are closed, that is, when
+the [`SigStore`] or the [`ChunkStore`] it has been turned into is dropped,
+including on errors and panics. On other systems, the files might not be
+removed.
+
+Each bucket keeps a file open, so a store with 2<sup><var>b</var></sup>
+buckets uses 2<sup><var>b</var></sup> file descriptors for its entire
+lifetime, and for the lifetime of the resulting [`ChunkStore`]: the limit
+on open files (e.g., `ulimit -n`) must be taken into account when choosing
+<var>b</var>. If there are not enough file descriptors [`SigStore::new`]
+returns an error.
+
 */
 
 use anyhow::Result;
 use epserde::traits::ZeroCopy;
+#[cfg(feature = "rayon")]
 use rayon::prelude::ParallelIterator;
+#[cfg(feature = "rayon")]
 use rayon::slice::ParallelSlice;
+#[cfg(feature = "rayon")]
 use rayon::slice::ParallelSliceMut;
 use std::borrow::Cow;
 use std::{collections::VecDeque, fs::File, io::*, marker::PhantomData};
@@ -105,16 +151,17 @@ to_sig_slice!(isize, usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
 
 Accumulates key signatures (i.e., random-looking
 hashes associated to keys) and associated values,
-grouping them in different disk buffers by the high bits of the hash.
+grouping them in different buffers by the high bits of the hash.
 Along the way, it keeps track of the number of signatures with the same
 `max_chunk_high_bits` high bits.
 
 The implementation exploits the fact that signatures are randomly distributed,
 and thus bucket sorting is very effective: at construction time you specify
 the number of high bits to use for bucket sorting (say, 8), and when you
-[push](`SigStore::push`) keys they will be stored in different disk buffers
-(in this case, 256) depending on their high bits. The buffers will be stored
-in a directory created by [`tempfile::TempDir`].
+[push](`SigStore::push`) keys they will be stored in different buffers
+(in this case, 256) depending on their high bits. In offline mode, the
+buffers are [temporary files](self#temporary-files); otherwise, they are
+kept in memory.
 
 After all key signatures and values have been accumulated, you must
 call [`SigStore::into_chunk_store`] to flush the buffers and obtain a
@@ -137,7 +184,7 @@ pub struct SigStore<T> {
     // A mask for the lowest `max_chunk_high_bits` bits.
     max_chunk_mask: u64,
     /// The writers associated to the buckets.
-    writers: VecDeque<BufWriter<File>>,
+    writers: VecDeque<BufWriter<Bucket>>,
     /// The number of keys in each bucket.
     bucket_sizes: Vec<usize>,
     /// The number of keys with the same `max_chunk_high_bits` high bits.
@@ -145,6 +192,48 @@ pub struct SigStore<T> {
     _marker: PhantomData<T>,
 }
 
+/// The storage of a bucket: a temporary file or, in online mode, a
+/// memory buffer.
+#[derive(Debug)]
+enum Bucket {
+    File(File),
+    Memory(Cursor<Vec<u8>>),
+}
+
+impl Read for Bucket {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Bucket::File(file) => file.read(buf),
+            Bucket::Memory(cursor) => cursor.read(buf),
+        }
+    }
+}
+
+impl Write for Bucket {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Bucket::File(file) => file.write(buf),
+            Bucket::Memory(cursor) => cursor.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Bucket::File(file) => file.flush(),
+            Bucket::Memory(cursor) => cursor.flush(),
+        }
+    }
+}
+
+impl Seek for Bucket {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            Bucket::File(file) => file.seek(pos),
+            Bucket::Memory(cursor) => cursor.seek(pos),
+        }
+    }
+}
+
 /**
 
 An container for the signatures and values accumulated by a [`SigStore`], with
@@ -157,16 +246,29 @@ pub struct ChunkStore<T> {
     bucket_high_bits: u32,
     /// The number of high bits defining a chunk.
     chunk_high_bits: u32,
-    /// The files associated to the buckets.
-    files: Vec<File>,
+    /// The files (or memory buffers) associated to the buckets.
+    files: Vec<Bucket>,
     /// The number of keys in each bucket.
     buf_sizes: Vec<usize>,
     /// The number of keys in each chunk.
     chunk_sizes: Vec<usize>,
+    /// Whether the same signature can be associated with different values.
+    allow_duplicates: bool,
     _marker: PhantomData<T>,
 }
 
 impl<T> ChunkStore<T> {
+    /// Set whether the same signature can be associated with different
+    /// values (default: false).
+    ///
+    /// If true, for each signature associated with different values only one
+    /// pair (which one is unspecified) is returned by the [iterator](ChunkStore::iter);
+    /// otherwise, a fake pair signalling a duplicate is returned, as explained
+    /// in the documentation of [`ChunkIterator`].
+    pub fn allow_duplicates(&mut self, allow_duplicates: bool) {
+        self.allow_duplicates = allow_duplicates;
+    }
+
     /// Return the chunk sizes.
     pub fn chunk_sizes(&self) -> &Vec<usize> {
         &self.chunk_sizes
@@ -174,6 +276,10 @@ impl<T> ChunkStore<T> {
 
     /// Return an iterator on chunks.
     ///
+    /// The iterator returns pairs given by the index of a chunk and the
+    /// signature/value pairs in the chunk, sorted by signature; chunks are
+    /// returned in order. See [`ChunkIterator`] for the handling of duplicates.
+    ///
     /// This method can be called multiple times.
     pub fn iter(&mut self) -> Result<ChunkIterator<'_, T>> {
         Ok(ChunkIterator {
@@ -193,13 +299,18 @@ Enumerate chunks in a [`ChunkStore`].
 A [`ChunkIterator`] handles the mapping between buckets and chunks. If a chunk is made
 by one or more buckets, it will aggregate them as necessary; if a bucket contains
 several chunks, it will split the bucket into chunks. In all cases, each chunk
-is sorted and tested for duplicates: if duplicates are detected, a fake pair
-containing `usize::MAX` and an empty chunk will be returned.
+is sorted and tested for duplicates: identical signature/value pairs are
+silently deduplicated, but if the same signature is associated with different
+values a fake pair containing `usize::MAX` and an empty chunk will be returned.
 
 Note that a [`ChunkIterator`] returns an owned variant of [`Cow`]. The reason for
 using [`Cow`] is easier interoperability with in-memory construction methods, which
 usually return borrowed variants.
 
+# Panics
+
+The iterator panics if an I/O error occurs while reading a bucket.
+
 */
 
 #[derive(Debug)]
@@ -214,7 +325,9 @@ pub struct ChunkIterator<'a, T> {
     _marker: PhantomData<T>,
 }
 
-impl<'a, T: ToOwned + ZeroCopy + Copy + Clone + Send + Sync> Iterator for ChunkIterator<'a, T> {
+impl<'a, T: ToOwned + ZeroCopy + Copy + Clone + PartialEq + Send + Sync> Iterator
+    for ChunkIterator<'a, T>
+{
     type Item = (usize, Cow<'a, [([u64; 2], T)]>);
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -242,7 +355,9 @@ impl<'a, T: ToOwned + ZeroCopy + Copy + Clone + Send + Sync> Iterator for ChunkI
                 assert!(pre.is_empty());
                 assert!(post.is_empty());
                 for i in self.next_file..self.next_file + to_aggr {
-                    let mut reader = &store.files[i];
+                    let reader = &mut store.files[i];
+                    // Necessary if the store has already been iterated upon
+                    reader.seek(SeekFrom::Start(0)).unwrap();
                     let bytes = store.buf_sizes[i] * core::mem::size_of::<([u64; 2], T)>();
                     reader.read_exact(&mut buf[..bytes]).unwrap();
                     buf = &mut buf[bytes..];
@@ -250,9 +365,12 @@ impl<'a, T: ToOwned + ZeroCopy + Copy + Clone + Send + Sync> Iterator for ChunkI
             }
 
             // Test for duplicates
+            #[cfg(feature = "rayon")]
             chunk.par_sort_unstable_by_key(|x| x.0);
+            #[cfg(not(feature = "rayon"))]
+            chunk.sort_unstable_by_key(|x| x.0);
 
-            if chunk.par_windows(2).any(|w| w[0].0 == w[1].0) {
+            if !dedup(&mut chunk, store.allow_duplicates) {
                 return Some((usize::MAX, Cow::Owned(vec![])));
             }
 
@@ -313,9 +431,12 @@ impl<'a, T: ToOwned + ZeroCopy + Copy + Clone + Send + Sync> Iterator for ChunkI
             }
 
             let mut chunk = self.chunks.pop_front().unwrap();
+            #[cfg(feature = "rayon")]
             chunk.par_sort_unstable_by_key(|x| x.0);
+            #[cfg(not(feature = "rayon"))]
+            chunk.sort_unstable_by_key(|x| x.0);
 
-            if chunk.par_windows(2).any(|w| w[0].0 == w[1].0) {
+            if !dedup(&mut chunk, store.allow_duplicates) {
                 return Some((usize::MAX, Cow::Owned(vec![])));
             }
 
@@ -326,11 +447,34 @@ impl<'a, T: ToOwned + ZeroCopy + Copy + Clone + Send + Sync> Iterator for ChunkI
     }
 }
 
-impl<'a, T: ToOwned + ZeroCopy + Copy + Clone + Send + Sync> ExactSizeIterator
+impl<'a, T: ToOwned + ZeroCopy + Copy + Clone + PartialEq + Send + Sync> ExactSizeIterator
     for ChunkIterator<'a, T>
 {
 }
 
+/// Remove identical signature/value pairs from a chunk sorted by signature,
+/// returning false if the same signature is associated with different values,
+/// unless `allow_duplicates` is true, in which case only one pair for each
+/// signature is kept.
+fn dedup<T: PartialEq + Send + Sync>(
+    chunk: &mut Vec<([u64; 2], T)>,
+    allow_duplicates: bool,
+) -> bool {
+    if !allow_duplicates {
+        let conflict = |w: &[([u64; 2], T)]| w[0].0 == w[1].0 && w[0].1 != w[1].1;
+        #[cfg(feature = "rayon")]
+        let conflicting = chunk.par_windows(2).any(conflict);
+        #[cfg(not(feature = "rayon"))]
+        let conflicting = chunk.windows(2).any(conflict);
+        if conflicting {
+            return false;
+        }
+    }
+    // All pairs with the same signature are now identical, or we do not care
+    chunk.dedup_by(|a, b| a.0 == b.0);
+    true
+}
+
 fn write_binary<T: ZeroCopy>(
     writer: &mut impl Write,
     tuples: &[([u64; 2], T)],
@@ -344,16 +488,30 @@ fn write_binary<T: ZeroCopy>(
 impl<T: ZeroCopy> SigStore<T> {
     /// Create a new store with 2<sup>`buckets_high_bits`</sup> buffers, keeping
     /// counts for chunks defined by at most `max_chunk_high_bits` high bits.
-    pub fn new(buckets_high_bits: u32, max_chunk_high_bits: u32) -> Result<Self> {
-        let temp_dir = tempfile::TempDir::new()?;
+    ///
+    /// If `offline` is true, the buffers are [temporary files](self#temporary-files),
+    /// so the store uses little memory, but 2<sup>`buckets_high_bits`</sup>
+    /// file descriptors; otherwise, the buffers are kept in memory.
+    ///
+    /// Returns an error if the temporary files cannot be created.
+    pub fn new(buckets_high_bits: u32, max_chunk_high_bits: u32, offline: bool) -> Result<Self> {
         let mut writers = VecDeque::new();
-        for i in 0..1 << buckets_high_bits {
-            let file = File::options()
-                .read(true)
-                .write(true)
-                .create(true)
-                .open(temp_dir.path().join(format!("{}.tmp", i)))?;
-            writers.push_back(BufWriter::new(file));
+        if offline {
+            // The directory is removed when temp_dir is dropped, leaving
+            // the files without a name
+            let temp_dir = tempfile::TempDir::new()?;
+            for i in 0..1 << buckets_high_bits {
+                let file = File::options()
+                    .read(true)
+                    .write(true)
+                    .create(true)
+                    .open(temp_dir.path().join(format!("{}.tmp", i)))?;
+                writers.push_back(BufWriter::new(Bucket::File(file)));
+            }
+        } else {
+            for _ in 0..1 << buckets_high_bits {
+                writers.push_back(BufWriter::new(Bucket::Memory(Cursor::new(vec![]))));
+            }
         }
         Ok(Self {
             len: 0,
@@ -369,6 +527,8 @@ impl<T: ZeroCopy> SigStore<T> {
     }
 
     /// Adds a signature/value pair to this store.
+    ///
+    /// Returns an error if the pair cannot be written to its bucket.
     pub fn push(&mut self, value: &([u64; 2], T)) -> std::io::Result<()> {
         self.len += 1;
         // high_bits can be 0
@@ -396,11 +556,12 @@ impl<T: ZeroCopy> SigStore<T> {
         self.len
     }
 
+    /// Return true if no signature/value pairs have been added to the store.
     pub fn is_empty(&self) -> bool {
         self.len == 0
     }
 
-    /// Flush the buffers and return a pair given by [`ChunkStore`] whose chunks are defined by
+    /// Flush the buffers and return a [`ChunkStore`] whose chunks are defined by
     /// the `chunk_high_bits` high bits of the signatures.
     ///
     /// It must hold that
@@ -431,6 +592,7 @@ impl<T: ZeroCopy> SigStore<T> {
             files,
             buf_sizes: self.bucket_sizes,
             chunk_sizes,
+            allow_duplicates: false,
             _marker: PhantomData,
         })
     }
@@ -446,7 +608,8 @@ fn test_sig_sorter() {
                 if chunk_high_bits > max_chunk_bits {
                     continue;
                 }
-                let mut sig_sorter = SigStore::new(buckets_high_bits, max_chunk_bits).unwrap();
+                let mut sig_sorter =
+                    SigStore::new(buckets_high_bits, max_chunk_bits, true).unwrap();
                 let mut rand = SmallRng::seed_from_u64(0);
 
                 for _ in (0..10000).rev() {
@@ -456,8 +619,7 @@ fn test_sig_sorter() {
                 }
                 let mut chunk_store = sig_sorter.into_chunk_store(chunk_high_bits).unwrap();
                 let mut count = 0;
-                let mut iter = chunk_store.iter().unwrap();
-                while let Some(chunk) = iter.next() {
+                for chunk in chunk_store.iter().unwrap() {
                     count += 1;
                     for w in chunk.1.windows(2) {
                         assert!(
@@ -475,7 +637,7 @@ fn test_sig_sorter() {
 #[test]
 fn test_u8() {
     use rand::prelude::*;
-    let mut sig_sorter = SigStore::new(2, 2).unwrap();
+    let mut sig_sorter = SigStore::new(2, 2, true).unwrap();
     let mut rand = SmallRng::seed_from_u64(0);
     for _ in (0..1000).rev() {
         sig_sorter
@@ -484,8 +646,7 @@ fn test_u8() {
     }
     let mut chunk_store = sig_sorter.into_chunk_store(2).unwrap();
     let mut count = 0;
-    let mut iter = chunk_store.iter().unwrap();
-    while let Some(chunk) = iter.next() {
+    for chunk in chunk_store.iter().unwrap() {
         count += 1;
         for w in chunk.1.windows(2) {
             assert!(w[0].0[0] < w[1].0[0] || w[0].0[0] == w[1].0[0] && w[0].0[1] < w[1].0[1]);
@@ -497,13 +658,13 @@ fn test_u8() {
 #[test]
 
 fn test_dup() {
-    let mut sig_sorter = SigStore::new(0, 0).unwrap();
+    let mut sig_sorter = SigStore::new(0, 0, true).unwrap();
     sig_sorter.push(&([0, 0], 0)).unwrap();
+    sig_sorter.push(&([0, 0], 1)).unwrap();
     sig_sorter.push(&([0, 0], 0)).unwrap();
     let mut dup = false;
     let mut chunk_store = sig_sorter.into_chunk_store(0).unwrap();
-    let mut iter = chunk_store.iter().unwrap();
-    while let Some(chunk) = iter.next() {
+    for chunk in chunk_store.iter().unwrap() {
         if chunk.0 == usize::MAX {
             dup = true;
             break;
@@ -511,3 +672,49 @@ fn test_dup() {
     }
     assert!(dup);
 }
+
+#[test]
+fn test_identical_dup() {
+    for buckets_high_bits in [0, 2] {
+        let mut sig_sorter = SigStore::new(buckets_high_bits, 1, true).unwrap();
+        sig_sorter.push(&([0, 0], 0)).unwrap();
+        sig_sorter.push(&([1, 0], 1)).unwrap();
+        sig_sorter.push(&([0, 0], 0)).unwrap();
+        sig_sorter.push(&([0, 0], 0)).unwrap();
+        let mut chunk_store = sig_sorter.into_chunk_store(1).unwrap();
+        let mut pairs = vec![];
+        for chunk in chunk_store.iter().unwrap() {
+            assert_ne!(chunk.0, usize::MAX);
+            pairs.extend(chunk.1.iter().copied());
+        }
+        assert_eq!(pairs, vec![([0, 0], 0), ([1, 0], 1)]);
+    }
+}
+
+#[test]
+fn test_online() {
+    use rand::prelude::*;
+    for buckets_high_bits in [0, 2, 8] {
+        for chunk_high_bits in [0, 2, 8] {
+            let mut offline = SigStore::new(buckets_high_bits, 8, true).unwrap();
+            let mut online = SigStore::new(buckets_high_bits, 8, false).unwrap();
+            let mut rand = SmallRng::seed_from_u64(0);
+            for _ in 0..10000 {
+                let pair = ([rand.next_u64(), rand.next_u64()], rand.next_u64());
+                offline.push(&pair).unwrap();
+                online.push(&pair).unwrap();
+            }
+            assert_eq!(online.len(), 10000);
+            let mut offline = offline.into_chunk_store(chunk_high_bits).unwrap();
+            let mut online = online.into_chunk_store(chunk_high_bits).unwrap();
+            assert_eq!(offline.chunk_sizes(), online.chunk_sizes());
+            // Iterate twice to check rewinding
+            for _ in 0..2 {
+                let offline = offline.iter().unwrap().collect::<Vec<_>>();
+                let online = online.iter().unwrap().collect::<Vec<_>>();
+                assert_eq!(offline, online);
+                assert_eq!(online.len(), 1 << chunk_high_bits);
+            }
+        }
+    }
+}
@@ -27,7 +27,7 @@ fn test_bit_field_vec() {
 }
 
 fn test_bit_field_vec_param<W: Word + CastableInto<u64> + CastableFrom<u64>>() {
-    for bit_width in 0..W::BITS {
+    for bit_width in 0..=W::BITS {
         let n = 100;
         let u = W::ONE << bit_width.saturating_sub(1).min(60);
         let mut rng = SmallRng::seed_from_u64(0);
@@ -65,6 +65,18 @@ fn test_bit_field_vec_param<W: Word + CastableInto<u64> + CastableFrom<u64>>() {
                 }
             }
 
+            for from in 0..=cp.len() {
+                let mut iter = cp.into_unchecked_iter_from(from);
+                for i in (0..from).rev() {
+                    unsafe {
+                        assert_eq!(iter.prev_unchecked(), values[i]);
+                    }
+                }
+                unsafe {
+                    assert_eq!(iter.next_unchecked(), values[0]);
+                }
+            }
+
             for from in 0..cp.len() {
                 let mut iter = cp.into_rev_unchecked_iter_from(from);
                 for v in values[..from].iter().rev() {
@@ -193,3 +205,1216 @@ fn test_resize() {
     }
     assert_eq!(c.len(), 50);
 }
+
+#[test]
+fn test_push_pop_word_boundaries() {
+    use sux::traits::bit_field_slice::BitFieldSlice;
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    // Widths for which values cross word boundaries, plus the extremes
+    for bit_width in [0, 1, 7, 13, 31, 33, 63, 64] {
+        let mask = if bit_width == 0 {
+            0
+        } else {
+            u64::MAX >> (64 - bit_width)
+        };
+        let values = (0..300)
+            .map(|_| rng.gen::<u64>() & mask)
+            .collect::<Vec<_>>();
+        let mut c = BitFieldVec::<u64>::new(bit_width, 0);
+        for (i, &value) in values.iter().enumerate() {
+            c.push(value);
+            assert_eq!(c.len(), i + 1);
+            assert_eq!(
+                c.clone().into_raw_parts().0.len(),
+                Ord::max(1, ((i + 1) * bit_width + 63) / 64)
+            );
+        }
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(c.get(i), value);
+        }
+        for &value in values.iter().rev() {
+            assert_eq!(c.pop(), Some(value));
+        }
+        assert_eq!(c.pop(), None);
+        assert!(c.is_empty());
+
+        // Values overwrite stale bits after popping
+        c.resize(10, mask);
+        c.resize(5, 0);
+        c.resize(10, 0);
+        for i in 0..5 {
+            assert_eq!(c.get(i), mask);
+        }
+        for i in 5..10 {
+            assert_eq!(c.get(i), 0);
+        }
+        c.pop();
+        c.push(0);
+        assert_eq!(c.get(9), 0);
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_push_too_large() {
+    let mut c = BitFieldVec::<u64>::new(13, 0);
+    c.push(1 << 13);
+}
+
+#[test]
+fn test_rows() {
+    use sux::traits::bit_field_slice::BitFieldSliceMut;
+
+    for bit_width in [0, 1, 3, 7, 12, 33, 63] {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mask = if bit_width == 0 {
+            0
+        } else {
+            usize::MAX >> (64 - bit_width)
+        };
+        let mut c = BitFieldVec::<usize>::new(bit_width, 120);
+        for i in 0..c.len() {
+            c.set(i, rng.gen::<usize>() & mask);
+        }
+        for ncols in [1, 2, 3, 5, 6, 8, 40, 120] {
+            let rows = c.rows(ncols).unwrap();
+            assert_eq!(rows.size_hint().0, 120 / ncols);
+            let flat = c.rows(ncols).unwrap().flatten().collect::<Vec<_>>();
+            assert_eq!(flat, c.into_iter().collect::<Vec<_>>());
+            for (r, row) in c.rows(ncols).unwrap().enumerate() {
+                for (j, v) in row.enumerate() {
+                    assert_eq!(v, c.get(r * ncols + j));
+                }
+            }
+        }
+        assert!(c.rows(0).is_err());
+        assert!(c.rows(7 * 3).is_err());
+        assert!(c.rows(121).is_err());
+    }
+
+    let c = BitFieldVec::<usize>::new(5, 0);
+    assert_eq!(c.rows(3).unwrap().count(), 0);
+}
+
+#[test]
+fn test_from_full_width_vec() {
+    test_from_full_width_vec_param::<u8>();
+    test_from_full_width_vec_param::<u16>();
+    test_from_full_width_vec_param::<u32>();
+    test_from_full_width_vec_param::<u64>();
+    test_from_full_width_vec_param::<u128>();
+    test_from_full_width_vec_param::<usize>();
+}
+
+fn test_from_full_width_vec_param<W: Word + CastableFrom<u64>>() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let data = (0..100)
+        .map(|_| rng.gen::<u64>().cast())
+        .collect::<Vec<W>>();
+    let mut c = BitFieldVec::<W>::from_full_width_vec(data.clone());
+    assert_eq!(c.bit_width(), W::BITS);
+    assert_eq!(c.len(), data.len());
+    for (i, v) in data.iter().enumerate() {
+        assert_eq!(c.get(i), *v);
+    }
+    assert_eq!(c.into_iter().collect::<Vec<_>>(), data);
+    let mut iter = c.into_rev_unchecked_iter();
+    for v in data.iter().rev() {
+        assert_eq!(unsafe { iter.next_unchecked() }, *v);
+    }
+
+    for i in 0..c.len() {
+        c.set(i, W::MAX);
+    }
+    for i in 0..c.len() {
+        assert_eq!(c.get(i), W::MAX);
+    }
+    assert!(c.into_iter().all(|v| v == W::MAX));
+
+    for (i, v) in data.iter().enumerate() {
+        c.set(i, *v);
+    }
+    assert_eq!(c.into_raw_parts().0, data);
+
+    let c = BitFieldVec::<W>::from_full_width_vec(vec![]);
+    assert!(c.is_empty());
+    assert_eq!(c.into_iter().count(), 0);
+}
+
+#[test]
+fn test_bytes() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 5, 8, 13, 31, 64] {
+        let len = 100;
+        let mut c = BitFieldVec::<usize>::new(bit_width, len);
+        for i in 0..len {
+            let value = rng.gen::<usize>().checked_shr(64 - bit_width as u32);
+            c.set(i, value.unwrap_or(0));
+        }
+        let bytes = c.to_bytes();
+        assert_eq!(bytes.len(), 16 + (len * bit_width + 7) / 8);
+        let (d, bit_len) = BitFieldVec::<usize>::from_bytes(&bytes).unwrap();
+        assert_eq!(bit_len, len * bit_width);
+        assert_eq!(d.bit_width(), bit_width);
+        if bit_width != 0 {
+            assert_eq!(d.len(), len);
+            assert_eq!(
+                d.into_iter().collect::<Vec<_>>(),
+                c.into_iter().collect::<Vec<_>>()
+            );
+            assert_eq!(d.to_bytes(), bytes);
+        }
+    }
+}
+
+#[test]
+fn test_truncate_bits() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [4, 5, 13, 31, 64] {
+        let len = 100;
+        let bit_len = len * bit_width - 3;
+        let mut c = BitFieldVec::<u64>::new(bit_width, len);
+        for i in 0..len {
+            c.set(i, rng.gen::<u64>() & (u64::MAX >> (64 - bit_width)));
+        }
+        // The last value has three high bits set to zero
+        c.set(len - 1, c.get(len - 1) >> 3);
+
+        let bytes = c.truncate_bits(bit_len).unwrap();
+        assert_eq!(bytes.len(), 16 + (bit_len + 7) / 8);
+        let (d, stored_bit_len) = BitFieldVec::<u64>::from_bytes(&bytes).unwrap();
+        assert_eq!(stored_bit_len, bit_len);
+        assert_eq!(d.len(), len);
+        assert_eq!(
+            d.into_iter().collect::<Vec<_>>(),
+            c.into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(d.truncate_bits(bit_len).unwrap(), bytes);
+
+        // From the raw bit stream
+        let (words, _, _) = c.clone().into_raw_parts();
+        let e = BitFieldVec::<u64>::from_bit_len(words, bit_width, bit_len).unwrap();
+        assert_eq!(e.len(), len);
+        assert_eq!(
+            e.into_iter().collect::<Vec<_>>(),
+            c.into_iter().collect::<Vec<_>>()
+        );
+
+        // Truncated bits must be zero
+        c.set(len - 1, 1 << (bit_width - 1));
+        assert!(c.truncate_bits(bit_len).is_err());
+        // The bit length must end within the last value
+        assert!(c.truncate_bits(bit_len - bit_width).is_err());
+        assert!(c.truncate_bits(len * bit_width + 1).is_err());
+    }
+
+    // A partial value is zero-extended, and bits beyond the bit length are ignored
+    let c = BitFieldVec::<u64>::from_bit_len(vec![u64::MAX], 5, 12).unwrap();
+    assert_eq!(c.len(), 3);
+    assert_eq!(c.into_iter().collect::<Vec<_>>(), vec![31, 31, 3]);
+    assert!(BitFieldVec::<u64>::from_bit_len(vec![0], 5, 65).is_err());
+    assert!(BitFieldVec::<u64>::from_bytes(&[0; 10]).is_err());
+    // A huge bit length in the header must not overflow
+    let mut bytes = 5_u64.to_le_bytes().to_vec();
+    bytes.extend(u64::MAX.to_le_bytes());
+    bytes.extend([0xFF; 8]);
+    assert!(BitFieldVec::<u64>::from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn test_builder() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 7, 13, 33, 64] {
+        let values = (0..1000)
+            .map(|_| rng.gen::<u64>().checked_shr(64 - bit_width).unwrap_or(0))
+            .collect::<Vec<_>>();
+        let actual_width = values.iter().map(|v| 64 - v.leading_zeros()).max().unwrap() as usize;
+
+        let mut infer = BitFieldVecBuilder::<u64>::new();
+        infer.reserve(values.len());
+        let mut fixed = BitFieldVecBuilder::<u64>::with_width(actual_width);
+        fixed.reserve(values.len());
+        for &value in &values {
+            infer.push(value).unwrap();
+            fixed.push(value).unwrap();
+        }
+        assert_eq!(infer.len(), values.len());
+        assert_eq!(fixed.len(), values.len());
+
+        let infer = infer.build();
+        let fixed = fixed.build();
+        assert_eq!(infer.bit_width(), actual_width);
+        assert_eq!(fixed.bit_width(), actual_width);
+        assert_eq!(infer.into_iter().collect::<Vec<_>>(), values);
+        assert_eq!(fixed.into_iter().collect::<Vec<_>>(), values);
+        assert_eq!(infer.to_bytes(), fixed.to_bytes());
+    }
+
+    let mut fixed = BitFieldVecBuilder::<u64>::with_width(3);
+    fixed.push(7).unwrap();
+    assert!(fixed.push(8).is_err());
+    assert_eq!(fixed.build().len(), 1);
+
+    let empty = BitFieldVecBuilder::<u64>::new();
+    assert!(empty.is_empty());
+    assert!(empty.build().is_empty());
+}
+
+#[test]
+fn test_approx_count_distinct() {
+    for distinct in [1, 100, 10_000, 100_000] {
+        let len = 200_000;
+        let mut c = BitFieldVec::<usize>::new(40, len);
+        for i in 0..len {
+            // Scramble the distinct values, and repeat them
+            c.set(
+                i,
+                (i % distinct).wrapping_mul(0x9E3779B97F4A7C15) & ((1 << 40) - 1),
+            );
+        }
+
+        for precision in [10, 12, 14] {
+            let estimate = c.approx_count_distinct(precision) as f64;
+            let std_error = 1.04 / ((1 << precision) as f64).sqrt();
+            let error = (estimate - distinct as f64).abs() / distinct as f64;
+            assert!(
+                error <= 3.0 * std_error,
+                "distinct: {}, precision: {}, estimate: {}",
+                distinct,
+                precision,
+                estimate
+            );
+        }
+    }
+    assert_eq!(BitFieldVec::<usize>::new(5, 0).approx_count_distinct(8), 0);
+}
+
+#[test]
+fn test_compact() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 5, 13, 32, 64] {
+        for density in [0.0, 0.01, 0.5, 0.99, 1.0] {
+            let len = 1000;
+            let values = (0..len)
+                .map(|_| rng.gen::<u64>().checked_shr(64 - bit_width).unwrap_or(0))
+                .collect::<Vec<_>>();
+            let mut c = BitFieldVec::<u64>::new(bit_width as usize, len);
+            for (i, &v) in values.iter().enumerate() {
+                c.set(i, v);
+            }
+            let keep = (0..len).map(|_| rng.gen_bool(density)).collect::<BitVec>();
+
+            c.compact(&keep);
+            let expected = values
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| keep.get(*i))
+                .map(|(_, v)| *v)
+                .collect::<Vec<_>>();
+            assert_eq!(c.len(), expected.len());
+            assert_eq!(c.into_iter().collect::<Vec<_>>(), expected);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_compact_wrong_len() {
+    let mut c = BitFieldVec::<u64>::new(5, 10);
+    c.compact(&BitVec::new(9));
+}
+
+#[test]
+fn test_partition() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 2, 3, 10, 100, 1000] {
+        for bit_width in [1, 5, 13, 64] {
+            let mask = u64::MAX >> (64 - bit_width);
+            let values = (0..len)
+                .map(|_| rng.gen::<u64>() & mask)
+                .collect::<Vec<_>>();
+            for threshold in [0, mask / 3, mask / 2, mask] {
+                let pred = |v: u64| v < threshold;
+                let mut expected = values.clone();
+                expected.sort();
+
+                let mut b = BitFieldVec::<u64>::new(bit_width, 0);
+                b.extend(values.iter().copied());
+                let count = b.partition(pred);
+                assert_eq!(count, values.iter().filter(|&&v| pred(v)).count());
+                let mut result = (&b).into_iter().collect::<Vec<_>>();
+                assert!(result[..count].iter().all(|&v| pred(v)));
+                assert!(result[count..].iter().all(|&v| !pred(v)));
+                result.sort();
+                assert_eq!(result, expected);
+
+                let mut b = BitFieldVec::<u64>::new(bit_width, 0);
+                b.extend(values.iter().copied());
+                let count = b.stable_partition(pred);
+                let (mut accepted, rejected): (Vec<_>, Vec<_>) =
+                    values.iter().partition(|&&v| pred(v));
+                assert_eq!(count, accepted.len());
+                accepted.extend(rejected);
+                assert_eq!((&b).into_iter().collect::<Vec<_>>(), accepted);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_checked() {
+    let mut b = BitFieldVec::<u16>::new(5, 10);
+    assert_eq!(b.checked_get(9), Some(0));
+    assert_eq!(b.checked_get(10), None);
+    assert_eq!(b.checked_get(usize::MAX), None);
+    b.checked_set(9, 31).unwrap();
+    assert_eq!(b.checked_get(9), Some(31));
+    assert!(b.checked_set(10, 1).is_err());
+    assert!(b.checked_set(0, 32).is_err());
+    assert_eq!(b.checked_get(0), Some(0));
+    assert!(b.checked_push(32).is_err());
+    assert_eq!(b.len(), 10);
+    b.checked_push(17).unwrap();
+    assert_eq!(b.checked_get(10), Some(17));
+}
+
+#[test]
+fn test_as_words() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 3, 7, 8, 13, 16] {
+        for len in [0, 1, 5, 100] {
+            let mut b = BitFieldVec::<u16>::new(bit_width, len);
+            let values = (0..len)
+                .map(|_| rng.gen::<u16>() & ((1u32 << bit_width) - 1) as u16)
+                .collect::<Vec<_>>();
+            for (i, &v) in values.iter().enumerate() {
+                b.set(i, v);
+            }
+            assert_eq!(b.word_len(), (len * bit_width + 15) / 16);
+            let words = b.as_words();
+            assert!(words.len() >= b.word_len());
+            // Decode following the documented packing
+            for (i, &v) in values.iter().enumerate() {
+                let mut value = 0;
+                for j in 0..bit_width {
+                    let pos = i * bit_width + j;
+                    value |= ((words[pos / 16] >> (pos % 16)) & 1) << j;
+                }
+                assert_eq!(value, v);
+            }
+        }
+    }
+
+    let mut b = BitFieldVec::<usize>::new(10, 100);
+    let word_len = b.word_len();
+    b.resize(7, 0);
+    assert_eq!(b.word_len(), 2);
+    assert_eq!(b.as_words().len(), word_len);
+}
+
+#[test]
+fn test_safe_bit_field_vec() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 7, 33, 64] {
+        let mask = u64::MAX.checked_shr(64 - bit_width as u32).unwrap_or(0);
+        let values = (0..100)
+            .map(|_| rng.gen::<u64>() & mask)
+            .collect::<Vec<_>>();
+        let mut safe = SafeBitFieldVec::<u64>::new(bit_width, 0).unwrap();
+        assert!(safe.is_empty());
+        for &value in &values {
+            safe.push(value).unwrap();
+        }
+        assert_eq!(safe.len(), values.len());
+        assert_eq!(safe.bit_width(), bit_width);
+        assert_eq!(safe.iter().collect::<Vec<_>>(), values);
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(safe.get(i), Some(value));
+        }
+        assert_eq!(safe.get(values.len()), None);
+        assert!(safe.set(values.len(), 0).is_err());
+        if bit_width < 64 {
+            assert!(safe.push(mask + 1).is_err());
+            assert!(safe.set(0, mask + 1).is_err());
+            assert_eq!(safe.get(0), Some(values[0]));
+        }
+        safe.set(99, mask).unwrap();
+        assert_eq!(safe.get(99), Some(mask));
+
+        let bits: BitFieldVec<u64> = safe.into();
+        assert_eq!(bits.get(99), mask);
+        let safe = SafeBitFieldVec::from(bits);
+        assert_eq!((&safe).into_iter().count(), values.len());
+    }
+
+    assert!(SafeBitFieldVec::<u64>::new(65, 0).is_err());
+    assert!(SafeBitFieldVec::<u8>::new(9, 10).is_err());
+    assert_eq!(SafeBitFieldVec::<u8>::new(8, 10).unwrap().len(), 10);
+}
+
+#[test]
+fn test_fetch_saturating_add() {
+    // Concurrent increments of 4-bit counters
+    let counters = AtomicBitFieldVec::<u64>::new(4, 100);
+    std::thread::scope(|s| {
+        for t in 0..8 {
+            let counters = &counters;
+            s.spawn(move || {
+                for round in 0..20 {
+                    for i in 0..100 {
+                        let delta = 1 + (t + round + i) as u64 % 3;
+                        let previous = counters.fetch_saturating_add(i, delta, Ordering::Relaxed);
+                        assert!(previous <= 15);
+                    }
+                }
+            });
+        }
+    });
+    for i in 0..100 {
+        assert_eq!(counters.get_atomic(i, Ordering::Relaxed), 15);
+    }
+
+    // Saturation does not affect adjacent values, also across word boundaries
+    for bit_width in [1, 5, 7, 13, 64] {
+        let mask = u64::MAX >> (64 - bit_width);
+        let v = AtomicBitFieldVec::<u64>::new(bit_width, 30);
+        for i in (0..30).step_by(2) {
+            v.set_atomic(i, mask - 1, Ordering::Relaxed);
+        }
+        for i in 0..30 {
+            let previous = v.fetch_saturating_add(i, 3, Ordering::Relaxed);
+            assert_eq!(previous, if i % 2 == 0 { mask - 1 } else { 0 });
+        }
+        for i in 0..30 {
+            let expected = if i % 2 == 0 { mask } else { 3.min(mask) };
+            assert_eq!(v.get_atomic(i, Ordering::Relaxed), expected);
+        }
+        assert_eq!(v.fetch_saturating_add(0, u64::MAX, Ordering::Relaxed), mask);
+        assert_eq!(v.get_atomic(0, Ordering::Relaxed), mask);
+        assert_eq!(v.get_atomic(1, Ordering::Relaxed), 3.min(mask));
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_fetch_saturating_add_out_of_bounds() {
+    let v = AtomicBitFieldVec::<u64>::new(4, 10);
+    v.fetch_saturating_add(10, 1, Ordering::Relaxed);
+}
+
+#[test]
+fn test_position_find_value() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 3, 7, 8, 13, 16] {
+        for len in [0, 1, 10, 1000] {
+            let max = 1_u32 << bit_width;
+            let values = (0..len)
+                .map(|_| rng.gen_range(0..max) as u16)
+                .collect::<Vec<_>>();
+            let mut b = BitFieldVec::<u16>::new(bit_width, len);
+            for (i, &v) in values.iter().enumerate() {
+                b.set(i, v);
+            }
+            for x in 0..Ord::min(max, 300) as u16 {
+                assert_eq!(
+                    b.find_value(x),
+                    values.iter().position(|&v| v == x),
+                    "{} {}",
+                    bit_width,
+                    x
+                );
+                assert_eq!(b.position(|v| v > x), values.iter().position(|&v| v > x));
+            }
+            if bit_width < 16 {
+                // Values that do not fit are never found
+                assert_eq!(b.find_value(max as u16), None);
+                assert_eq!(b.find_value(u16::MAX), None);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_iter_nth() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 5, 8, 13, 64] {
+        for len in [0, 1, 10, 100] {
+            let mut b = BitFieldVec::<u64>::new(bit_width, len);
+            let mask = if bit_width == 0 {
+                0
+            } else {
+                u64::MAX >> (64 - bit_width)
+            };
+            for i in 0..len {
+                b.set(i, rng.gen::<u64>() & mask);
+            }
+            let values = b.to_vec();
+            for from in [0, len / 2, len] {
+                for n in [0, 1, 2, 7, len / 3, len, len + 1, usize::MAX] {
+                    let mut iter = b.into_iter_from(from);
+                    let mut naive = values[from..].iter().copied();
+                    assert_eq!(iter.nth(n), naive.nth(n));
+                    assert_eq!(iter.len(), naive.len());
+                    assert_eq!(iter.next(), naive.next());
+                    assert_eq!(iter.next_back(), naive.next_back());
+                    assert_eq!(iter.nth(1), naive.nth(1));
+                    assert_eq!(iter.len(), naive.len());
+                    assert_eq!(iter.collect::<Vec<_>>(), naive.collect::<Vec<_>>());
+                }
+            }
+
+            // Skipping from both ends
+            let mut iter = b.into_iter();
+            let mut naive = values.iter().copied();
+            while naive.len() != 0 {
+                assert_eq!(iter.next_back(), naive.next_back());
+                assert_eq!(iter.nth(2), naive.nth(2));
+                assert_eq!(iter.len(), naive.len());
+            }
+            assert_eq!(iter.next(), None);
+            assert_eq!(
+                b.into_iter().skip(3).step_by(4).collect::<Vec<_>>(),
+                values
+                    .iter()
+                    .copied()
+                    .skip(3)
+                    .step_by(4)
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_split_first_last() {
+    let b = BitFieldVec::<u32>::new(7, 0);
+    assert!(b.split_first().is_none());
+    assert!(b.split_last().is_none());
+
+    let mut b = BitFieldVec::<u32>::new(7, 0);
+    b.push(42);
+    let (first, rest) = b.split_first().unwrap();
+    assert_eq!(first, 42);
+    assert_eq!(rest.count(), 0);
+    let (last, rest) = b.split_last().unwrap();
+    assert_eq!(last, 42);
+    assert_eq!(rest.count(), 0);
+
+    let values = (0..100).map(|i| (i * 37) % 128).collect::<Vec<u32>>();
+    let mut b = BitFieldVec::<u32>::new(7, 0);
+    b.extend(values.iter().copied());
+    let (first, rest) = b.split_first().unwrap();
+    assert_eq!(first, values[0]);
+    assert_eq!(rest.len(), 99);
+    assert_eq!(rest.collect::<Vec<_>>(), values[1..]);
+    let (last, rest) = b.split_last().unwrap();
+    assert_eq!(last, values[99]);
+    assert_eq!(rest.len(), 99);
+    assert_eq!(rest.collect::<Vec<_>>(), values[..99]);
+}
+
+#[test]
+fn test_checksum() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [1, 3, 8, 13, 64] {
+        let mask = u64::MAX >> (64 - bit_width);
+        for len in [0, 1, 10, 100, 1000] {
+            let values = (0..len)
+                .map(|_| rng.gen::<u64>() & mask)
+                .collect::<Vec<_>>();
+            let mut a = BitFieldVec::<u64>::new(bit_width, 0);
+            a.extend(values.iter().copied());
+            let checksum = a.checksum();
+            assert!(a.verify_checksum(checksum));
+
+            // Same values, but with dirty padding and a longer backend
+            let mut b = BitFieldVec::<u64>::new(bit_width, 0);
+            b.extend(values.iter().copied());
+            b.extend((0..200).map(|_| mask));
+            b.resize(len, 0);
+            assert_eq!(b.checksum(), checksum);
+
+            // Different values
+            if len > 0 {
+                let i = rng.gen_range(0..len);
+                a.set(i, a.get(i) ^ 1);
+                assert!(!a.verify_checksum(checksum));
+            }
+        }
+    }
+    // The bit width is part of the checksum
+    assert_ne!(
+        BitFieldVec::<u64>::new(4, 2).checksum(),
+        BitFieldVec::<u64>::new(8, 1).checksum()
+    );
+}
+
+#[test]
+fn test_iter_order() {
+    test_iter_order_param::<u8>();
+    test_iter_order_param::<u16>();
+    test_iter_order_param::<u32>();
+    test_iter_order_param::<u64>();
+    test_iter_order_param::<u128>();
+    test_iter_order_param::<usize>();
+}
+
+fn test_iter_order_param<W: Word + CastableFrom<u64>>() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=W::BITS {
+        // Lengths around word boundaries, so that the last value
+        // ends in a partial word or exactly at the end of a word
+        let words = [0, 1, 2, 3, 7];
+        let lens = words
+            .iter()
+            .flat_map(|w| {
+                let bits = w * W::BITS;
+                let len = if bit_width == 0 {
+                    bits
+                } else {
+                    bits / bit_width
+                };
+                [len.saturating_sub(1), len, len + 1]
+            })
+            .chain([100]);
+        for len in lens {
+            let mask = if bit_width == 0 {
+                W::ZERO
+            } else {
+                W::MAX >> (W::BITS - bit_width)
+            };
+            let mut c = BitFieldVec::<W>::new(bit_width, len);
+            for i in 0..len {
+                let value: W = rng.gen::<u64>().cast();
+                c.set(i, value.rotate_left(bit_width as u32) & mask);
+            }
+            let expected = (0..len).map(|i| c.get(i)).collect::<Vec<_>>();
+
+            assert_eq!(c.into_iter().collect::<Vec<_>>(), expected);
+            for from in 0..=len {
+                assert_eq!(
+                    c.into_iter_from(from).collect::<Vec<_>>(),
+                    c.into_iter().skip(from).collect::<Vec<_>>()
+                );
+            }
+
+            let mut iter = c.into_unchecked_iter();
+            for &value in &expected {
+                assert_eq!(unsafe { iter.next_unchecked() }, value);
+            }
+            let mut iter = c.into_rev_unchecked_iter();
+            for &value in expected.iter().rev() {
+                assert_eq!(unsafe { iter.next_unchecked() }, value);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_from_values() {
+    use sux::traits::bit_field_slice::BitFieldSlice;
+
+    let c = BitFieldVec::<usize>::from_values(&[]);
+    assert_eq!(c.bit_width(), 0);
+    assert!(c.is_empty());
+    let c = BitFieldVec::<usize>::from_values(&[0, 0, 0]);
+    assert_eq!(c.bit_width(), 0);
+    assert_eq!(c.len(), 3);
+    assert_eq!(c.get(2), 0);
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    for max in [1_u64, 2, 1000, 1 << 40, u64::MAX] {
+        let values = (0..1000)
+            .map(|_| rng.gen_range(0..=max))
+            .collect::<Vec<_>>();
+        let width = 64 - values.iter().max().unwrap().leading_zeros() as usize;
+        let c = BitFieldVec::<u64>::from_values(&values);
+        assert_eq!(c.bit_width(), width);
+        assert_eq!(c.len(), values.len());
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(c.get(i), value);
+        }
+        let d: BitFieldVec<u64> = values.iter().copied().collect();
+        assert_eq!(d.bit_width(), width);
+        assert_eq!(d.into_iter().collect::<Vec<_>>(), values);
+
+        let mut e = BitFieldVec::<u64>::from_iter_with_width(64, values.iter().copied());
+        assert_eq!(e.bit_width(), 64);
+        Extend::extend(&mut e, values.iter().copied());
+        assert_eq!(e.len(), 2 * values.len());
+        for (i, &value) in values.iter().chain(values.iter()).enumerate() {
+            assert_eq!(e.get(i), value);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_extend_too_large() {
+    let mut c = BitFieldVec::<usize>::from_iter_with_width(4, [1, 2, 3]);
+    Extend::extend(&mut c, [15, 16]);
+}
+
+#[test]
+fn test_copy_to_slice() {
+    use sux::traits::bit_field_slice::BitFieldSlice;
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 3, 7, 8, 13, 32, 33, 61, 64] {
+        let mask = if bit_width == 0 {
+            0
+        } else {
+            u64::MAX >> (64 - bit_width)
+        };
+        let n = 500;
+        let c = BitFieldVec::<u64>::from_iter_with_width(
+            bit_width,
+            (0..n).map(|_| rng.gen::<u64>() & mask),
+        );
+        for _ in 0..100 {
+            let from = rng.gen_range(0..=n);
+            let to = rng.gen_range(from..=n);
+            let expected = (from..to).map(|i| c.get(i)).collect::<Vec<_>>();
+            assert_eq!(c.get_range(from..to), expected);
+            let mut dst = vec![0; to - from];
+            c.copy_to_slice(from, &mut dst);
+            assert_eq!(dst, expected);
+        }
+        assert_eq!(c.get_range(0..n).len(), n);
+        assert!(c.get_range(n..n).is_empty());
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_copy_to_slice_out_of_bounds() {
+    let c = BitFieldVec::<usize>::new(5, 10);
+    let mut dst = [0; 4];
+    c.copy_to_slice(7, &mut dst);
+}
+
+#[test]
+#[should_panic]
+fn test_get_range_out_of_bounds() {
+    let c = BitFieldVec::<usize>::new(5, 10);
+    c.get_range(0..11);
+}
+
+#[test]
+fn test_fetch_update() {
+    // Concurrent wrapping increments of 4-bit counters at random indices
+    let n = 100;
+    let counters = AtomicBitFieldVec::<u64>::new(4, n);
+    let increments = std::thread::scope(|s| {
+        (0..8)
+            .map(|t| {
+                let counters = &counters;
+                s.spawn(move || {
+                    let mut rng = SmallRng::seed_from_u64(t);
+                    let mut increments = vec![0_u64; n];
+                    for _ in 0..10000 {
+                        let i = rng.gen_range(0..n);
+                        let previous = counters
+                            .fetch_update(i, Ordering::Relaxed, |x| Some((x + 1) % 16))
+                            .unwrap();
+                        assert!(previous < 16);
+                        increments[i] += 1;
+                    }
+                    increments
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .fold(vec![0_u64; n], |mut acc, increments| {
+                for (a, b) in acc.iter_mut().zip(increments) {
+                    *a += b;
+                }
+                acc
+            })
+    });
+    assert_eq!(increments.iter().sum::<u64>(), 80000);
+    for (i, &count) in increments.iter().enumerate() {
+        assert_eq!(counters.get_atomic(i, Ordering::Relaxed), count % 16);
+    }
+
+    // Failed updates return the current value and leave adjacent values alone
+    for bit_width in [0, 1, 2, 4, 8, 16, 32, 64] {
+        let mask = if bit_width == 0 {
+            0
+        } else {
+            u64::MAX >> (64 - bit_width)
+        };
+        let v = AtomicBitFieldVec::<u64>::new(bit_width, 130);
+        for i in (0..130).step_by(2) {
+            assert_eq!(v.fetch_update(i, Ordering::Relaxed, |_| Some(mask)), Ok(0));
+        }
+        for i in 0..130 {
+            let expected = if i % 2 == 0 { mask } else { 0 };
+            assert_eq!(
+                v.fetch_update(i, Ordering::Relaxed, |_| None),
+                Err(expected)
+            );
+            assert_eq!(v.get_atomic(i, Ordering::Relaxed), expected);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_fetch_update_too_large() {
+    let v = AtomicBitFieldVec::<u64>::new(4, 10);
+    let _ = v.fetch_update(0, Ordering::Relaxed, |_| Some(16));
+}
+
+#[test]
+#[should_panic]
+#[cfg(debug_assertions)]
+fn test_fetch_update_not_power_of_two() {
+    let v = AtomicBitFieldVec::<u64>::new(5, 10);
+    let _ = v.fetch_update(0, Ordering::Relaxed, Some);
+}
+
+#[test]
+fn test_apply_in_place() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 2, 3, 10, 100, 1000] {
+        for bit_width in [0, 1, 5, 13, 31, 32, 63, 64] {
+            let mask = if bit_width == 0 {
+                0
+            } else {
+                u64::MAX >> (64 - bit_width)
+            };
+            let mut a = BitFieldVec::<u64>::new(bit_width, 0);
+            a.extend((0..len).map(|_| rng.gen::<u64>() & mask));
+            let mut b = a.clone();
+
+            let f = |i: usize, v: u64| v.wrapping_add(i as u64 + 1) & mask;
+            for i in 0..len {
+                a.set(i, f(i, a.get(i)));
+            }
+            b.apply_in_place(f);
+            assert_eq!(
+                (&a).into_iter().collect::<Vec<_>>(),
+                (&b).into_iter().collect::<Vec<_>>()
+            );
+        }
+    }
+    // Words of different size
+    let mut b = BitFieldVec::<u16>::new(7, 0);
+    b.extend((0..100).map(|x| x as u16));
+    b.apply_in_place(|_, v| v + 10);
+    assert_eq!(
+        (&b).into_iter().collect::<Vec<_>>(),
+        (10..110).collect::<Vec<u16>>()
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_apply_in_place_too_large() {
+    let mut b = BitFieldVec::<usize>::new(5, 10);
+    b.apply_in_place(|i, _| i * 4);
+}
+
+#[test]
+fn test_copy_from_slice() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 500;
+    for bit_width in [0, 1, 3, 5, 7, 13, 31, 32, 33, 61, 63, 64] {
+        let mask = if bit_width == 0 {
+            0
+        } else {
+            u64::MAX >> (64 - bit_width)
+        };
+        let mut a = BitFieldVec::<u64>::new(bit_width, 0);
+        a.extend((0..len).map(|_| rng.gen::<u64>() & mask));
+        let mut b = a.clone();
+        // Ranges starting and ending near word boundaries
+        let mut starts = vec![0, 1, len / 2];
+        for word in 1..4 {
+            let index = word * 64 / bit_width.max(1);
+            starts.extend([index - 1, index, index + 1]);
+        }
+        for &from in &starts {
+            for count in [0, 1, 2, 3, 5, 64, 65, 100] {
+                if from + count > len {
+                    continue;
+                }
+                let src = (0..count)
+                    .map(|_| rng.gen::<u64>() & mask)
+                    .collect::<Vec<_>>();
+                for (i, &value) in src.iter().enumerate() {
+                    a.set(from + i, value);
+                }
+                b.copy_from_slice(from, &src);
+                // Compare the backends, so that bits outside the range are checked, too
+                assert_eq!(
+                    a.clone().into_raw_parts(),
+                    b.clone().into_raw_parts(),
+                    "bit width {}, range {}..{}",
+                    bit_width,
+                    from,
+                    from + count
+                );
+            }
+        }
+        // The whole vector
+        let src = (0..len)
+            .map(|_| rng.gen::<u64>() & mask)
+            .collect::<Vec<_>>();
+        b.copy_from_slice(0, &src);
+        assert_eq!(b.to_vec(), src);
+    }
+    // Words of different size
+    let mut b = BitFieldVec::<u16>::new(7, 100);
+    b.copy_from_slice(3, &(0..90).collect::<Vec<u16>>());
+    assert_eq!(b.get_range(0..3), vec![0; 3]);
+    assert_eq!(b.get_range(3..93), (0..90).collect::<Vec<u16>>());
+    assert_eq!(b.get_range(93..100), vec![0; 7]);
+}
+
+#[test]
+#[should_panic]
+fn test_copy_from_slice_out_of_bounds() {
+    let mut b = BitFieldVec::<usize>::new(5, 10);
+    b.copy_from_slice(8, &[0, 1, 2]);
+}
+
+#[test]
+fn test_copy_from_slice_too_large() {
+    let mut b = BitFieldVec::<usize>::new(5, 10);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        b.copy_from_slice(0, &[1, 2, 32]);
+    }));
+    assert!(result.is_err());
+    // Values are checked before modifying the vector
+    assert_eq!(b.to_vec(), vec![0; 10]);
+}
+
+#[test]
+fn test_from_indexed_parallel() {
+    for len in [0, 1, 10, 1000, 1_000_003] {
+        for bit_width in [0, 1, 8, 12, 13, 64] {
+            let mask = if bit_width == 0 {
+                0
+            } else {
+                u64::MAX >> (64 - bit_width)
+            };
+            let f = |i: usize| (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) & mask;
+            let b = BitFieldVec::<u64>::from_indexed_parallel(bit_width, len, f);
+            assert_eq!(b.len(), len);
+            assert_eq!(b.bit_width(), bit_width);
+            let mut a = BitFieldVec::<u64>::new(bit_width, len);
+            for i in 0..len {
+                a.set(i, f(i));
+            }
+            assert_eq!(a.into_raw_parts(), b.into_raw_parts());
+        }
+    }
+
+    let b = BitFieldVec::<u16>::from_indexed_parallel(7, 1_000_000, |i| (i % 128) as u16);
+    for i in 0..1_000_000 {
+        assert_eq!(b.get(i), (i % 128) as u16);
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_from_indexed_parallel_too_large() {
+    BitFieldVec::<usize>::from_indexed_parallel(5, 1000, |i| i);
+}
+
+#[test]
+fn test_iter_atomic() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 2, 3, 10, 100, 1000] {
+        for bit_width in [0, 1, 5, 8, 13, 32, 63, 64] {
+            let mask = if bit_width == 0 {
+                0
+            } else {
+                u64::MAX >> (64 - bit_width)
+            };
+            let v = AtomicBitFieldVec::<u64>::new(bit_width, len);
+            for i in 0..len {
+                v.set_atomic(i, rng.gen::<u64>() & mask, Ordering::Relaxed);
+            }
+            let mut iter = v.iter_atomic(Ordering::Relaxed);
+            assert_eq!(iter.len(), len);
+            for i in 0..len {
+                assert_eq!(iter.next(), Some(v.get_atomic(i, Ordering::Relaxed)));
+                assert_eq!(iter.len(), len - i - 1);
+            }
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.next(), None);
+        }
+    }
+
+    // Words of different size
+    let v = AtomicBitFieldVec::<u16>::new(7, 100);
+    for i in 0..100 {
+        v.set_atomic(i, i as u16, Ordering::Relaxed);
+    }
+    assert!(v.iter_atomic(Ordering::Relaxed).eq(0..100));
+}
+
+#[test]
+fn test_count_nonzero_sum() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 2, 3, 10, 100, 1000] {
+        for bit_width in [0, 1, 5, 8, 13, 32, 63, 64] {
+            let mask = if bit_width == 0 {
+                0
+            } else {
+                u64::MAX >> (64 - bit_width)
+            };
+            let values = (0..len)
+                .map(|_| {
+                    if rng.gen_bool(0.3) {
+                        0
+                    } else {
+                        rng.gen::<u64>() & mask
+                    }
+                })
+                .collect::<Vec<_>>();
+            let v = AtomicBitFieldVec::<u64>::new(bit_width, len);
+            for (i, &value) in values.iter().enumerate() {
+                v.set_atomic(i, value, Ordering::Relaxed);
+            }
+            assert_eq!(
+                v.count_nonzero(Ordering::Relaxed),
+                values.iter().filter(|&&x| x != 0).count()
+            );
+            assert_eq!(
+                v.sum(Ordering::Relaxed),
+                values.iter().map(|&x| x as u128).sum::<u128>()
+            );
+        }
+    }
+
+    // Concurrent increments of 8-bit counters while summing
+    let n = 1000;
+    let counters = AtomicBitFieldVec::<usize>::new(8, n);
+    std::thread::scope(|s| {
+        for t in 0..4 {
+            let counters = &counters;
+            s.spawn(move || {
+                let mut rng = SmallRng::seed_from_u64(t);
+                for _ in 0..10000 {
+                    let i = rng.gen_range(0..n);
+                    counters.fetch_saturating_add(i, 1, Ordering::Relaxed);
+                }
+            });
+        }
+        let mut last = 0;
+        for _ in 0..100 {
+            let sum = counters.sum(Ordering::Relaxed);
+            assert!(sum <= 40000);
+            assert!(counters.count_nonzero(Ordering::Relaxed) <= n);
+            // Counters only grow
+            assert!(sum >= last);
+            last = sum;
+        }
+    });
+    assert_eq!(counters.sum(Ordering::Relaxed), 40000);
+}
+
+#[test]
+fn test_double_ended() {
+    test_double_ended_param::<u8>();
+    test_double_ended_param::<u16>();
+    test_double_ended_param::<u64>();
+    test_double_ended_param::<usize>();
+}
+
+fn test_double_ended_param<W: Word + CastableFrom<u64>>() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=W::BITS {
+        for len in [0, 1, 2, 3, 10, 100] {
+            let mask = if bit_width == 0 {
+                W::ZERO
+            } else {
+                W::MAX >> (W::BITS - bit_width)
+            };
+            let mut c = BitFieldVec::<W>::new(bit_width, len);
+            for i in 0..len {
+                let value: W = rng.gen::<u64>().cast();
+                c.set(i, value & mask);
+            }
+            let expected = (0..len).map(|i| c.get(i)).collect::<Vec<_>>();
+
+            assert_eq!(
+                c.into_iter().rev().collect::<Vec<_>>(),
+                expected.iter().rev().copied().collect::<Vec<_>>()
+            );
+
+            // Interleave next and next_back randomly
+            for from in [0, len / 2, len] {
+                let mut iter = c.into_iter_from(from);
+                let mut reference = expected[from..].iter().copied();
+                loop {
+                    assert_eq!(iter.len(), reference.len());
+                    let (a, b) = if rng.gen_bool(0.5) {
+                        (iter.next(), reference.next())
+                    } else {
+                        (iter.next_back(), reference.next_back())
+                    };
+                    assert_eq!(a, b);
+                    if a.is_none() {
+                        break;
+                    }
+                }
+                assert_eq!(iter.next(), None);
+                assert_eq!(iter.next_back(), None);
+            }
+
+            // Move the unchecked iterator back and forth
+            let mut iter = c.into_unchecked_iter();
+            let mut index = 0;
+            for _ in 0..3 * len {
+                if index == len || (index > 0 && rng.gen_bool(0.5)) {
+                    index -= 1;
+                    assert_eq!(unsafe { iter.prev_unchecked() }, expected[index]);
+                } else {
+                    assert_eq!(unsafe { iter.next_unchecked() }, expected[index]);
+                    index += 1;
+                }
+            }
+            let mut iter = c.into_unchecked_iter_from(len);
+            for &value in expected.iter().rev() {
+                assert_eq!(unsafe { iter.prev_unchecked() }, value);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_to_vec_from_slice() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 10, 100] {
+        for bit_width in [1, 5, 13, 64] {
+            let mask = u64::MAX >> (64 - bit_width);
+            let values = (0..len)
+                .map(|_| rng.gen::<u64>() & mask)
+                .collect::<Vec<_>>();
+            let b = BitFieldVec::from(values.as_slice());
+            assert_eq!(b.to_vec(), values);
+            assert_eq!(b.to_vec(), (&b).into_iter().collect::<Vec<_>>());
+            let b: BitFieldVec<u64> = values.as_slice().try_into().unwrap();
+            assert_eq!(b.to_vec(), values);
+        }
+    }
+
+    // Empty and all-zero slices use bit width zero
+    let b = BitFieldVec::<u64>::from(&[][..]);
+    assert_eq!(b.bit_width(), 0);
+    assert!(b.to_vec().is_empty());
+    let b = BitFieldVec::<u16>::from(&[0, 0, 0][..]);
+    assert_eq!(b.bit_width(), 0);
+    assert_eq!(b.to_vec(), vec![0, 0, 0]);
+
+    let b = BitFieldVec::<u32>::from(&[1, 2, 255][..]);
+    assert_eq!(b.bit_width(), 8);
+    assert_eq!(b.to_vec(), vec![1, 2, 255]);
+}
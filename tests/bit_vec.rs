@@ -9,7 +9,7 @@ use core::sync::atomic::Ordering;
 use epserde::prelude::*;
 use rand::rngs::SmallRng;
 use rand::seq::SliceRandom;
-use rand::{RngCore, SeedableRng};
+use rand::{Rng, RngCore, SeedableRng};
 use sux::bits::bit_vec::BitVec;
 use sux::prelude::AtomicBitVec;
 
@@ -147,3 +147,206 @@ fn test_epserde() {
         assert_eq!(b.get(i), c.get(i));
     }
 }
+
+#[test]
+fn test_ones() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 63, 64, 65, 1000] {
+        let bits = (0..len).map(|_| rng.gen_bool(0.3)).collect::<BitVec>();
+        let expected = (0..len).filter(|&i| bits.get(i)).collect::<Vec<_>>();
+        assert_eq!(bits.ones().collect::<Vec<_>>(), expected);
+    }
+}
+
+#[test]
+fn test_ranges() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 300;
+    let bounds = [
+        0, 1, 2, 31, 63, 64, 65, 127, 128, 129, 200, 255, 256, 299, 300,
+    ];
+    for &start in &bounds {
+        for &end in bounds.iter().filter(|&&end| end >= start) {
+            let mut bits = BitVec::new(len);
+            let mut naive = vec![false; len];
+            for (i, b) in naive.iter_mut().enumerate() {
+                *b = rng.gen_bool(0.5);
+                bits.set(i, *b);
+            }
+
+            assert_eq!(
+                bits.count_ones_in(start..end),
+                naive[start..end].iter().filter(|&&b| b).count()
+            );
+
+            bits.flip_range(start..end);
+            naive[start..end].iter_mut().for_each(|b| *b = !*b);
+            assert_eq!((0..len).map(|i| bits.get(i)).collect::<Vec<_>>(), naive);
+
+            let value = rng.gen_bool(0.5);
+            bits.set_range(start..end, value);
+            naive[start..end].iter_mut().for_each(|b| *b = value);
+            assert_eq!((0..len).map(|i| bits.get(i)).collect::<Vec<_>>(), naive);
+
+            for i in [start, end.saturating_sub(1)] {
+                if i < len {
+                    bits.flip(i);
+                    naive[i] = !naive[i];
+                }
+            }
+            assert_eq!((0..len).map(|i| bits.get(i)).collect::<Vec<_>>(), naive);
+            assert_eq!(bits.count_ones_in(..), bits.count_ones());
+        }
+    }
+
+    // Inclusive and unbounded ranges
+    let mut bits = BitVec::new(1_000_000);
+    bits.set_range(1..=999_998, true);
+    assert_eq!(bits.count_ones_in(..), 999_998);
+    assert!(!bits.get(0) && !bits.get(999_999));
+    bits.flip_range(..500_000);
+    assert_eq!(bits.count_ones_in(..), 500_000);
+    assert_eq!(bits.count_ones_in(..=500_000), 2);
+}
+
+#[test]
+#[should_panic]
+fn test_range_out_of_bounds() {
+    BitVec::new(10).set_range(5..11, true);
+}
+
+#[test]
+fn test_set_algebra() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 63, 64, 65, 200, 1000] {
+        for density in [0.1, 0.5, 0.9] {
+            let [a, b, c] =
+                [0; 3].map(|_| (0..len).map(|_| rng.gen_bool(density)).collect::<BitVec>());
+            let naive = |x: &BitVec| (0..len).map(|i| x.get(i)).collect::<Vec<_>>();
+
+            for i in 0..len {
+                assert_eq!(a.and(&b).get(i), a.get(i) & b.get(i));
+                assert_eq!(a.or(&b).get(i), a.get(i) | b.get(i));
+                assert_eq!(a.xor(&b).get(i), a.get(i) ^ b.get(i));
+                assert_eq!(a.andnot(&b).get(i), a.get(i) & !b.get(i));
+            }
+
+            // Commutativity
+            assert_eq!(naive(&a.and(&b)), naive(&b.and(&a)));
+            assert_eq!(naive(&a.or(&b)), naive(&b.or(&a)));
+            assert_eq!(naive(&a.xor(&b)), naive(&b.xor(&a)));
+            // Associativity
+            assert_eq!(naive(&a.and(&b).and(&c)), naive(&a.and(&b.and(&c))));
+            assert_eq!(naive(&a.or(&b).or(&c)), naive(&a.or(&b.or(&c))));
+            assert_eq!(naive(&a.xor(&b).xor(&c)), naive(&a.xor(&b.xor(&c))));
+
+            // In-place variants
+            let mut d = a.or(&BitVec::new(len));
+            d.and_assign(&b);
+            assert_eq!(naive(&d), naive(&a.and(&b)));
+            let mut d = a.or(&BitVec::new(len));
+            d.or_assign(&b);
+            assert_eq!(naive(&d), naive(&a.or(&b)));
+            let mut d = a.or(&BitVec::new(len));
+            d.xor_assign(&b);
+            assert_eq!(naive(&d), naive(&a.xor(&b)));
+            let mut d = a.or(&BitVec::new(len));
+            d.andnot_assign(&b);
+            assert_eq!(naive(&d), naive(&a.andnot(&b)));
+
+            assert_eq!(a.intersection_count(&b), a.and(&b).count_ones());
+            assert!(a.and(&b).is_subset(&a));
+            assert!(a.is_subset(&a.or(&b)));
+            assert_eq!(a.is_subset(&b), a.andnot(&b).count_ones() == 0);
+            assert!(a.andnot(&b).is_disjoint(&b));
+            assert_eq!(a.is_disjoint(&b), a.intersection_count(&b) == 0);
+        }
+    }
+}
+
+#[test]
+fn test_set_algebra_padding() {
+    // Shrinking leaves stale bits beyond the length, which must be ignored
+    let mut a = BitVec::new(128);
+    a.set_range(.., true);
+    a.resize(70, false);
+    let mut b = BitVec::new(128);
+    b.set_range(64.., true);
+    b.resize(70, false);
+    let full = {
+        let mut full = BitVec::new(70);
+        full.set_range(.., true);
+        full
+    };
+
+    let not_a = full.andnot(&a);
+    assert_eq!(not_a.count_ones(), 0);
+    let x = a.xor(&b);
+    assert_eq!(x.count_ones(), 64);
+    assert!(b.is_subset(&a));
+    assert!(!a.is_disjoint(&b));
+    assert_eq!(a.intersection_count(&b), 6);
+    assert_eq!(full.xor(&b).count_ones(), 64);
+
+    let mut c = full.or(&BitVec::new(70));
+    c.xor_assign(&a);
+    assert_eq!(c.count_ones(), 0);
+    let mut c = full.or(&BitVec::new(70));
+    c.or_assign(&b);
+    assert_eq!(c.count_ones(), 70);
+}
+
+#[test]
+#[should_panic]
+fn test_set_algebra_different_len() {
+    BitVec::new(10).and(&BitVec::new(11));
+}
+
+#[test]
+fn test_from_bools() {
+    let b = vec![true, false, true].into_iter().collect::<BitVec>();
+    assert_eq!(b.len(), 3);
+    assert_eq!(b.count_ones(), 2);
+    assert!(b.get(0) && !b.get(1) && b.get(2));
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 3, 63, 64, 65, 128, 200] {
+        let bools = (0..len).map(|_| rng.gen_bool(0.5)).collect::<Vec<_>>();
+        let ones = bools.iter().filter(|&&b| b).count();
+        let from_iter = bools.iter().copied().collect::<BitVec>();
+        let from_slice = BitVec::from(bools.as_slice());
+        let from_vec = BitVec::from(bools.clone());
+        for b in [from_iter, from_slice, from_vec] {
+            assert_eq!(b.len(), len);
+            assert_eq!(b.count_ones(), ones);
+            assert_eq!(b.as_ref().len(), len.div_ceil(usize::BITS as usize));
+            assert_eq!((0..len).map(|i| b.get(i)).collect::<Vec<_>>(), bools);
+        }
+    }
+}
+
+#[test]
+fn test_rank_select() {
+    use sux::prelude::{BitCount, BitLength, Rank, Select, SelectZero};
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 63, 64, 65, 1000] {
+        for density in [0.0, 0.1, 0.5, 1.0] {
+            let bits = (0..len).map(|_| rng.gen_bool(density)).collect::<BitVec>();
+            let ones = (0..len).filter(|&i| bits.get(i)).collect::<Vec<_>>();
+            let zeros = (0..len).filter(|&i| !bits.get(i)).collect::<Vec<_>>();
+            assert_eq!(BitLength::len(&bits), len);
+            assert_eq!(bits.count(), ones.len());
+            assert_eq!(bits.count_ones(), ones.len());
+            for (rank, &pos) in ones.iter().enumerate() {
+                assert_eq!(bits.select(rank), Some(pos));
+                assert_eq!(bits.rank(pos), rank);
+            }
+            assert_eq!(bits.select(ones.len()), None);
+            for (rank, &pos) in zeros.iter().enumerate() {
+                assert_eq!(bits.select_zero(rank), Some(pos));
+            }
+            assert_eq!(bits.select_zero(zeros.len()), None);
+            assert_eq!(bits.rank(len), ones.len());
+        }
+    }
+}
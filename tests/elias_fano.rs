@@ -11,7 +11,6 @@ use epserde::prelude::*;
 use rand::rngs::SmallRng;
 use rand::Rng;
 use rand::SeedableRng;
-use std::sync::atomic::Ordering;
 use sux::prelude::BitFieldVec;
 use sux::prelude::*;
 
@@ -19,6 +18,7 @@ use sux::prelude::*;
 #[cfg(feature = "rayon")]
 fn test_elias_fano_concurrent() -> Result<()> {
     use rayon::prelude::*;
+    use std::sync::atomic::Ordering;
     use sux::dict::elias_fano::EliasFanoConcurrentBuilder;
     let mut rng = SmallRng::seed_from_u64(0);
     for (n, u) in [(10, 1000), (100, 1000), (100, 100), (1000, 100), (1000, 10)] {
@@ -209,3 +209,150 @@ fn test_epserde() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_binary_search() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(10, 1000), (100, 1000), (100, 100), (1000, 100), (1000, 10)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..u)).collect::<Vec<_>>();
+        values.sort();
+
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for value in values.iter() {
+            efb.push(*value)?;
+        }
+        let ef: EliasFano<QuantumIndex> = efb.build().convert_to()?;
+        // Containment does not need selection on zeroes
+        for v in 0..u + 10 {
+            assert_eq!(ef.contains(&v), values.contains(&v), "value {}", v);
+        }
+        let ef: EliasFano<QuantumZeroIndex<QuantumIndex>> = ef.convert_to()?;
+
+        for v in 0..u + 10 {
+            let index = values.partition_point(|&x| x < v);
+            let expected = if index < n && values[index] == v {
+                Ok(index)
+            } else {
+                Err(index)
+            };
+            assert_eq!(ef.binary_search(&v), expected, "value {}", v);
+            // The default implementation must agree
+            assert_eq!(
+                SortedIndexedDict::binary_search(&Sorted(&values), &v),
+                expected
+            );
+            assert_eq!(ef.index_of(&v), expected.ok());
+            assert_eq!(Sorted(&values).index_of(&v), expected.ok());
+            assert_eq!(ef.contains(&v), expected.is_ok());
+        }
+    }
+    // Empty dictionaries
+    assert_eq!(Sorted(&[]).index_of(&0), None);
+    let ef: EliasFano<QuantumIndex> = EliasFanoBuilder::new(0, 10).build().convert_to()?;
+    let ef: EliasFano<QuantumZeroIndex<QuantumIndex>> = ef.convert_to()?;
+    assert_eq!(ef.index_of(&0), None);
+    assert!(!ef.contains(&0));
+    Ok(())
+}
+
+/// A sorted slice using the default implementation of binary search.
+struct Sorted<'a>(&'a [usize]);
+
+impl<'a> IndexedDict for Sorted<'a> {
+    type Input = usize;
+    type Output = usize;
+
+    unsafe fn get_unchecked(&self, index: usize) -> usize {
+        *self.0.get_unchecked(index)
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<'a> SortedIndexedDict for Sorted<'a> {}
+
+#[test]
+fn test_from_unsorted() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for n in [0, 1, 10, 1000, 10000] {
+        let values = (0..n)
+            .map(|_| rng.gen_range(0..1000))
+            .collect::<Vec<usize>>();
+        let mut sorted = values.clone();
+        sorted.sort();
+        let mut distinct = sorted.clone();
+        distinct.dedup();
+        let has_dups = distinct.len() != sorted.len();
+
+        let (ef, dups) = EliasFanoBuilder::from_unsorted(values.iter().copied(), false)?;
+        assert_eq!(dups, has_dups);
+        assert_eq!(ef.len(), n);
+        for (i, &value) in sorted.iter().enumerate() {
+            assert_eq!(ef.get(i), value);
+        }
+
+        let (ef, dups) = EliasFanoBuilder::from_unsorted(values.iter().copied(), true)?;
+        assert_eq!(dups, has_dups);
+        assert_eq!(ef.len(), distinct.len());
+        for (i, &value) in distinct.iter().enumerate() {
+            assert_eq!(ef.get(i), value);
+        }
+    }
+
+    // Distinct values
+    let (ef, dups) = EliasFanoBuilder::from_unsorted([5_u32, 3, 100, 0], true)?;
+    assert!(!dups);
+    assert_eq!(
+        (0..4).map(|i| ef.get(i)).collect::<Vec<_>>(),
+        [0, 3, 5, 100]
+    );
+
+    // Negative values are rejected
+    assert!(EliasFanoBuilder::from_unsorted([5_i64, -3, 100], false).is_err());
+    // The universe would overflow
+    assert!(EliasFanoBuilder::from_unsorted([0, usize::MAX], false).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_get_ref() -> Result<()> {
+    use std::borrow::Borrow;
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut values = (0..1000)
+        .map(|_| rng.gen_range(0..10000))
+        .collect::<Vec<usize>>();
+    values.sort();
+
+    let mut efb = EliasFanoBuilder::new(values.len(), 10000);
+    for &value in &values {
+        efb.push(value)?;
+    }
+    let ef: EliasFano = efb.build();
+    for i in 0..values.len() {
+        assert_eq!(*ef.get_ref(i).borrow(), ef.get(i));
+    }
+
+    // Slices lend their values
+    let strings = values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    for i in 0..strings.len() {
+        let s: &String = strings.get_ref(i);
+        assert_eq!(*s, IndexedDict::get(&strings, i));
+        assert!(std::ptr::eq(s, &strings[i]));
+    }
+    let boxed = strings.clone().into_boxed_slice();
+    let slice = strings.as_slice();
+    for (i, s) in strings.iter().enumerate() {
+        assert_eq!(boxed.get_ref(i), s);
+        assert_eq!(slice.get_ref(i), s);
+    }
+    Ok(())
+}
+
+#[test]
+#[should_panic]
+fn test_get_ref_out_of_bounds() {
+    let v = vec![1, 2, 3];
+    v.get_ref(3);
+}
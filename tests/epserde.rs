@@ -11,6 +11,7 @@ use rand::rngs::SmallRng;
 use rand::Rng;
 use rand::SeedableRng;
 use sux::prelude::BitFieldVec;
+use sux::prelude::*;
 use sux::traits::bit_field_slice::BitFieldSlice;
 use sux::traits::bit_field_slice::BitFieldSliceMut;
 
@@ -36,3 +37,69 @@ fn test_epserde() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_epserde_rear_coded_list() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for sorted in [true, false] {
+        let mut words = (0..1000)
+            .map(|_| {
+                let len = rng.gen_range(0..20);
+                (0..len)
+                    .map(|_| rng.gen_range(b'a'..=b'd') as char)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        if sorted {
+            words.sort();
+            words.dedup();
+        }
+
+        let mut rclb = RearCodedListBuilder::new(4);
+        rclb.extend(words.iter());
+        let rcl = rclb.build();
+
+        let tmp_file = std::env::temp_dir().join("test_serdes_rcl_mmap.bin");
+        rcl.store(&tmp_file)?;
+
+        let mmapped = <RearCodedList>::mmap(&tmp_file, epserde::deser::Flags::empty())?;
+        let loaded = <RearCodedList>::load_full(&tmp_file)?;
+        assert_eq!(mmapped.len(), rcl.len());
+        assert_eq!(loaded.len(), rcl.len());
+        for i in 0..rcl.len() {
+            assert_eq!(mmapped.get(i), rcl.get(i));
+            assert_eq!(loaded.get(i), rcl.get(i));
+        }
+        for (i, word) in mmapped.into_iter().enumerate() {
+            assert_eq!(word, words[i]);
+        }
+        if sorted {
+            for (i, word) in words.iter().enumerate() {
+                assert_eq!(mmapped.index_of(word.as_str()), Some(i));
+            }
+        }
+        std::fs::remove_file(&tmp_file)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_rice_list_epserde() -> Result<()> {
+    let mut builder = RiceListBuilder::new(8);
+    builder.extend((0..10_000).map(|x| x * 10 + x % 7))?;
+    let list = builder.build();
+
+    let tmp_file = std::env::temp_dir().join("test_serdes_rice_list.bin");
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp_file)?);
+    list.serialize(&mut file)?;
+    drop(file);
+
+    let c = <RiceList>::mmap(&tmp_file, epserde::deser::Flags::empty())?;
+    for i in 0..list.len() {
+        assert_eq!(list.get(i), c.get(i));
+    }
+    assert_eq!(c.succ(&100), list.succ(&100));
+    std::fs::remove_file(&tmp_file)?;
+    Ok(())
+}
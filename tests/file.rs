@@ -0,0 +1,127 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use std::io::Write;
+use sux::utils::*;
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn test_compressed_lines() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let lines = (0..1000).map(|i| format!("line{}", i)).collect::<Vec<_>>();
+    let text = lines.join("\n") + "\n";
+
+    let plain = dir.path().join("keys.txt");
+    std::fs::write(&plain, &text)?;
+    let gz = dir.path().join("keys.txt.gz");
+    std::fs::write(&gz, gzip(text.as_bytes()))?;
+    let zst = dir.path().join("keys.txt.zst");
+    std::fs::write(&zst, zstd::encode_all(text.as_bytes(), 0)?)?;
+
+    assert_eq!(
+        FilenameGzipIntoIterator(&gz)
+            .into_iter()
+            .collect::<std::io::Result<Vec<_>>>()?,
+        lines
+    );
+    for path in [&plain, &gz, &zst] {
+        assert_eq!(
+            FilenameAutoIntoIterator(path)
+                .into_iter()
+                .collect::<std::io::Result<Vec<_>>>()?,
+            lines
+        );
+    }
+    assert_eq!(
+        file::FileLines::plain(&plain).collect::<std::io::Result<Vec<_>>>()?,
+        lines
+    );
+    assert_eq!(
+        file::FileLines::zstd(&zst).collect::<std::io::Result<Vec<_>>>()?,
+        lines
+    );
+
+    // Concatenated gzip members
+    let mut data = gzip(b"a\nb\n");
+    data.extend(gzip(b"c\n"));
+    std::fs::write(&gz, data)?;
+    assert_eq!(
+        FilenameAutoIntoIterator(&gz)
+            .into_iter()
+            .collect::<std::io::Result<Vec<_>>>()?,
+        vec!["a", "b", "c"]
+    );
+
+    // Empty files
+    std::fs::write(&plain, "")?;
+    assert_eq!(FilenameAutoIntoIterator(&plain).into_iter().count(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_compressed_lines_errors() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+
+    // Missing file
+    let missing = dir.path().join("missing");
+    let mut iter = FilenameAutoIntoIterator(&missing).into_iter();
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().is_none());
+    let mut iter = FilenameGzipIntoIterator(&missing).into_iter();
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().is_none());
+    for mut iter in [
+        file::FileLines::plain(&missing),
+        file::FileLines::zstd(&missing),
+    ] {
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+
+    // Malformed UTF-8
+    let path = dir.path().join("bad_utf8");
+    std::fs::write(&path, b"good\n\xff\xfe\n")?;
+    let mut iter = FilenameAutoIntoIterator(&path).into_iter();
+    assert_eq!(iter.next().unwrap()?, "good");
+    assert!(iter.next().unwrap().is_err());
+    let mut iter = file::FileLines::plain(&path);
+    assert_eq!(iter.next().unwrap()?, "good");
+    assert!(iter.next().unwrap().is_err());
+
+    // Not a Zstandard stream
+    assert!(file::FileLines::zstd(&path)
+        .take(10)
+        .any(|line| line.is_err()));
+
+    // Truncated gzip stream
+    let text = (0..1000).map(|i| format!("line{}", i)).collect::<Vec<_>>();
+    let data = gzip(text.join("\n").as_bytes());
+    let path = dir.path().join("truncated.gz");
+    std::fs::write(&path, &data[..data.len() / 2])?;
+    assert!(FilenameGzipIntoIterator(&path)
+        .into_iter()
+        .take(2000)
+        .any(|line| line.is_err()));
+    assert!(FilenameAutoIntoIterator(&path)
+        .into_iter()
+        .take(2000)
+        .any(|line| line.is_err()));
+
+    // Truncated Zstandard stream
+    let data = zstd::encode_all(text.join("\n").as_bytes(), 0)?;
+    let path = dir.path().join("truncated.zst");
+    std::fs::write(&path, &data[..data.len() / 2])?;
+    assert!(file::FileLines::zstd(&path)
+        .take(2000)
+        .any(|line| line.is_err()));
+    Ok(())
+}
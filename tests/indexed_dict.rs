@@ -0,0 +1,48 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use sux::traits::IndexedDict;
+
+#[test]
+fn test_get_checked() {
+    let v = vec![3_usize, 1, 4, 1, 5];
+    for i in 0..v.len() {
+        assert_eq!(IndexedDict::get_checked(&v, i), Some(v[i]));
+    }
+    assert_eq!(IndexedDict::get_checked(&v, v.len()), None);
+    assert_eq!(IndexedDict::get_checked(&v, usize::MAX), None);
+
+    let s: &[usize] = &v[1..3];
+    assert_eq!(IndexedDict::get_checked(&s, 1), Some(4));
+    assert_eq!(IndexedDict::get_checked(&s, 2), None);
+
+    let b: Box<[String]> = vec!["a".to_owned(), "b".to_owned()].into_boxed_slice();
+    assert_eq!(IndexedDict::get_checked(&b, 1), Some("b".to_owned()));
+    assert_eq!(IndexedDict::get_checked(&b, 2), None);
+
+    let e: Vec<u8> = vec![];
+    assert_eq!(IndexedDict::get_checked(&e, 0), None);
+}
+
+#[test]
+fn test_contains() {
+    let v = vec![3_usize, 1, 4, 1, 5];
+    for x in 0..10 {
+        assert_eq!(IndexedDict::contains(&v, &x), v.contains(&x));
+    }
+
+    let s: &[usize] = &v[1..3];
+    assert!(IndexedDict::contains(&s, &4));
+    assert!(!IndexedDict::contains(&s, &5));
+
+    let b: Box<[String]> = vec!["a".to_owned(), "b".to_owned()].into_boxed_slice();
+    assert!(IndexedDict::contains(&b, &"b".to_owned()));
+    assert!(!IndexedDict::contains(&b, &"c".to_owned()));
+
+    let e: Vec<u8> = vec![];
+    assert!(!IndexedDict::contains(&e, &0));
+}
@@ -0,0 +1,184 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+#[test]
+fn test_uniform_select() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 100_000;
+    let uniform = |density: f64, rng: &mut SmallRng| {
+        (0..len).map(|_| rng.gen_bool(density)).collect::<BitVec>()
+    };
+    // Ones concentrated in a few short runs
+    let clustered = |rng: &mut SmallRng| {
+        let mut bits = BitVec::new(len);
+        for _ in 0..10 {
+            let start = rng.gen_range(0..len - 1000);
+            for i in start..start + rng.gen_range(1..1000) {
+                bits.set(i, true);
+            }
+        }
+        bits.set(len - 1, true);
+        bits
+    };
+
+    let inputs = vec![
+        uniform(0.5, &mut rng),
+        uniform(0.1, &mut rng),
+        uniform(0.001, &mut rng),
+        uniform(1.0, &mut rng),
+        clustered(&mut rng),
+        clustered(&mut rng),
+    ];
+
+    for bits in inputs {
+        let ones = bits.count_ones();
+        let positions = (0..len).filter(|&i| bits.get(i)).collect::<Vec<_>>();
+        let exact: QuantumIndex = QuantumIndex::new(bits.with_count(ones), ones).unwrap();
+        let uniform = UniformSelect::<_>::assuming_uniform(exact).unwrap();
+        assert_eq!(uniform.count(), ones);
+        assert_eq!(BitLength::len(&uniform), len);
+        for (rank, &pos) in positions.iter().enumerate() {
+            assert_eq!(uniform.select(rank), Some(pos));
+        }
+        assert_eq!(uniform.select(ones), None);
+    }
+}
+
+#[test]
+fn test_select_via_rank() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 63, 64, 65, 1000, 5000] {
+        for density in [0.0, 0.001, 0.1, 0.5, 1.0] {
+            let bits = (0..len).map(|_| rng.gen_bool(density)).collect::<BitVec>();
+            let ones = bits.count_ones();
+            for pos in 0..=len {
+                assert_eq!(bits.rank(pos), (0..pos).filter(|&i| bits.get(i)).count());
+            }
+            let copy = (0..len).map(|i| bits.get(i)).collect::<BitVec>();
+            let exact: QuantumIndex = QuantumIndex::new(copy.with_count(ones), ones).unwrap();
+            let via_rank = SelectViaRank::new(bits);
+            assert_eq!(via_rank.count(), ones);
+            assert_eq!(BitLength::len(&via_rank), len);
+            for rank in 0..ones {
+                assert_eq!(via_rank.select(rank), exact.select(rank));
+            }
+            assert_eq!(via_rank.select(ones), None);
+        }
+    }
+}
+
+#[test]
+fn test_quantum_index_with_quantum() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 100_000;
+    for density in [0.01, 0.4, 1.0] {
+        let bits = (0..len).map(|_| rng.gen_bool(density)).collect::<BitVec>();
+        let ones = bits.count_ones();
+        let positions = (0..len).filter(|&i| bits.get(i)).collect::<Vec<_>>();
+        let mut bits = bits.with_count(ones);
+
+        let mut last_size = usize::MAX;
+        for log2_quantum in [0, 1, 4, 8, 12, 20, 63] {
+            let index = QuantumIndex::with_quantum(bits, log2_quantum).unwrap();
+            assert_eq!(index.log2_quantum(), log2_quantum);
+            assert_eq!(index.num_hints(), ones.div_ceil(1 << log2_quantum));
+            for (rank, &pos) in positions.iter().enumerate() {
+                assert_eq!(index.select(rank), Some(pos));
+            }
+            assert_eq!(index.select(ones), None);
+            // Sparser quanta yield smaller indices
+            let size = index.mem_size();
+            assert!(size <= last_size);
+            last_size = size;
+            // Forget the index
+            bits = index.convert_to().unwrap();
+        }
+
+        // The default quantum
+        let index: QuantumIndex = bits.convert_to().unwrap();
+        assert_eq!(index.log2_quantum(), DEFAULT_LOG2_QUANTUM);
+        let index = QuantumIndex::new(index.convert_to().unwrap(), ones).unwrap();
+        assert_eq!(index.log2_quantum(), DEFAULT_LOG2_QUANTUM);
+
+        let bits: CountBitVec = index.convert_to().unwrap();
+        assert!(QuantumIndex::with_quantum(bits, 64).is_err());
+    }
+}
+
+#[test]
+fn test_quantum_zero_index() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    // Lengths that are not multiples of the word size leave padding bits
+    for len in [0, 1, 63, 64, 1000, 100_001] {
+        for density in [0.0, 0.01, 0.5, 0.99, 1.0] {
+            let bits = (0..len).map(|_| rng.gen_bool(density)).collect::<BitVec>();
+            let ones = bits.count_ones();
+            let zeros = (0..len).filter(|&i| !bits.get(i)).collect::<Vec<_>>();
+            assert_eq!(zeros.len(), len - ones);
+            let mut bits = bits.with_count(ones);
+
+            for log2_quantum in [0, 3, DEFAULT_LOG2_QUANTUM, 20] {
+                let index = QuantumZeroIndex::with_quantum(bits, log2_quantum).unwrap();
+                assert_eq!(index.log2_quantum(), log2_quantum);
+                assert_eq!(index.num_hints(), zeros.len().div_ceil(1 << log2_quantum));
+                for (rank, &pos) in zeros.iter().enumerate() {
+                    assert_eq!(index.select_zero(rank), Some(pos));
+                }
+                assert_eq!(index.select_zero(zeros.len()), None);
+                bits = index.convert_to().unwrap();
+            }
+
+            let index = QuantumZeroIndex::new(bits).unwrap();
+            assert_eq!(index.log2_quantum(), DEFAULT_LOG2_QUANTUM);
+            for (rank, &pos) in zeros.iter().enumerate() {
+                assert_eq!(index.select_zero(rank), Some(pos));
+            }
+            let bits: CountBitVec = index.convert_to().unwrap();
+            assert!(QuantumZeroIndex::with_quantum(bits, 64).is_err());
+        }
+    }
+}
+
+#[test]
+fn test_rank9() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 63, 64, 65, 511, 512, 513, 1000, 4096, 3_000_000] {
+        for density in [0.0, 0.01, 0.5, 1.0] {
+            let bits = (0..len).map(|_| rng.gen_bool(density)).collect::<BitVec>();
+            let ones = bits.count_ones();
+            let rank9 = Rank9::new(bits);
+            assert_eq!(BitLength::len(&rank9), len);
+            assert_eq!(rank9.count(), ones);
+            assert_eq!(rank9.num_blocks(), len.div_ceil(64) / 8 + 1);
+
+            let mut rank = 0;
+            for pos in 0..len {
+                assert_eq!(rank9.rank(pos), rank);
+                assert_eq!(rank9.rank_zero(pos), pos - rank);
+                if rank9.as_ref()[pos / 64] & (1 << (pos % 64)) != 0 {
+                    rank += 1;
+                }
+            }
+            assert_eq!(rank, ones);
+            // Positions at and past the end are clamped
+            for pos in [len, len + 1, len + 1000, usize::MAX] {
+                assert_eq!(rank9.rank(pos), ones);
+                assert_eq!(rank9.rank_zero(pos), len - ones);
+            }
+
+            // The index uses 128 bits every 512 bits
+            assert!(rank9.mem_size() - rank9.as_ref().len() * 8 >= 16 * rank9.num_blocks());
+            let bits: BitVec = rank9.convert_to().unwrap();
+            assert_eq!(bits.len(), len);
+        }
+    }
+}
@@ -83,3 +83,274 @@ fn test_rear_coded_list() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_binary_search() {
+    let mut words = BufReader::new(std::fs::File::open("tests/data/wordlist.10000").unwrap())
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<_>>();
+    words.sort();
+    // Add some repeated strings, also across blocks
+    let repeated = words[100].clone();
+    for _ in 0..20 {
+        words.insert(100, repeated.clone());
+    }
+
+    for k in [1, 4, 8] {
+        let mut rclb = RearCodedListBuilder::new(k);
+        rclb.extend(words.iter());
+        let rcl = rclb.build();
+
+        for (i, word) in words.iter().enumerate() {
+            let first = words.partition_point(|w| w < word);
+            assert_eq!(rcl.binary_search(word), Ok(first), "{}", i);
+            let mut word = word.clone();
+            word.push('\u{10FFFF}');
+            let last = words.partition_point(|w| w <= &word);
+            assert_eq!(rcl.binary_search(&word), Err(last));
+        }
+        assert_eq!(rcl.binary_search(""), Err(0));
+        assert_eq!(rcl.binary_search(&repeated), Ok(100));
+    }
+
+    assert_eq!(
+        RearCodedListBuilder::new(4).build().binary_search("a"),
+        Err(0)
+    );
+}
+
+#[test]
+fn test_estimated_size() -> Result<()> {
+    let words = BufReader::new(std::fs::File::open("tests/data/wordlist.10000")?)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<_>>();
+
+    for k in [1, 4, 8, 64] {
+        let mut rclb = RearCodedListBuilder::new(k);
+        let mut last_estimate = rclb.estimated_size();
+        for word in &words {
+            rclb.push(word);
+            let estimate = rclb.estimated_size();
+            assert!(estimate > last_estimate);
+            last_estimate = estimate;
+        }
+        let rcl = rclb.build();
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        rcl.serialize(&mut bytes)?;
+        let actual = bytes.into_inner().len();
+        // Only the header and the padding are not estimated
+        assert!(actual >= last_estimate, "{} < {}", actual, last_estimate);
+        assert!(
+            actual - last_estimate < 256,
+            "{} - {}",
+            actual,
+            last_estimate
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_iter() {
+    let words = BufReader::new(std::fs::File::open("tests/data/wordlist.10000").unwrap())
+        .lines()
+        .map(|line| line.unwrap())
+        .take(1000)
+        .collect::<Vec<_>>();
+
+    for k in [1, 3, 8] {
+        let mut rcab = <RearCodedListBuilder>::new(k);
+        rcab.extend(words.iter());
+        let rca = rcab.build();
+
+        assert_eq!(rca.iter().len(), words.len());
+        assert_eq!(rca.iter().collect::<Vec<_>>(), words);
+        let mut n = 0;
+        for word in &rca {
+            assert_eq!(word, words[n]);
+            n += 1;
+        }
+        assert_eq!(n, words.len());
+
+        for from in [0, 1, k - 1, k, k + 1, 500, words.len() - 1, words.len()] {
+            let iter = rca.iter_from(from);
+            assert_eq!(iter.len(), words.len() - from);
+            assert_eq!(iter.collect::<Vec<_>>(), words[from..]);
+        }
+    }
+
+    // Empty list
+    let rca = <RearCodedListBuilder>::new(4).build();
+    assert_eq!(rca.iter().count(), 0);
+    assert_eq!(rca.iter_from(0).count(), 0);
+}
+
+#[test]
+#[should_panic]
+fn test_iter_from_out_of_bounds() {
+    let mut rcab = <RearCodedListBuilder>::new(4);
+    rcab.extend(["a", "b", "c"].iter());
+    rcab.build().iter_from(4);
+}
+
+#[test]
+fn test_index_of() {
+    let mut words = BufReader::new(std::fs::File::open("tests/data/wordlist.10000").unwrap())
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<_>>();
+    words.sort();
+    words.dedup();
+
+    for k in [1, 4, 16] {
+        let mut rcab = RearCodedListBuilder::new(k).check_sorted(true);
+        rcab.extend(words.iter());
+        assert!(rcab.is_sorted());
+        let rca = rcab.build();
+        assert!(rca.is_sorted());
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(rca.index_of(word), Some(i));
+        }
+        assert_eq!(rca.index_of(""), None);
+        assert_eq!(rca.index_of("~~~ not in the list"), None);
+        for word in words.iter().step_by(100) {
+            assert_eq!(rca.index_of(&format!("{}\u{1}", word)), None);
+        }
+    }
+
+    // Unsorted lists are scanned linearly
+    let mut rcab = RearCodedListBuilder::new(4);
+    rcab.extend(["b", "a", "c", "a"].iter());
+    assert!(!rcab.is_sorted());
+    let rca = rcab.build();
+    assert_eq!(rca.index_of("a"), Some(1));
+    assert_eq!(rca.index_of("c"), Some(2));
+    assert_eq!(rca.index_of("d"), None);
+
+    // Empty list
+    let rca = RearCodedListBuilder::new(4).build();
+    assert_eq!(rca.index_of("a"), None);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn test_check_sorted() {
+    let mut rcab = RearCodedListBuilder::new(4).check_sorted(true);
+    rcab.push("a");
+    rcab.push("b");
+    rcab.push("b");
+}
+
+#[test]
+fn test_strip_suffixes() -> Result<()> {
+    let mut words = BufReader::new(std::fs::File::open("tests/data/wordlist.10000")?)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<_>>();
+    let mut paths = Vec::new();
+    for dir in ["src", "src/dict", "src/bits", "tests"] {
+        for word in words.iter().take(500) {
+            for ext in [".rs", ".rs.bak", ".txt"] {
+                paths.push(format!("{}/{}{}", dir, word, ext));
+            }
+        }
+    }
+    paths.sort();
+    let mut urls = words
+        .iter()
+        .take(2000)
+        .map(|word| format!("https://example.com/{}/index.html", word))
+        .collect::<Vec<_>>();
+    urls.sort();
+    words.sort();
+    // Strings that are prefixes or suffixes of each other, and multibyte characters
+    let mut tricky = vec![
+        "", "a", "aa", "aaa", "aba", "abaa", "baa", "èè", "èéè", "éè", "é",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect::<Vec<_>>();
+    let unsorted = tricky.clone();
+    tricky.sort();
+
+    for strings in [&paths, &urls, &words, &tricky, &unsorted] {
+        for k in [1, 2, 4, 8, 64] {
+            let mut rclb = RearCodedListBuilder::new(k).strip_suffixes(true);
+            rclb.extend(strings.iter());
+            let stats = rclb.stats();
+            assert_eq!(
+                stats.sum_str_len,
+                strings.iter().map(String::len).sum::<usize>()
+            );
+            assert_eq!(
+                stats.max_str_len,
+                strings.iter().map(String::len).max().unwrap()
+            );
+            // Every byte of a string is either shared with the previous
+            // string or stored, followed by a terminator
+            assert_eq!(
+                stats.sum_lcp + stats.sum_shared_suffix + stats.suffixes_bytes,
+                stats.sum_str_len + strings.len()
+            );
+            if k == 1 {
+                assert_eq!(stats.sum_lcp, 0);
+                assert_eq!(stats.sum_shared_suffix, 0);
+                assert_eq!(stats.suffix_code_bytes, 0);
+            }
+            let rcl = rclb.build();
+
+            assert_eq!(rcl.len(), strings.len());
+            for (i, string) in strings.iter().enumerate() {
+                assert_eq!(&rcl.get(i), string);
+            }
+            assert_eq!(rcl.iter().collect::<Vec<_>>(), *strings);
+            for from in [0, 1, strings.len() / 2, strings.len()] {
+                assert_eq!(rcl.iter_from(from).collect::<Vec<_>>(), strings[from..]);
+            }
+            if rcl.is_sorted() {
+                for (i, string) in strings.iter().enumerate() {
+                    assert_eq!(
+                        rcl.index_of(string),
+                        Some(strings.partition_point(|s| s < string)),
+                        "{}",
+                        i
+                    );
+                }
+            }
+            for string in strings.iter() {
+                assert!(rcl.contains(string));
+            }
+
+            let mut cursor = epserde::new_aligned_cursor();
+            rcl.serialize(&mut cursor)?;
+            cursor.set_position(0);
+            let buf = cursor.into_inner();
+            let loaded = <RearCodedList>::deserialize_eps(&buf)?;
+            assert_eq!(loaded.iter().collect::<Vec<_>>(), *strings);
+        }
+    }
+
+    // Consecutive URLs share their final path component, so stripping
+    // suffixes saves space
+    let mut plain = RearCodedListBuilder::new(8);
+    plain.extend(urls.iter());
+    let mut stripped = RearCodedListBuilder::new(8).strip_suffixes(true);
+    stripped.extend(urls.iter());
+    let (plain, stripped) = (plain.build(), stripped.build());
+    assert!(stripped.mem_size() < plain.mem_size());
+    for (i, url) in urls.iter().enumerate() {
+        assert_eq!(&stripped.get(i), url);
+    }
+    Ok(())
+}
+
+#[test]
+#[should_panic]
+fn test_strip_suffixes_after_push() {
+    let mut rclb = RearCodedListBuilder::new(4);
+    rclb.push("a");
+    let _ = rclb.strip_suffixes(true);
+}
@@ -0,0 +1,127 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+fn check(list: &RiceList, values: &[u64]) {
+    assert_eq!(list.len(), values.len());
+    for (i, &v) in values.iter().enumerate() {
+        assert_eq!(list.get(i), v);
+    }
+    assert_eq!(list.into_iter().collect::<Vec<_>>(), values);
+    for from in [
+        0,
+        1,
+        values.len() / 2,
+        values.len().saturating_sub(1),
+        values.len(),
+    ] {
+        let from = from.min(values.len());
+        let iter = list.into_iter_from(from);
+        assert_eq!(iter.len(), values.len() - from);
+        assert_eq!(iter.collect::<Vec<_>>(), &values[from..]);
+    }
+
+    let last = values.last().copied().unwrap_or(0);
+    for x in 0..last + 3 {
+        // first value greater than or equal to x
+        let succ = values.iter().position(|&v| v >= x);
+        assert_eq!(list.succ(&x), succ.map(|i| (i, values[i])));
+        let succ_strict = values.iter().position(|&v| v > x);
+        assert_eq!(list.succ_strict(&x), succ_strict.map(|i| (i, values[i])));
+        // last value smaller than or equal to x
+        let pred = values.iter().rposition(|&v| v <= x);
+        assert_eq!(list.pred(&x), pred.map(|i| (i, values[i])));
+        let pred_strict = values.iter().rposition(|&v| v < x);
+        assert_eq!(list.pred_strict(&x), pred_strict.map(|i| (i, values[i])));
+
+        assert_eq!(list.contains(&x), values.contains(&x));
+        let expected = match values.binary_search(&x) {
+            Ok(_) => Ok(values.iter().position(|&v| v == x).unwrap()),
+            Err(i) => Err(i),
+        };
+        assert_eq!(list.binary_search(&x), expected);
+    }
+}
+
+#[test]
+fn test_rice_list() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for n in [0, 1, 2, 10, 100, 1000] {
+        for max_gap in [1, 2, 10, 100, 1000] {
+            let mut values = Vec::with_capacity(n);
+            let mut value = rng.gen_range(0..100);
+            for _ in 0..n {
+                value += rng.gen_range(0..max_gap);
+                values.push(value);
+            }
+            for k in [1, 2, 3, 8, 32] {
+                let mut builder = RiceListBuilder::new(k);
+                builder.extend(values.iter().copied())?;
+                assert_eq!(builder.len(), n);
+                let list = builder.build();
+                assert_eq!(list.k(), k);
+                check(&list, &values);
+
+                for log2_b in [0, 1, 5, 63] {
+                    let mut builder = RiceListBuilder::new(k).log2_b(log2_b);
+                    builder.extend(values.iter().copied())?;
+                    let list = builder.build();
+                    assert_eq!(list.log2_b(), log2_b);
+                    check(&list, &values);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_rice_list_large_gaps() -> Result<()> {
+    let values = [0, 1, u64::MAX / 2, u64::MAX - 1, u64::MAX, u64::MAX];
+    for log2_b in [60, 63] {
+        let mut builder = RiceListBuilder::new(4).log2_b(log2_b);
+        builder.extend(values)?;
+        let list = builder.build();
+        for (i, &v) in values.iter().enumerate() {
+            assert_eq!(list.get(i), v);
+        }
+        assert_eq!(list.succ(&(u64::MAX / 2 + 1)), Some((3, u64::MAX - 1)));
+        assert_eq!(list.pred(&(u64::MAX - 2)), Some((2, u64::MAX / 2)));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_rice_list_param() -> Result<()> {
+    // geometric gaps with mean about 1000
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut builder = RiceListBuilder::new(16);
+    let mut value = 0;
+    for _ in 0..100_000 {
+        value += (-rng.gen::<f64>().ln() * 1000.0) as u64;
+        builder.push(value)?;
+    }
+    let list = builder.build();
+    assert_eq!(list.log2_b(), 9);
+    // about log2_b + 2 bits per gap, plus the samples
+    assert!(list.mem_size() * 8 < 100_000 * (11 + 128 / 16 + 1));
+    Ok(())
+}
+
+#[test]
+fn test_rice_list_not_monotone() {
+    let mut builder = RiceListBuilder::new(4);
+    builder.push(10).unwrap();
+    builder.push(10).unwrap();
+    assert!(builder.push(9).is_err());
+    assert_eq!(builder.len(), 2);
+}
@@ -0,0 +1,75 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use sux::prelude::*;
+
+/// Return the suffix array and the LF-mapping of a text.
+fn suffix_array(text: &[u8]) -> (Vec<usize>, Vec<usize>) {
+    let n = text.len();
+    let mut sa = (0..n).collect::<Vec<_>>();
+    sa.sort_by(|&a, &b| text[a..].cmp(&text[b..]));
+    let mut isa = vec![0; n];
+    for (i, &p) in sa.iter().enumerate() {
+        isa[p] = i;
+    }
+    let lf = sa.iter().map(|&p| isa[(p + n - 1) % n]).collect();
+    (sa, lf)
+}
+
+#[test]
+fn test_sampled_sa() -> anyhow::Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut texts: Vec<Vec<u8>> = vec![
+        b"$".to_vec(),
+        b"banana$".to_vec(),
+        b"mississippi$".to_vec(),
+        b"abracadabra$".to_vec(),
+    ];
+    for len in [10, 100, 1000] {
+        let mut text = (0..len)
+            .map(|_| b"acgt"[rng.gen_range(0..4)])
+            .collect::<Vec<_>>();
+        text.push(b'$');
+        texts.push(text);
+    }
+
+    for text in &texts {
+        let (sa, lf) = suffix_array(text);
+        for sample_rate in [1, 2, 3, 5, 16, 2000] {
+            let ssa = <SampledSA>::new(&sa, sample_rate)?;
+            assert_eq!(ssa.len(), sa.len());
+            assert_eq!(ssa.sample_rate(), sample_rate);
+            assert_eq!(ssa.num_samples(), sa.len().div_ceil(sample_rate));
+            for (i, &value) in sa.iter().enumerate() {
+                assert_eq!(ssa.is_sampled(i), value % sample_rate == 0);
+                assert_eq!(
+                    ssa.get_sampled(i),
+                    Some(value).filter(|v| v % sample_rate == 0)
+                );
+                assert_eq!(ssa.get(i, |j| lf[j]), value, "position {}", i);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_sampled_sa_empty() -> anyhow::Result<()> {
+    let ssa = <SampledSA>::new(&[], 4)?;
+    assert!(ssa.is_empty());
+    assert_eq!(ssa.num_samples(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_sampled_sa_errors() {
+    assert!(<SampledSA>::new(&[0, 1], 0).is_err());
+    assert!(<SampledSA>::new(&[0, 2], 1).is_err());
+    assert!(<SampledSA>::new(&[0, 0, 1], 1).is_err());
+}
@@ -0,0 +1,109 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(feature = "serde")]
+
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sux::prelude::*;
+
+fn round_trip<W: Word + Serialize + for<'de> Deserialize<'de>>(
+    v: &BitFieldVec<W>,
+) -> BitFieldVec<W> {
+    serde_json::from_str(&serde_json::to_string(v).unwrap()).unwrap()
+}
+
+#[test]
+fn test_serde_round_trip() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 10, 100, 1000] {
+        for bit_width in [0, 1, 5, 8, 13, 32, 63, 64] {
+            let mask = if bit_width == 0 {
+                0
+            } else {
+                u64::MAX >> (64 - bit_width)
+            };
+            let mut v = BitFieldVec::<u64>::new(bit_width, len);
+            for i in 0..len {
+                v.set(i, rng.gen::<u64>() & mask);
+            }
+            let w = round_trip(&v);
+            assert_eq!(w.bit_width(), bit_width);
+            assert_eq!(w.len(), len);
+            assert_eq!(v.clone().into_raw_parts(), w.into_raw_parts());
+
+            if bit_width <= 8 {
+                let v = (&v)
+                    .into_iter()
+                    .map(|x| x as u8)
+                    .collect::<BitFieldVec<u8>>();
+                let w = round_trip(&v);
+                assert_eq!(v.clone().into_raw_parts(), w.into_raw_parts());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_serde_fields() {
+    let v: BitFieldVec = vec![1_usize, 2, 3].into_iter().collect();
+    let json = serde_json::to_string(&v).unwrap();
+    let prefix = format!(r#"{{"bit_width":{},"len":3,"data":["#, v.bit_width());
+    assert!(json.starts_with(&prefix), "{}", json);
+
+    // Formats can also represent structures as sequences
+    let Value::Object(fields) = serde_json::to_value(&v).unwrap() else {
+        panic!("Expected a structure");
+    };
+    let seq = json!([fields["bit_width"], fields["len"], fields["data"]]);
+    let w = serde_json::from_value::<BitFieldVec<usize>>(seq).unwrap();
+    assert_eq!(v.into_raw_parts(), w.into_raw_parts());
+}
+
+#[test]
+fn test_serde_invalid() {
+    let fixture = |bit_width: u64, len: u64, words: usize| {
+        json!({
+            "bit_width": bit_width,
+            "len": len,
+            "data": vec![0; words],
+        })
+    };
+
+    // Exactly enough bits
+    let v = serde_json::from_value::<BitFieldVec<u64>>(fixture(10, 64, 10)).unwrap();
+    assert_eq!(v.len(), 64);
+    assert_eq!(v.get(63), 0);
+
+    // Data too short
+    let err = serde_json::from_value::<BitFieldVec<u64>>(fixture(10, 65, 10)).unwrap_err();
+    assert!(err.to_string().contains("not enough"), "{}", err);
+    let err = serde_json::from_value::<BitFieldVec<u64>>(fixture(1, u64::MAX, 1)).unwrap_err();
+    assert!(err.to_string().contains("not enough"), "{}", err);
+
+    // No data, even if no bits are needed
+    let err = serde_json::from_str::<BitFieldVec<u64>>(r#"{"bit_width":0,"len":5,"data":[]}"#)
+        .unwrap_err();
+    assert!(err.to_string().contains("at least one word"), "{}", err);
+
+    // Bit width too large
+    let err = serde_json::from_value::<BitFieldVec<u8>>(fixture(9, 1, 2)).unwrap_err();
+    assert!(err.to_string().contains("bit width"), "{}", err);
+
+    // Missing field
+    let err =
+        serde_json::from_value::<BitFieldVec<u64>>(json!({"bit_width": 1, "len": 1})).unwrap_err();
+    assert!(err.to_string().contains("data"), "{}", err);
+
+    // Unknown field
+    assert!(
+        serde_json::from_value::<BitFieldVec<u64>>(json!({"bit_width": 1, "width": 1})).is_err()
+    );
+}
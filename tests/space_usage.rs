@@ -0,0 +1,82 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::io::{BufRead, BufReader};
+use sux::prelude::*;
+
+fn check_breakdown(s: &impl SpaceUsage, labels: &[&str]) {
+    let breakdown = s.space_breakdown();
+    assert_eq!(breakdown.keys().copied().collect::<Vec<_>>(), labels);
+    assert_eq!(breakdown.values().sum::<usize>(), s.mem_size());
+}
+
+#[test]
+fn test_bits() {
+    let v = vec![0_usize; 10];
+    assert_eq!(v.mem_size(), core::mem::size_of::<Vec<usize>>() + 80);
+    check_breakdown(&v, &["data"]);
+
+    let b = BitVec::new(1000);
+    assert!(b.mem_size() >= 1000 / 8);
+    check_breakdown(&b, &["bits"]);
+
+    let b = BitFieldVec::<u16>::new(10, 1000);
+    assert!(b.mem_size() >= 10 * 1000 / 8);
+    check_breakdown(&b, &["data"]);
+}
+
+#[test]
+fn test_elias_fano() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(1, 10), (1000, 1000), (10000, 1_000_000)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..u)).collect::<Vec<_>>();
+        values.sort();
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for &value in &values {
+            efb.push(value)?;
+        }
+        let ef = efb.build();
+        check_breakdown(&ef, &["high_bits", "low_bits", "metadata"]);
+        let plain = ef.mem_size();
+
+        let ef: EliasFano<QuantumIndex> = ef.convert_to()?;
+        check_breakdown(&ef, &["high_bits", "low_bits", "metadata", "select_index"]);
+        assert!(ef.mem_size() >= plain);
+
+        let ef: EliasFano<QuantumZeroIndex<QuantumIndex>> = ef.convert_to()?;
+        check_breakdown(
+            &ef,
+            &[
+                "high_bits",
+                "low_bits",
+                "metadata",
+                "select_index",
+                "select_zero_index",
+            ],
+        );
+        let breakdown = ef.space_breakdown();
+        // The upper bits use less than 3n + 1 bits
+        assert!(breakdown["high_bits"] <= 3 * n / 8 + 64);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_rear_coded_list() -> Result<()> {
+    let words = BufReader::new(std::fs::File::open("tests/data/wordlist.10000")?)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<_>>();
+    let mut rclb = RearCodedListBuilder::new(8);
+    rclb.extend(words.iter());
+    let rcl = rclb.build();
+    check_breakdown(&rcl, &["data", "metadata", "pointers"]);
+    Ok(())
+}
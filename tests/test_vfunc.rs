@@ -7,7 +7,10 @@
 
 use dsi_progress_logger::*;
 use epserde::prelude::*;
-use sux::{func::VFunc, prelude::VFuncBuilder};
+use sux::func::{Phase, VFunc, VFuncError};
+use sux::prelude::VFuncBuilder;
+use sux::traits::bit_field_slice::BitFieldSliceCore;
+use sux::utils::ToSig;
 
 #[test]
 fn test_func() -> anyhow::Result<()> {
@@ -45,3 +48,491 @@ fn test_dup_key() {
         )
         .is_err());
 }
+
+/// A key recording the seeds used to compute its signature.
+#[derive(Debug, Clone, Copy, Default)]
+struct SeedRecorder(u64);
+
+static SEEDS: std::sync::Mutex<Vec<u64>> = std::sync::Mutex::new(Vec::new());
+
+impl ToSig for SeedRecorder {
+    fn to_sig(key: &Self, seed: u64) -> [u64; 2] {
+        SEEDS.lock().unwrap().push(seed);
+        <u64 as ToSig>::to_sig(&key.0, seed)
+    }
+}
+
+#[test]
+fn test_dup_key_seeds() {
+    // Every attempt after a duplicate signature uses a new seed
+    for offline in [false, true] {
+        SEEDS.lock().unwrap().clear();
+        let keys = (0..1000).chain([500]).map(SeedRecorder).collect::<Vec<_>>();
+        let err = VFuncBuilder::<_, u64>::default()
+            .offline(offline)
+            .build(keys, &(0..), &mut Option::<ProgressLogger>::None)
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<VFuncError>(),
+            Some(&VFuncError::DuplicateKey)
+        );
+        let mut seeds = SEEDS.lock().unwrap().clone();
+        seeds.dedup();
+        assert_eq!(seeds, vec![0, 1, 2, 3]);
+    }
+}
+
+#[test]
+fn test_allow_duplicates() -> anyhow::Result<()> {
+    let n = 1000_u64;
+    // Key 500 is repeated with a different value
+    let keys = (0..n).chain([500]).collect::<Vec<_>>();
+    let values = (0..n).chain([0]).collect::<Vec<_>>();
+    for offline in [false, true] {
+        let err = VFuncBuilder::<_, u64>::default()
+            .offline(offline)
+            .build(
+                keys.iter().copied(),
+                &values,
+                &mut Option::<ProgressLogger>::None,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<VFuncError>(),
+            Some(&VFuncError::DuplicateKey)
+        );
+
+        let func = VFuncBuilder::<_, u64>::default()
+            .offline(offline)
+            .allow_duplicates(true)
+            .build(
+                keys.iter().copied(),
+                &values,
+                &mut Option::<ProgressLogger>::None,
+            )?;
+        assert_eq!(func.len(), n as usize);
+        for i in 0..n {
+            if i == 500 {
+                assert!(func.get(&i) == 500 || func.get(&i) == 0);
+            } else {
+                assert_eq!(func.get(&i), i);
+            }
+        }
+    }
+
+    let pairs = keys.iter().copied().zip(values.iter().copied());
+    let err = VFuncBuilder::<_, u64>::default()
+        .build_from_stream(pairs.clone(), &mut Option::<ProgressLogger>::None)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<VFuncError>(),
+        Some(&VFuncError::DuplicateKey)
+    );
+    let func = VFuncBuilder::<_, u64>::default()
+        .allow_duplicates(true)
+        .build_from_stream(pairs, &mut Option::<ProgressLogger>::None)?;
+    assert_eq!(func.len(), n as usize);
+    assert!(func.get(&500) == 500 || func.get(&500) == 0);
+    Ok(())
+}
+
+#[test]
+fn test_build_small() -> anyhow::Result<()> {
+    let mut pl = ProgressLogger::default();
+    let n = 10000_u64;
+    for bits in [1, 2, 3, 8] {
+        let labels = (0..n)
+            .map(|i| (i.wrapping_mul(0x9E3779B97F4A7C15) >> (64 - bits)) as u8)
+            .collect::<Vec<_>>();
+        let func = VFuncBuilder::<_, u8>::default().build_small(
+            0..n,
+            labels.iter().copied(),
+            bits,
+            &mut pl,
+        )?;
+        for i in 0..n {
+            assert_eq!(labels[i as usize], func.get(&i));
+        }
+    }
+
+    // A label that does not fit
+    assert!(VFuncBuilder::<_, u8>::default()
+        .build_small(0..3_u64, [0, 1, 2], 1, &mut pl)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_build_small_bits_per_key() -> anyhow::Result<()> {
+    let mut pl = ProgressLogger::default();
+    let n = 100000_u64;
+    let func = VFuncBuilder::<_, u8>::default().build_small(
+        0..n,
+        (0..n).map(|i| (i % 4) as u8),
+        2,
+        &mut pl,
+    )?;
+    let mut cursor = epserde::new_aligned_cursor();
+    func.serialize(&mut cursor).unwrap();
+    let bits_per_key = cursor.into_inner().len() as f64 * 8.0 / n as f64;
+    pl.info(format_args!("bits/key: {}", bits_per_key));
+    assert!(bits_per_key < 2.5, "{}", bits_per_key);
+    Ok(())
+}
+
+#[test]
+#[should_panic]
+fn test_build_small_too_many_bits() {
+    let _ = VFuncBuilder::<_, u8>::default().build_small(
+        0..3_u64,
+        [0, 1, 2],
+        9,
+        &mut Option::<ProgressLogger>::None,
+    );
+}
+
+#[test]
+fn test_identical_dup() -> anyhow::Result<()> {
+    let mut pl = ProgressLogger::default();
+    let n = 1000_usize;
+    for offline in [false, true] {
+        // Exact-duplicate key/value pairs are harmless
+        let keys = (0..n).chain(0..10).chain(500..600).collect::<Vec<_>>();
+        let func = VFuncBuilder::<_>::default().offline(offline).build(
+            keys.iter().copied(),
+            &keys.clone(),
+            &mut pl,
+        )?;
+        assert_eq!(func.len(), n);
+        for i in 0..n {
+            assert_eq!(i, func.get(&i));
+        }
+
+        // Duplicate keys with conflicting values are not
+        let values = (0..keys.len()).collect::<Vec<_>>();
+        assert!(VFuncBuilder::<_>::default()
+            .offline(offline)
+            .build(keys.iter().copied(), &values, &mut pl)
+            .is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_build_stats() -> anyhow::Result<()> {
+    for offline in [false, true] {
+        let n = 200_000_u64;
+        let (func, stats) = VFuncBuilder::<_>::default()
+            .offline(offline)
+            .build_with_stats(0..n, &(0..), &mut Option::<ProgressLogger>::None)?;
+        for i in 0..n {
+            assert_eq!(i, func.get(&i) as u64);
+        }
+        assert!(stats.attempts >= 1);
+        assert_eq!(
+            stats.phase_durations.keys().copied().collect::<Vec<_>>(),
+            vec![
+                Phase::Hashing,
+                Phase::Sorting,
+                Phase::Peeling,
+                Phase::Assignment
+            ]
+        );
+        assert!(stats.phase_durations.values().all(|d| !d.is_zero()));
+        let sum = stats.phase_durations.values().sum::<std::time::Duration>();
+        assert!(sum <= stats.total_duration);
+        // The phases must account for most of the construction time
+        assert!(sum * 2 >= stats.total_duration);
+    }
+
+    let n = 200_000_u64;
+    let (func, mut stats) = VFuncBuilder::<_>::default()
+        .build_from_stream_with_stats((0..n).zip(0..), &mut Option::<ProgressLogger>::None)?;
+    for i in 0..n {
+        assert_eq!(i, func.get(&i) as u64);
+    }
+    assert_eq!(stats.attempts as u64, func.remix() + 1);
+    assert_eq!(stats.phase_durations.len(), 4);
+    let sum = stats.phase_durations.values().sum::<std::time::Duration>();
+    assert!(sum <= stats.total_duration);
+    assert!(sum * 2 >= stats.total_duration);
+
+    let total = stats.total_duration;
+    let elapsed = std::time::Duration::from_millis(5);
+    stats.record(Phase::Serialization, elapsed);
+    assert_eq!(stats.phase_durations[&Phase::Serialization], elapsed);
+    assert_eq!(stats.total_duration, total + elapsed);
+    Ok(())
+}
+
+#[test]
+fn test_metadata() -> anyhow::Result<()> {
+    let n = 100_000_u64;
+    for offline in [false, true] {
+        let (func, stats) = VFuncBuilder::<_>::default()
+            .offline(offline)
+            .log2_buckets(4)
+            .build_with_stats(0..n, &(0..), &mut Option::<ProgressLogger>::None)?;
+        // Each failed attempt increments the seed
+        assert_eq!(func.seed(), stats.attempts as u64 - 1);
+        assert_eq!(func.remix(), 0);
+        assert_eq!(func.log2_buckets(), if offline { 4 } else { 0 });
+        assert_eq!(func.bit_width(), 17);
+        assert_eq!(
+            func.bits_per_key(),
+            func.values().len() as f64 * 17.0 / n as f64
+        );
+        assert!(func.bits_per_key() < 17.0 * 1.2);
+
+        // The metadata survive serialization
+        let mut cursor = epserde::new_aligned_cursor();
+        func.serialize(&mut cursor).unwrap();
+        cursor.set_position(0);
+        let buf = cursor.into_inner();
+        let loaded = VFunc::<u64>::deserialize_eps(&buf).unwrap();
+        assert_eq!(loaded.seed(), func.seed());
+        assert_eq!(loaded.log2_buckets(), func.log2_buckets());
+        assert_eq!(loaded.num_chunks(), func.num_chunks());
+        assert_eq!(loaded.bits_per_key(), func.bits_per_key());
+        assert_eq!(loaded.len(), n as usize);
+    }
+
+    let func = VFuncBuilder::<_>::default()
+        .log2_buckets(5)
+        .build_from_stream((0..n).zip(0..), &mut Option::<ProgressLogger>::None)?;
+    assert_eq!(func.seed(), 0);
+    assert_eq!(func.log2_buckets(), 5);
+    Ok(())
+}
+
+#[test]
+fn test_values() -> anyhow::Result<()> {
+    use sux::prelude::{BitFieldSlice, BitFieldSliceCore};
+    use sux::utils::ToSig;
+    for n in [10_u64, 1000, 100000] {
+        let func = VFuncBuilder::<_>::default().build(
+            0..n,
+            &(0..),
+            &mut Option::<ProgressLogger>::None,
+        )?;
+        let values = func.values();
+        for i in 0..n {
+            let vars = func.vars(&i);
+            assert_eq!(vars, func.vars_by_sig(&u64::to_sig(&i, func.seed())));
+            assert!(vars[0] != vars[1] && vars[1] != vars[2] && vars[0] != vars[2]);
+            assert_eq!(
+                values.get(vars[0]) ^ values.get(vars[1]) ^ values.get(vars[2]),
+                func.get(&i)
+            );
+        }
+        let len = values.len();
+        assert!(len as u64 >= n);
+        let values = func.into_values();
+        assert_eq!(values.len(), len);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_arbitrary_values() -> anyhow::Result<()> {
+    use sux::prelude::BitFieldSliceCore;
+    for offline in [false, true] {
+        let n = 10000_u64;
+        let values = (0..n)
+            .map(|i| i.wrapping_mul(0x9E3779B97F4A7C15) >> 20)
+            .collect::<Vec<_>>();
+        let func = VFuncBuilder::<_, u64>::default().offline(offline).build(
+            0..n,
+            &values,
+            &mut Option::<ProgressLogger>::None,
+        )?;
+        assert_eq!(func.values().bit_width(), 44);
+        for i in 0..n {
+            assert_eq!(func.get(&i), values[i as usize]);
+        }
+
+        // Additional values are ignored
+        let func = VFuncBuilder::<_, u64>::default().offline(offline).build(
+            0..n,
+            &(0..2 * n),
+            &mut Option::<ProgressLogger>::None,
+        )?;
+        for i in 0..n {
+            assert_eq!(func.get(&i), i);
+        }
+
+        // All-zero values use no bits
+        let func = VFuncBuilder::<_, u64>::default().offline(offline).build(
+            0..n,
+            &std::iter::repeat(0).take(n as usize),
+            &mut Option::<ProgressLogger>::None,
+        )?;
+        assert_eq!(func.values().bit_width(), 0);
+        for i in 0..n {
+            assert_eq!(func.get(&i), 0);
+        }
+
+        // Empty key set
+        let func = VFuncBuilder::<_, u64>::default().offline(offline).build(
+            0..0,
+            &(0..0),
+            &mut Option::<ProgressLogger>::None,
+        )?;
+        assert!(func.is_empty());
+        assert_eq!(func.values().bit_width(), 0);
+
+        // Fewer values than keys
+        assert!(VFuncBuilder::<_, u64>::default()
+            .offline(offline)
+            .build(0..n, &(0..n - 1), &mut Option::<ProgressLogger>::None)
+            .is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_try_get() -> anyhow::Result<()> {
+    use sux::prelude::BitFieldSliceCore;
+    let n = 10000_u64;
+    for offline in [false, true] {
+        // Without signature bits, try_get always returns a value
+        let func = VFuncBuilder::<_, u64>::default().offline(offline).build(
+            0..n,
+            &(0..),
+            &mut Option::<ProgressLogger>::None,
+        )?;
+        assert_eq!(func.signature_bits(), 0);
+        for i in 0..n {
+            assert_eq!(func.try_get(&i), Some(i));
+        }
+        assert!(func.try_get(&n).is_some());
+
+        for signature_bits in [1, 8, 20] {
+            let func = VFuncBuilder::<_, u64>::default()
+                .offline(offline)
+                .signature_bits(signature_bits)
+                .build(0..n, &(0..), &mut Option::<ProgressLogger>::None)?;
+            assert_eq!(func.signature_bits(), signature_bits);
+            assert_eq!(func.values().bit_width(), 14 + signature_bits as usize);
+            for i in 0..n {
+                assert_eq!(func.get(&i), i);
+                assert_eq!(func.try_get(&i), Some(i));
+            }
+            // The false-positive rate is about 2^-signature_bits
+            let false_positives = (n..11 * n).filter(|i| func.try_get(i).is_some()).count();
+            let expected = 10.0 * n as f64 / (1_u64 << signature_bits) as f64;
+            assert!(
+                (false_positives as f64) < 2.0 * expected + 10.0,
+                "{} false positives, expected about {}",
+                false_positives,
+                expected
+            );
+        }
+    }
+
+    // The signature bits must fit in the output type
+    assert!(VFuncBuilder::<_, u8>::default()
+        .signature_bits(8)
+        .build(0..10_u64, &(0..), &mut Option::<ProgressLogger>::None)
+        .is_err());
+    assert!(VFuncBuilder::<_, u8>::default()
+        .signature_bits(4)
+        .build(0..100_u64, &(0..), &mut Option::<ProgressLogger>::None)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_build_from_stream() -> anyhow::Result<()> {
+    let mut pl = ProgressLogger::default();
+    for n in [0_u64, 1, 10, 1000, 100000] {
+        let func = VFuncBuilder::<_, u64>::default()
+            .signature_bits(4)
+            .build_from_stream((0..n).zip(0..), &mut pl)?;
+        assert_eq!(func.len(), n as usize);
+        let mut cursor = epserde::new_aligned_cursor();
+        func.serialize(&mut cursor).unwrap();
+        cursor.set_position(0);
+        let buf = cursor.into_inner();
+        let func = VFunc::<u64, u64>::deserialize_eps(&buf).unwrap();
+        for i in 0..n {
+            assert_eq!(func.get(&i), i);
+            assert_eq!(func.try_get(&i), Some(i));
+        }
+    }
+
+    // Many small key sets, for which some attempts fail and the
+    // signatures must be remixed
+    for offset in 0..200_u64 {
+        let keys = offset * 100..offset * 100 + 20;
+        let func = VFuncBuilder::<_, u64>::default()
+            .log2_buckets(2)
+            .build_from_stream(
+                keys.clone().map(|k| (k, k % 7)),
+                &mut Option::<ProgressLogger>::None,
+            )?;
+        for k in keys {
+            assert_eq!(func.get(&k), k % 7);
+        }
+    }
+
+    // Keys are consumed once
+    let mut calls = 0;
+    let keys = (0..1000_u64).inspect(|_| calls += 1);
+    VFuncBuilder::<_, u64>::default()
+        .build_from_stream(keys.zip(0..), &mut Option::<ProgressLogger>::None)?;
+    assert_eq!(calls, 1000);
+
+    assert!(VFuncBuilder::<_, u64>::default()
+        .build_from_stream(
+            [(0_u64, 0), (1, 1), (0, 2)],
+            &mut Option::<ProgressLogger>::None
+        )
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_get_batch() -> anyhow::Result<()> {
+    for n in [0_u64, 1, 31, 32, 33, 1000, 100000] {
+        for signature_bits in [0, 8] {
+            let func = VFuncBuilder::<_, u64>::default()
+                .signature_bits(signature_bits)
+                .build(0..n, &(0..), &mut Option::<ProgressLogger>::None)?;
+            // Include keys that are not in the function
+            let keys = (0..n + 100).collect::<Vec<_>>();
+            let expected = keys.iter().map(|k| func.get(k)).collect::<Vec<_>>();
+            let mut out = vec![0; keys.len()];
+            func.get_batch(&keys, &mut out);
+            assert_eq!(out, expected);
+            let mut out = vec![0; keys.len()];
+            func.par_get_batch(&keys, &mut out);
+            assert_eq!(out, expected);
+        }
+    }
+
+    let keys = (0..10000).map(|i| format!("key{}", i)).collect::<Vec<_>>();
+    let func = VFuncBuilder::<String, u64>::default().build(
+        keys.clone(),
+        &(0..),
+        &mut Option::<ProgressLogger>::None,
+    )?;
+    let mut out = vec![0; keys.len()];
+    func.get_batch(&keys, &mut out);
+    assert_eq!(out, (0..keys.len() as u64).collect::<Vec<_>>());
+    // References to keys can be used, too
+    let refs = keys.iter().rev().collect::<Vec<_>>();
+    func.par_get_batch(&refs, &mut out);
+    assert_eq!(out, (0..keys.len() as u64).rev().collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+#[should_panic]
+fn test_get_batch_len_mismatch() {
+    let func = VFuncBuilder::<_, u64>::default()
+        .build(0..10_u64, &(0..), &mut Option::<ProgressLogger>::None)
+        .unwrap();
+    func.get_batch(&[0, 1, 2], &mut [0; 2]);
+}
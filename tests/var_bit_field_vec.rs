@@ -0,0 +1,93 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use sux::prelude::*;
+
+#[test]
+fn test_var_bit_field_vec() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for n in [0, 1, 10, 1000, 100000] {
+        // Mostly small values, with a few large ones
+        let values = (0..n)
+            .map(|_| {
+                if rng.gen_bool(0.01) {
+                    rng.gen::<usize>() >> rng.gen_range(0..64)
+                } else {
+                    rng.gen_range(0..8)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut builder = VarBitFieldVecBuilder::new();
+        builder.extend(values.iter().copied());
+        assert_eq!(builder.len(), n);
+        let v = builder.build()?;
+        assert_eq!(v.len(), n);
+        assert_eq!(v.is_empty(), n == 0);
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(v.get(i), value);
+        }
+        assert_eq!(v.iter().collect::<Vec<_>>(), values);
+
+        let breakdown = v.space_breakdown();
+        assert_eq!(breakdown.values().sum::<usize>(), v.mem_size());
+
+        if n >= 1000 {
+            // Compare with the minimum fixed width
+            let max = *values.iter().max().unwrap();
+            let bit_width = (usize::BITS - max.leading_zeros()) as usize;
+            let mut b = BitFieldVec::<usize>::new(bit_width, 0);
+            b.extend(values.iter().copied());
+            assert!(v.bit_len() < n * bit_width / 4);
+            assert!(v.mem_size() < b.mem_size() / 2);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_var_bit_field_vec_extremes() -> Result<()> {
+    let values = [
+        0,
+        1,
+        2,
+        3,
+        usize::MAX,
+        0,
+        usize::MAX - 1,
+        1 << 63,
+        (1 << 63) - 1,
+        0,
+    ];
+    // Shift the values by different amounts to test all alignments
+    for shift in 0..64 {
+        let mut builder = VarBitFieldVecBuilder::new();
+        for _ in 0..shift {
+            builder.push(1);
+        }
+        builder.extend(values.iter().copied());
+        let v = builder.build()?;
+        for i in 0..shift {
+            assert_eq!(v.get(i), 1);
+        }
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(v.get(shift + i), value);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[should_panic]
+fn test_var_bit_field_vec_out_of_bounds() {
+    let mut builder = VarBitFieldVecBuilder::new();
+    builder.push(5);
+    builder.build().unwrap().get(1);
+}
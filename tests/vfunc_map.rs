@@ -0,0 +1,57 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use dsi_progress_logger::*;
+use epserde::prelude::*;
+use sux::func::VFuncMap;
+use sux::prelude::VFuncBuilder;
+
+#[test]
+fn test_vfunc_map() -> anyhow::Result<()> {
+    let mut pl = ProgressLogger::default();
+
+    for offline in [false, true] {
+        for n in [1_usize, 10, 1000, 10000] {
+            let keys = (0..n).map(|i| format!("key{}", i)).collect::<Vec<_>>();
+            // Variable-length UTF-8 values, including empty ones
+            let values = (0..n)
+                .map(|i| "äβ€😀".repeat(i % 7) + &"x".repeat(i % 3))
+                .collect::<Vec<_>>();
+            let map = VFuncBuilder::<_>::default().offline(offline).build_map(
+                keys.iter().cloned(),
+                values.iter(),
+                &mut pl,
+            )?;
+            assert_eq!(map.len(), n);
+            for (key, value) in keys.iter().zip(values.iter()) {
+                assert_eq!(std::str::from_utf8(map.get(key))?, value);
+            }
+
+            let mut cursor = epserde::new_aligned_cursor();
+            map.serialize(&mut cursor)?;
+            cursor.set_position(0);
+            let buf = cursor.into_inner();
+            let map = VFuncMap::<String>::deserialize_eps(&buf)?;
+            for (key, value) in keys.iter().zip(values.iter()) {
+                assert_eq!(map.get(key), value.as_bytes());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_vfunc_map_mismatch() {
+    let mut pl = Option::<ProgressLogger>::None;
+    assert!(VFuncBuilder::<_>::default()
+        .build_map(0..10_usize, ["a", "b"], &mut pl)
+        .is_err());
+    assert!(VFuncBuilder::<_>::default()
+        .build_map([0_usize, 1, 0], ["a", "b", "c"], &mut pl)
+        .is_err());
+}